@@ -1,4 +1,8 @@
-use lib_utils::envs::{get_env_b64u_as_u8s, get_env_parse};
+use crate::keyring::Keyring;
+use crate::oidc::OidcConfig;
+use crate::token::jwt::{JwtAlg, JwtConfig};
+use crate::token::{self, TokenFormat};
+use lib_utils::envs::{get_env, get_env_parse, Error};
 use std::sync::OnceLock;
 
 pub fn auth_config() -> &'static AuthConfig {
@@ -13,20 +17,94 @@ pub fn auth_config() -> &'static AuthConfig {
 #[allow(non_snake_case)]
 pub struct AuthConfig {
     // -- Crypt
-    pub PWD_KEY: Vec<u8>,
+    pub PWD_KEYRING: Keyring,
 
-    pub TOKEN_KEY: Vec<u8>,
+    pub TOKEN_KEYRING: Keyring,
     pub TOKEN_DURATION_SEC: f64,
+    pub TOKEN_FORMAT: TokenFormat,
+
+    // -- Pwd Policy
+    pub PWD_MIN_LEN: usize,
+    pub PWD_MIN_CHAR_CLASSES: usize,
+    pub PWD_MIN_SCORE: u8,
+    pub PWD_CHECK_USERNAME: bool,
+    pub PWD_BREACHED_FILE: Option<String>, // Optional, sorted SHA-1 (HIBP style) file.
+
+    // -- Oidc (SSO login, enabled when SERVICE_OIDC_ISSUER is set)
+    pub OIDC: Option<OidcConfig>,
 }
 
 impl AuthConfig {
     fn load_from_env() -> lib_utils::envs::Result<AuthConfig> {
         Ok(AuthConfig {
             // -- Crypt
-            PWD_KEY: get_env_b64u_as_u8s("SERVICE_PWD_KEY")?,
+            PWD_KEYRING: get_env_parse("SERVICE_PWD_KEYRING")?,
 
-            TOKEN_KEY: get_env_b64u_as_u8s("SERVICE_TOKEN_KEY")?,
+            TOKEN_KEYRING: get_env_parse("SERVICE_TOKEN_KEYRING")?,
             TOKEN_DURATION_SEC: get_env_parse("SERVICE_TOKEN_DURATION_SEC")?,
+            TOKEN_FORMAT: load_token_format()?,
+
+            // -- Pwd Policy
+            PWD_MIN_LEN: get_env_parse("SERVICE_PWD_MIN_LEN")?,
+            PWD_MIN_CHAR_CLASSES: get_env_parse("SERVICE_PWD_MIN_CHAR_CLASSES")?,
+            PWD_MIN_SCORE: get_env_parse("SERVICE_PWD_MIN_SCORE")?,
+            PWD_CHECK_USERNAME: get_env_parse("SERVICE_PWD_CHECK_USERNAME")?,
+            PWD_BREACHED_FILE: get_env("SERVICE_PWD_BREACHED_FILE").ok(),
+
+            // -- Oidc
+            OIDC: load_oidc()?,
         })
     }
 }
+
+/// `SERVICE_TOKEN_FORMAT` is `custom` (default) or `jwt`.
+/// The `SERVICE_JWT_*` envs are only required for `jwt`.
+fn load_token_format() -> lib_utils::envs::Result<TokenFormat> {
+    let format = get_env("SERVICE_TOKEN_FORMAT").unwrap_or_else(|_| "custom".to_string());
+
+    match format.as_str() {
+        "custom" => Ok(TokenFormat::Custom),
+        "jwt" => {
+            let alg_name = get_env("SERVICE_JWT_ALG")?;
+            let alg = if alg_name == "HS512" {
+                JwtAlg::HS512
+            } else {
+                let read_pem = |name: &'static str| {
+                    std::fs::read(get_env(name)?).map_err(|_| Error::WrongFormat(name))
+                };
+                JwtAlg::from_pems(
+                    &alg_name,
+                    get_env("SERVICE_JWT_KEY_ID").ok(),
+                    &read_pem("SERVICE_JWT_PRIVATE_KEY_FILE")?,
+                    &read_pem("SERVICE_JWT_PUBLIC_KEY_FILE")?,
+                )
+                .map_err(|ex| match ex {
+                    token::Error::JwtAlgNotSupported(_) => Error::WrongFormat("SERVICE_JWT_ALG"),
+                    _ => Error::WrongFormat("SERVICE_JWT_PRIVATE_KEY_FILE"),
+                })?
+            };
+
+            Ok(TokenFormat::Jwt(JwtConfig {
+                alg,
+                iss: get_env("SERVICE_JWT_ISS")?,
+                aud: get_env("SERVICE_JWT_AUD")?,
+            }))
+        }
+        _ => Err(Error::WrongFormat("SERVICE_TOKEN_FORMAT")),
+    }
+}
+
+fn load_oidc() -> lib_utils::envs::Result<Option<OidcConfig>> {
+    let Ok(issuer) = get_env("SERVICE_OIDC_ISSUER") else {
+        return Ok(None);
+    };
+
+    Ok(Some(OidcConfig {
+        issuer,
+        client_id: get_env("SERVICE_OIDC_CLIENT_ID")?,
+        client_secret: get_env("SERVICE_OIDC_CLIENT_SECRET").ok(),
+        redirect_uri: get_env("SERVICE_OIDC_REDIRECT_URI")?,
+        scopes: get_env("SERVICE_OIDC_SCOPES")
+            .unwrap_or_else(|_| "openid email profile".to_string()),
+    }))
+}
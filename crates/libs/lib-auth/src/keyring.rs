@@ -0,0 +1,178 @@
+//! Keyring for key rotation (e.g., token key, pwd key).
+//!
+//! String format: `key_id:key_b64u,key_id:key_b64u,...`
+//!
+//! The first entry is the current key, used to sign/hash new content. The other
+//! entries are only used to validate content created with a previous key.
+
+// region:    --- Modules
+
+use lazy_regex::regex_is_match;
+use lib_utils::b64::b64u_decode;
+use std::str::FromStr;
+
+// endregion: --- Modules
+
+// region:    --- Types
+
+#[derive(Debug, Clone)]
+pub struct KeyEntry {
+    /// Key identifier (e.g., "k01"), restricted to `[A-Za-z0-9_-]`.
+    pub id: String,
+    pub key: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keyring {
+    /// Never empty. First entry is the current key.
+    entries: Vec<KeyEntry>,
+}
+
+// endregion: --- Types
+
+impl Keyring {
+    /// The key to be used to sign/hash new content.
+    pub fn current(&self) -> &KeyEntry {
+        &self.entries[0]
+    }
+
+    /// Get a key by its id (current or previous).
+    pub fn get(&self, id: &str) -> Option<&KeyEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// All keys, starting with the current one.
+    pub fn entries(&self) -> &[KeyEntry] {
+        &self.entries
+    }
+}
+
+impl FromStr for Keyring {
+    type Err = Error;
+
+    fn from_str(keyring_str: &str) -> Result<Self> {
+        let mut entries: Vec<KeyEntry> = Vec::new();
+
+        for entry_str in keyring_str.split(',').map(str::trim) {
+            let (id, key_b64u) = entry_str
+                .split_once(':')
+                .ok_or_else(|| Error::EntryInvalidFormat(entry_str.to_string()))?;
+
+            if !regex_is_match!(r"^[A-Za-z0-9_-]+$", id) {
+                return Err(Error::KeyIdInvalid(id.to_string()));
+            }
+            if entries.iter().any(|entry| entry.id == id) {
+                return Err(Error::KeyIdDuplicate(id.to_string()));
+            }
+
+            let key = b64u_decode(key_b64u).map_err(|_| Error::KeyNotB64u(id.to_string()))?;
+            if key.is_empty() {
+                return Err(Error::KeyEmpty(id.to_string()));
+            }
+
+            entries.push(KeyEntry {
+                id: id.to_string(),
+                key,
+            });
+        }
+
+        Ok(Keyring { entries })
+    }
+}
+
+// region:    --- Error
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    EntryInvalidFormat(String),
+    KeyIdInvalid(String),
+    KeyIdDuplicate(String),
+    KeyNotB64u(String),
+    KeyEmpty(String),
+}
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
+        write!(fmt, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
+
+// endregion: --- Error
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_keyring_from_str_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_keyring_str = "k02:a2V5LTAy, k01:a2V5LTAx";
+
+        // -- Exec
+        let keyring: Keyring = fx_keyring_str.parse()?;
+
+        // -- Check
+        assert_eq!(keyring.current().id, "k02");
+        assert_eq!(keyring.current().key, b"key-02");
+        assert_eq!(
+            keyring.get("k01").map(|e| e.key.as_slice()),
+            Some(&b"key-01"[..])
+        );
+        assert!(keyring.get("k03").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyring_from_str_err_duplicate() -> Result<()> {
+        // -- Exec
+        let res = "k01:a2V5LTAy,k01:a2V5LTAx".parse::<Keyring>();
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::KeyIdDuplicate(ref id)) if id == "k01"),
+            "Should have matched `Err(Error::KeyIdDuplicate)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyring_from_str_err_key_id_invalid() -> Result<()> {
+        // -- Exec
+        // Note: `é` is a Unicode word character, but not in `[A-Za-z0-9_-]`.
+        let res = "clé:a2V5LTAy".parse::<Keyring>();
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::KeyIdInvalid(ref id)) if id == "clé"),
+            "Should have matched `Err(Error::KeyIdInvalid)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_keyring_from_str_err_key_empty() -> Result<()> {
+        // -- Exec
+        let res = "k02:a2V5LTAy,k01:".parse::<Keyring>();
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::KeyEmpty(ref id)) if id == "k01"),
+            "Should have matched `Err(Error::KeyEmpty)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -1,4 +1,6 @@
 mod config;
+pub mod keyring;
+pub mod oidc;
 pub mod pwd;
 pub mod token;
 
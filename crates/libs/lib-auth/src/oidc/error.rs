@@ -0,0 +1,32 @@
+use serde::Serialize;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+#[derive(Debug, Serialize)]
+pub enum Error {
+    // -- Provider
+    DiscoveryFail(String),
+    IssuerNotMatching { expected: String, actual: String },
+    JwksFetchFail(String),
+    AuthorizeUrlInvalid(String),
+    TokenEndpointFail(String),
+
+    // -- Auth Request
+    AuthRequestInvalidFormat,
+
+    // -- Id Token
+    IdTokenAlgNotSupported(String),
+    IdTokenKeyNotFound { kid: Option<String> },
+    IdTokenInvalid(String),
+    NonceNotMatching,
+}
+
+// region:    --- Error Boilerplate
+impl core::fmt::Display for Error {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
+        write!(fmt, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+// endregion: --- Error Boilerplate
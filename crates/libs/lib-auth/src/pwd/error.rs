@@ -1,4 +1,4 @@
-use crate::pwd::scheme;
+use crate::pwd::{policy, scheme};
 
 use derive_more::From;
 use serde::Serialize;
@@ -8,13 +8,17 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug, From, Serialize)]
 pub enum Error {
     PwdWithSchemeFailedParse,
+    PwdKeyIdNotFound(String),
 
     FailSpawnBlocking,
     FailSpawnBlockForValidate,
+    FailSpawnBlockForPolicy,
 
     // -- Modules
     #[from]
     Scheme(scheme::Error),
+    #[from]
+    Policy(policy::Error),
 }
 
 // region:    --- Error Boilerplate
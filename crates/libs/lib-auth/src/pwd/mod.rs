@@ -1,15 +1,24 @@
 // region:    --- Modules
 
 mod error;
+pub mod policy;
 mod scheme;
 
 use std::str::FromStr;
 
 pub use self::error::{Error, Result};
+pub use policy::{PolicyViolation, PwdPolicy};
 pub use scheme::SchemeStatus;
 
+/// Histogram (seconds) of the pwd hash/validate durations,
+/// labeled by `op` ("hash" or "validate") and `scheme`.
+pub const PWD_HASH_DURATION_METRIC: &str = "pwd_hash_duration_seconds";
+
+use crate::config::auth_config;
+use crate::keyring::KeyEntry;
 use crate::pwd::scheme::{get_scheme, Scheme, DEFAULT_SCHEME};
 use lazy_regex::regex_captures;
+use std::time::Instant;
 use uuid::Uuid;
 
 // endregion: --- Modules
@@ -25,52 +34,111 @@ pub struct ContentToHash {
 
 // region:    --- Public Functions
 
-/// Hash the password with the default scheme.
+/// Hash the password with the default scheme and the current pwd key.
 pub async fn hash_pwd(to_hash: ContentToHash) -> Result<String> {
-    tokio::task::spawn_blocking(move || hash_for_scheme(DEFAULT_SCHEME, to_hash))
-        .await
-        .map_err(|_| Error::FailSpawnBlocking)?
+    tokio::task::spawn_blocking(move || {
+        hash_for_scheme(DEFAULT_SCHEME, auth_config().PWD_KEYRING.current(), to_hash)
+    })
+    .await
+    .map_err(|_| Error::FailSpawnBlocking)?
 }
 
 /// Validate if ContentToHash matches.
+///
+/// Returns `SchemeStatus::Outdated` when the pwd was hashed with an old scheme
+/// or an old key, so that the caller can re-hash it.
 pub async fn validate_pwd(to_hash: ContentToHash, pwd_ref: &str) -> Result<SchemeStatus> {
     let PwdParts {
         scheme_name,
+        key_id,
         hashed,
     } = pwd_ref.parse()?;
 
-    let scheme_status = if scheme_name == DEFAULT_SCHEME {
+    let current_key_id = &auth_config().PWD_KEYRING.current().id;
+    let scheme_status = if scheme_name == DEFAULT_SCHEME && key_id.as_ref() == Some(current_key_id)
+    {
         SchemeStatus::Ok
     } else {
         SchemeStatus::Outdated
     };
 
-    tokio::task::spawn_blocking(move || validate_for_scheme(&scheme_name, to_hash, &hashed))
-        .await
-        .map_err(|_| Error::FailSpawnBlockForValidate)??;
+    tokio::task::spawn_blocking(move || {
+        validate_for_scheme(&scheme_name, key_id.as_deref(), to_hash, &hashed)
+    })
+    .await
+    .map_err(|_| Error::FailSpawnBlockForValidate)??;
 
     Ok(scheme_status)
 }
+
+/// Check the clear password against the configured password policy.
+/// To be called before hashing a new password (e.g., registration, change, reset).
+pub async fn check_pwd_policy(pwd_clear: &str, username: &str) -> Result<()> {
+    let pwd_clear = pwd_clear.to_string();
+    let username = username.to_string();
+
+    tokio::task::spawn_blocking(move || PwdPolicy::from_config().check(&pwd_clear, &username))
+        .await
+        .map_err(|_| Error::FailSpawnBlockForPolicy)??;
+
+    Ok(())
+}
 // endregion: --- Public Functions
 
 // region:    --- Privates
 
-fn hash_for_scheme(scheme_name: &str, to_hash: ContentToHash) -> Result<String> {
+fn hash_for_scheme(scheme_name: &str, key: &KeyEntry, to_hash: ContentToHash) -> Result<String> {
     let scheme = get_scheme(scheme_name)?;
 
-    let pwd_hashed = scheme.hash(&to_hash)?;
+    let start = Instant::now();
+    let pwd_hashed = scheme.hash(&to_hash, &key.key)?;
+    record_duration("hash", scheme_name, start);
 
-    Ok(format!("#{scheme_name}#{pwd_hashed}"))
+    Ok(format!("#{scheme_name}#{}#{pwd_hashed}", key.id))
 }
 
-fn validate_for_scheme(scheme_name: &str, to_hash: ContentToHash, pwd_ref: &str) -> Result<()> {
-    get_scheme(scheme_name)?.validate(&to_hash, pwd_ref)?;
+fn validate_for_scheme(
+    scheme_name: &str,
+    key_id: Option<&str>,
+    to_hash: ContentToHash,
+    pwd_ref: &str,
+) -> Result<()> {
+    let scheme = get_scheme(scheme_name)?;
+    let keyring = &auth_config().PWD_KEYRING;
+
+    let start = Instant::now();
+    let res = match key_id {
+        Some(key_id) => {
+            let key = keyring
+                .get(key_id)
+                .ok_or_else(|| Error::PwdKeyIdNotFound(key_id.to_string()))?;
+            scheme.validate(&to_hash, pwd_ref, &key.key)
+        }
+        // Pwd hashed before the keyring (no key id), so try each key.
+        None => keyring
+            .entries()
+            .iter()
+            .find(|key| scheme.validate(&to_hash, pwd_ref, &key.key).is_ok())
+            .map(|_| ())
+            .ok_or(scheme::Error::PwdValidate),
+    };
+    record_duration("validate", scheme_name, start);
+
+    res?;
+
     Ok(())
 }
 
+fn record_duration(op: &'static str, scheme_name: &str, start: Instant) {
+    metrics::histogram!(PWD_HASH_DURATION_METRIC, "op" => op, "scheme" => scheme_name.to_string())
+        .record(start.elapsed().as_secs_f64());
+}
+
 struct PwdParts {
     /// The scheme only (e.g., "01")
     scheme_name: String,
+    /// The pwd keyring key id (None for pwds hashed before the keyring)
+    key_id: Option<String>,
     /// The hashed password,
     hashed: String,
 }
@@ -79,10 +147,12 @@ impl FromStr for PwdParts {
     type Err = Error;
 
     // This works because Result<Self> is a type alias of Result<Self, Error>
+    // Format: `#scheme_name#key_id#hashed` (or legacy `#scheme_name#hashed`)
     fn from_str(pwd_with_scheme: &str) -> Result<Self> {
-        regex_captures!(r#"^#(\w+)#(.*)"#, pwd_with_scheme)
-            .map(|(_, scheme, hashed)| Self {
+        regex_captures!(r#"^#(\w+)#(?:([\w-]+)#)?(.*)"#, pwd_with_scheme)
+            .map(|(_, scheme, key_id, hashed)| Self {
                 scheme_name: scheme.to_string(),
+                key_id: (!key_id.is_empty()).then(|| key_id.to_string()),
                 hashed: hashed.to_string(),
             })
             .ok_or(Error::PwdWithSchemeFailedParse)
@@ -106,8 +176,10 @@ mod tests {
             salt: fx_salt,
         };
 
+        let fx_key = auth_config().PWD_KEYRING.current();
+
         // -- Exec
-        let pwd_hashed = hash_for_scheme("01", fx_to_hash.clone())?;
+        let pwd_hashed = hash_for_scheme("01", fx_key, fx_to_hash.clone())?;
         let pwd_validate = validate_pwd(fx_to_hash, &pwd_hashed).await?;
 
         // -- Check
@@ -118,6 +190,75 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_current_scheme_and_key_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+
+        // -- Exec
+        let pwd_hashed = hash_pwd(fx_to_hash.clone()).await?;
+        let pwd_validate = validate_pwd(fx_to_hash, &pwd_hashed).await?;
+
+        // -- Check
+        let fx_prefix = format!("#02#{}#", auth_config().PWD_KEYRING.current().id);
+        assert!(
+            pwd_hashed.starts_with(&fx_prefix),
+            "should record the key id"
+        );
+        assert!(
+            matches!(pwd_validate, SchemeStatus::Ok),
+            "status should be SchemeStatus::Ok"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_no_key_id_outdated() -> Result<()> {
+        // -- Setup & Fixtures
+        // Pwd hashed before the keyring (`#02#hashed` format).
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+        let fx_pwd = "#02#$argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUw$TaRnmmbDdQ1aTzk2qQ2yQzPQoZfnKqhrfuTH/TRP5V4";
+
+        // -- Exec
+        let pwd_validate = validate_pwd(fx_to_hash, fx_pwd).await?;
+
+        // -- Check
+        assert!(
+            matches!(pwd_validate, SchemeStatus::Outdated),
+            "status should be SchemeStatus::Outdated"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_key_id_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_to_hash = ContentToHash {
+            content: "hello world".to_string(),
+            salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
+        };
+        let fx_pwd = "#02#k-retired#$argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUw$TaRnmmbDdQ1aTzk2qQ2yQzPQoZfnKqhrfuTH/TRP5V4";
+
+        // -- Exec
+        let res = validate_pwd(fx_to_hash, fx_pwd).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::PwdKeyIdNotFound(ref id)) if id == "k-retired"),
+            "Should have matched `Err(Error::PwdKeyIdNotFound)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
 }
 
 // endregion: --- Tests
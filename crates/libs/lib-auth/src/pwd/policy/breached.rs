@@ -0,0 +1,84 @@
+//! Offline breached-password check against a local list.
+//!
+//! The list is the "ordered by hash" SHA-1 format of haveibeenpwned.com,
+//! one `HASH_HEX_UPPER[:COUNT]` per line, sorted by hash. Since these files can be
+//! several gigabytes, the lookup is a binary search over file offsets rather
+//! than a full load.
+
+// region:    --- Modules
+
+use super::{Error, Result};
+
+use sha1::{Digest, Sha1};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+// endregion: --- Modules
+
+/// Returns true if the clear password is in the breached list file.
+pub fn is_breached(file: &Path, pwd_clear: &str) -> Result<bool> {
+    let hash = sha1_hex_upper(pwd_clear);
+
+    let mut file = File::open(file).map_err(|ex| Error::BreachedFileOpen(ex.to_string()))?;
+    let len = file
+        .metadata()
+        .map_err(|ex| Error::BreachedFileRead(ex.to_string()))?
+        .len();
+
+    // Invariant: if present, the matching line starts in [lo, hi).
+    let (mut lo, mut hi) = (0, len);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+
+        let Some((line_start, line_len, line)) = line_at_or_after(&mut file, mid)? else {
+            hi = mid;
+            continue;
+        };
+
+        let line_hash = line.split(':').next().unwrap_or_default();
+        match line_hash.cmp(hash.as_str()) {
+            Ordering::Equal => return Ok(true),
+            Ordering::Less => lo = line_start + line_len,
+            Ordering::Greater => hi = mid,
+        }
+    }
+
+    Ok(false)
+}
+
+pub(super) fn sha1_hex_upper(content: &str) -> String {
+    Sha1::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect()
+}
+
+/// Returns the first full line at or after `offset` as `(line_start, line_len, line)`,
+/// where `line_len` includes the line ending and `line` does not.
+/// `None` when there is no such line.
+fn line_at_or_after(file: &mut File, offset: u64) -> Result<Option<(u64, u64, String)>> {
+    let map_read_err = |ex: std::io::Error| Error::BreachedFileRead(ex.to_string());
+
+    // Start one byte before, so that a line starting exactly at `offset` is not skipped.
+    let seek_pos = offset.saturating_sub(1);
+    file.seek(SeekFrom::Start(seek_pos)).map_err(map_read_err)?;
+    let mut reader = BufReader::new(file);
+
+    let mut line_start = seek_pos;
+    if offset > 0 {
+        let mut skipped = Vec::new();
+        line_start += reader
+            .read_until(b'\n', &mut skipped)
+            .map_err(map_read_err)? as u64;
+    }
+
+    let mut line = String::new();
+    let line_len = reader.read_line(&mut line).map_err(map_read_err)? as u64;
+    if line_len == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some((line_start, line_len, line.trim_end().to_string())))
+}
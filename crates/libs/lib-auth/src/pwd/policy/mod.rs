@@ -0,0 +1,264 @@
+//! Password policy, to be checked whenever a new clear password is set
+//! (e.g., registration, password change, password reset).
+//!
+//! Note: All violations are collected (rather than failing at the first one),
+//!       so that the client can display them all at once.
+
+// region:    --- Modules
+
+mod breached;
+mod error;
+
+pub use self::error::{Error, Result};
+
+use crate::config::auth_config;
+use serde::Serialize;
+use std::path::PathBuf;
+
+// endregion: --- Modules
+
+// region:    --- Types
+
+#[derive(Debug, Clone)]
+pub struct PwdPolicy {
+    pub min_len: usize,
+    /// Minimum number of distinct `CharClass` in the password.
+    pub min_char_classes: usize,
+    /// Minimum zxcvbn score (0 to 4).
+    pub min_score: u8,
+    /// Reject the password when it contains the username (or vice versa).
+    pub check_username: bool,
+    /// Sorted SHA-1 breached password list (see `breached` module).
+    pub breached_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub enum PolicyViolation {
+    TooShort { min_len: usize, actual: usize },
+    NotEnoughCharClasses { min: usize, actual: usize },
+    SimilarToUsername,
+    TooWeak { min_score: u8, score: u8 },
+    Breached,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl From<char> for CharClass {
+    fn from(c: char) -> Self {
+        if c.is_lowercase() {
+            CharClass::Lower
+        } else if c.is_uppercase() {
+            CharClass::Upper
+        } else if c.is_numeric() {
+            CharClass::Digit
+        } else {
+            CharClass::Symbol
+        }
+    }
+}
+
+// endregion: --- Types
+
+impl PwdPolicy {
+    pub fn from_config() -> Self {
+        let config = auth_config();
+        PwdPolicy {
+            min_len: config.PWD_MIN_LEN,
+            min_char_classes: config.PWD_MIN_CHAR_CLASSES,
+            min_score: config.PWD_MIN_SCORE,
+            check_username: config.PWD_CHECK_USERNAME,
+            breached_file: config.PWD_BREACHED_FILE.as_ref().map(PathBuf::from),
+        }
+    }
+
+    /// Check the clear password against this policy.
+    /// Returns `Error::Violations` with all of the violations if any.
+    ///
+    /// Note: This is blocking (entropy estimation and breached file lookup).
+    pub fn check(&self, pwd_clear: &str, username: &str) -> Result<()> {
+        let mut violations = Vec::new();
+
+        // -- Length
+        let len = pwd_clear.chars().count();
+        if len < self.min_len {
+            violations.push(PolicyViolation::TooShort {
+                min_len: self.min_len,
+                actual: len,
+            });
+        }
+
+        // -- Character classes
+        let mut classes: Vec<CharClass> = Vec::new();
+        for class in pwd_clear.chars().map(CharClass::from) {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        if classes.len() < self.min_char_classes {
+            violations.push(PolicyViolation::NotEnoughCharClasses {
+                min: self.min_char_classes,
+                actual: classes.len(),
+            });
+        }
+
+        // -- Username similarity
+        if self.check_username && is_similar(pwd_clear, username) {
+            violations.push(PolicyViolation::SimilarToUsername);
+        }
+
+        // -- Entropy score
+        let score: u8 = zxcvbn::zxcvbn(pwd_clear, &[username]).score().into();
+        if score < self.min_score {
+            violations.push(PolicyViolation::TooWeak {
+                min_score: self.min_score,
+                score,
+            });
+        }
+
+        // -- Breached list
+        if let Some(breached_file) = &self.breached_file {
+            if breached::is_breached(breached_file, pwd_clear)? {
+                violations.push(PolicyViolation::Breached);
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Violations(violations))
+        }
+    }
+}
+
+fn is_similar(pwd_clear: &str, username: &str) -> bool {
+    let pwd = pwd_clear.to_lowercase();
+    let username = username.to_lowercase();
+
+    if pwd.is_empty() || username.is_empty() {
+        return false;
+    }
+
+    pwd.contains(&username) || username.contains(&pwd)
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::fs;
+
+    fn fx_policy() -> PwdPolicy {
+        PwdPolicy {
+            min_len: 10,
+            min_char_classes: 3,
+            min_score: 3,
+            check_username: true,
+            breached_file: None,
+        }
+    }
+
+    #[test]
+    fn test_check_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let policy = fx_policy();
+
+        // -- Exec & Check
+        policy.check("Correct-Horse-Battery-42", "demo1")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_err_violations() -> Result<()> {
+        // -- Setup & Fixtures
+        let policy = fx_policy();
+
+        // -- Exec
+        let res = policy.check("welcome", "demo1");
+
+        // -- Check
+        let Err(Error::Violations(violations)) = res else {
+            panic!("Should have been `Err(Error::Violations(..))` but was `{res:?}`");
+        };
+        assert!(matches!(
+            violations[0],
+            PolicyViolation::TooShort {
+                min_len: 10,
+                actual: 7
+            }
+        ));
+        assert!(matches!(
+            violations[1],
+            PolicyViolation::NotEnoughCharClasses { min: 3, actual: 1 }
+        ));
+        assert!(matches!(violations[2], PolicyViolation::TooWeak { .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_err_similar_to_username() -> Result<()> {
+        // -- Setup & Fixtures
+        let policy = fx_policy();
+
+        // -- Exec
+        let res = policy.check("Demo1-Horse-Battery-42", "demo1");
+
+        // -- Check
+        let Err(Error::Violations(violations)) = res else {
+            panic!("Should have been `Err(Error::Violations(..))` but was `{res:?}`");
+        };
+        assert!(violations
+            .iter()
+            .any(|v| matches!(v, PolicyViolation::SimilarToUsername)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_err_breached() -> Result<()> {
+        // -- Setup & Fixtures
+        // SHA-1 of "Correct-Horse-Battery-42" surrounded by other sorted hashes.
+        let fx_pwd = "Correct-Horse-Battery-42";
+        let fx_hash = breached::sha1_hex_upper(fx_pwd);
+        let mut fx_lines = [
+            "0000000A2DC610C7A5F3E6B7C1C2E6F6A6D6E0A1:12".to_string(),
+            "7C4A8D09CA3762AF61E59520943DC26494F8941B:24230577".to_string(),
+            "FFFFFFF0AFAF1D8C7D7E7C3C1B7E8D5AE1F7D2C3:1".to_string(),
+            format!("{fx_hash}:3"),
+        ];
+        fx_lines.sort();
+        let fx_file = std::env::temp_dir().join("test_check_err_breached.txt");
+        fs::write(&fx_file, fx_lines.join("\r\n"))?;
+        let policy = PwdPolicy {
+            breached_file: Some(fx_file.clone()),
+            ..fx_policy()
+        };
+
+        // -- Exec
+        let res = policy.check(fx_pwd, "demo1");
+        let res_other = policy.check("Other-Horse-Battery-43", "demo1");
+
+        // -- Check
+        let Err(Error::Violations(violations)) = res else {
+            panic!("Should have been `Err(Error::Violations(..))` but was `{res:?}`");
+        };
+        assert!(matches!(violations[..], [PolicyViolation::Breached]));
+        res_other?;
+
+        // -- Cleanup
+        fs::remove_file(fx_file)?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
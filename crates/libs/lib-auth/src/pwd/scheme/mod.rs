@@ -16,15 +16,16 @@ pub const DEFAULT_SCHEME: &str = "02";
 
 #[derive(Debug)]
 pub enum SchemeStatus {
-    Ok,       // The pwd uses the latest scheme. All good.
-    Outdated, // The pwd uses an old scheme
+    Ok,       // The pwd uses the latest scheme and current key. All good.
+    Outdated, // The pwd uses an old scheme or an old key.
 }
 
+/// Note: The `key` is the pepper key from the `PWD_KEYRING` entry used for this hash.
 #[enum_dispatch]
 pub trait Scheme {
-    fn hash(&self, to_hash: &ContentToHash) -> Result<String>;
+    fn hash(&self, to_hash: &ContentToHash, key: &[u8]) -> Result<String>;
 
-    fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()>;
+    fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str, key: &[u8]) -> Result<()>;
 }
 
 #[enum_dispatch(Scheme)]
@@ -1,7 +1,6 @@
 // region:    --- Modules
 
 use super::{Error, Result};
-use crate::config::auth_config;
 use crate::pwd::ContentToHash;
 
 use hmac::{Hmac, Mac};
@@ -15,14 +14,13 @@ use crate::pwd::scheme::Scheme;
 pub struct Scheme01;
 
 impl Scheme for Scheme01 {
-    fn hash(&self, to_hash: &ContentToHash) -> Result<String> {
-        let key = &auth_config().PWD_KEY;
+    fn hash(&self, to_hash: &ContentToHash, key: &[u8]) -> Result<String> {
         hash(key, to_hash)
     }
 
     /// Validate if an ContentToHash matches.
-    fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str) -> Result<()> {
-        let raw_pwd = self.hash(to_hash)?;
+    fn validate(&self, to_hash: &ContentToHash, pwd_ref: &str, key: &[u8]) -> Result<()> {
+        let raw_pwd = self.hash(to_hash, key)?;
 
         if raw_pwd == pwd_ref {
             Ok(())
@@ -54,6 +52,7 @@ fn hash(key: &[u8], to_hash: &ContentToHash) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::auth_config;
     use anyhow::Result;
     use uuid::Uuid;
 
@@ -61,7 +60,7 @@ mod tests {
     fn test_scheme_01_hash_into_b64u_ok() -> Result<()> {
         // -- Setup & Fixtures
         let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?;
-        let fx_key = &auth_config().PWD_KEY; // 512 bits = 64 bytes
+        let fx_key = &auth_config().PWD_KEYRING.current().key; // 512 bits = 64 bytes
         let fx_to_hash = ContentToHash {
             content: "hello world".to_string(),
             salt: fx_salt,
@@ -1,18 +1,16 @@
-use std::sync::OnceLock;
-
 use argon2::{
     password_hash::SaltString, Argon2, Params, PasswordHash, PasswordHasher as _,
     PasswordVerifier as _,
 };
 
 use super::{Error, Result};
-use crate::{config::auth_config, pwd::scheme::Scheme};
+use crate::pwd::scheme::Scheme;
 
 pub struct Scheme02;
 
 impl Scheme for Scheme02 {
-    fn hash(&self, to_hash: &crate::pwd::ContentToHash) -> Result<String> {
-        let argon2 = get_argon2();
+    fn hash(&self, to_hash: &crate::pwd::ContentToHash, key: &[u8]) -> Result<String> {
+        let argon2 = new_argon2(key)?;
 
         let salt_b64 = SaltString::encode_b64(to_hash.salt.as_bytes()).map_err(|_| Error::Salt)?;
 
@@ -24,8 +22,13 @@ impl Scheme for Scheme02 {
         Ok(pwd)
     }
 
-    fn validate(&self, to_hash: &crate::pwd::ContentToHash, pwd_ref: &str) -> Result<()> {
-        let argon2 = get_argon2();
+    fn validate(
+        &self,
+        to_hash: &crate::pwd::ContentToHash,
+        pwd_ref: &str,
+        key: &[u8],
+    ) -> Result<()> {
+        let argon2 = new_argon2(key)?;
 
         // Note all the config is stored in pwd_ref, including the salt. This is different from Scheme01
         let parsed_hash_ref = PasswordHash::new(pwd_ref).map_err(|_| Error::Hash)?;
@@ -36,19 +39,16 @@ impl Scheme for Scheme02 {
     }
 }
 
-fn get_argon2() -> &'static Argon2<'static> {
-    static INSTANCE: OnceLock<Argon2<'static>> = OnceLock::new();
-
-    INSTANCE.get_or_init(|| {
-        let key = &auth_config().PWD_KEY;
-        Argon2::new_with_secret(
-            key,
-            argon2::Algorithm::Argon2id, // Same as Argon2::default
-            argon2::Version::V0x13,      // Same as Argon2::default()
-            Params::default(),
-        )
-        .unwrap() // TODO - needs to fail early
-    })
+// Note: Cheap to create (no allocation), so one per key use rather than cached
+//       since the key depends on the keyring entry of the pwd.
+fn new_argon2(key: &[u8]) -> Result<Argon2<'_>> {
+    Argon2::new_with_secret(
+        key,
+        argon2::Algorithm::Argon2id, // Same as Argon2::default
+        argon2::Version::V0x13,      // Same as Argon2::default()
+        Params::default(),
+    )
+    .map_err(|_| Error::Key)
 }
 
 #[cfg(test)]
@@ -56,6 +56,7 @@ mod tests {
     use crate::pwd::ContentToHash;
 
     use super::*;
+    use crate::config::auth_config;
     use anyhow::Result;
     use uuid::Uuid;
 
@@ -66,11 +67,12 @@ mod tests {
             content: "hello world".to_string(),
             salt: Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453")?,
         };
+        let fx_key = &auth_config().PWD_KEYRING.current().key;
         let fx_res = "$argon2id$v=19$m=19456,t=2,p=1$8F6JYdatQIaeeKbeBl5UUw$TaRnmmbDdQ1aTzk2qQ2yQzPQoZfnKqhrfuTH/TRP5V4";
 
         // -- Exec
         let scheme = Scheme02;
-        let res = scheme.hash(&fx_to_hash)?;
+        let res = scheme.hash(&fx_to_hash, fx_key)?;
 
         // -- Check
         assert_eq!(res, fx_res);
@@ -0,0 +1,103 @@
+//! Signed CSRF token, bound to the server side user session.
+//!
+//! String format: `key_id.sign_b64u`, where the signature is the HMAC-SHA-512
+//! (`TOKEN_KEYRING` key) of `csrf.{session_id}`.
+//!
+//! Note: Stateless, since the session id is already part of the (validated) web token.
+
+// region:    --- Modules
+
+use super::{Error, Result};
+use crate::config::auth_config;
+use crate::keyring::{KeyEntry, Keyring};
+use hmac::{Hmac, Mac};
+use lib_utils::b64::b64u_encode;
+use sha2::Sha512;
+
+// endregion: --- Modules
+
+/// Generate the CSRF token of a session, signed with the current `TOKEN_KEYRING` key.
+pub fn generate_csrf_token(session_id: i64) -> Result<String> {
+    _generate_csrf_token(session_id, auth_config().TOKEN_KEYRING.current())
+}
+
+/// Validate the CSRF token against the session it should be bound to.
+pub fn validate_csrf_token(csrf_token: &str, session_id: i64) -> Result<()> {
+    _validate_csrf_token(csrf_token, session_id, &auth_config().TOKEN_KEYRING)
+}
+
+// region:    --- (Private) Gen and Validation
+
+fn _generate_csrf_token(session_id: i64, key: &KeyEntry) -> Result<String> {
+    let sign_b64u = _csrf_sign_into_b64u(session_id, &key.key)?;
+
+    Ok(format!("{}.{sign_b64u}", key.id))
+}
+
+fn _validate_csrf_token(csrf_token: &str, session_id: i64, keyring: &Keyring) -> Result<()> {
+    let (key_id, sign_b64u) = csrf_token.split_once('.').ok_or(Error::InvalidFormat)?;
+    let key = keyring
+        .get(key_id)
+        .ok_or_else(|| Error::KeyIdNotFound(key_id.to_string()))?;
+
+    if _csrf_sign_into_b64u(session_id, &key.key)? != sign_b64u {
+        return Err(Error::SignatureNotMatching);
+    }
+
+    Ok(())
+}
+
+fn _csrf_sign_into_b64u(session_id: i64, key: &[u8]) -> Result<String> {
+    let mut hmac_sha512 =
+        Hmac::<Sha512>::new_from_slice(key).map_err(|_| Error::HmacFailNewFromSlice)?;
+    hmac_sha512.update(format!("csrf.{session_id}").as_bytes());
+
+    Ok(b64u_encode(hmac_sha512.finalize().into_bytes()))
+}
+
+// endregion: --- (Private) Gen and Validation
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_csrf_token_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_keyring: Keyring = "k02:a2V5LTAy,k01:a2V5LTAx".parse()?;
+        let fx_token_previous_key = _generate_csrf_token(1000, fx_keyring.get("k01").unwrap())?;
+
+        // -- Exec
+        let csrf_token = _generate_csrf_token(1000, fx_keyring.current())?;
+
+        // -- Check
+        assert!(csrf_token.starts_with("k02."));
+        _validate_csrf_token(&csrf_token, 1000, &fx_keyring)?;
+        _validate_csrf_token(&fx_token_previous_key, 1000, &fx_keyring)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_csrf_token_err_other_session() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_keyring: Keyring = "k01:a2V5LTAx".parse()?;
+        let fx_csrf_token = _generate_csrf_token(1000, fx_keyring.current())?;
+
+        // -- Exec
+        let res = _validate_csrf_token(&fx_csrf_token, 1001, &fx_keyring);
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::SignatureNotMatching)),
+            "Should have matched `Err(Error::SignatureNotMatching)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -5,14 +5,23 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Debug, Serialize)]
 pub enum Error {
     HmacFailNewFromSlice,
+    KeyIdNotFound(String),
 
     // -- Token
     InvalidFormat,
     CannotDecodeIdent,
+    CannotDecodeSessionId,
     CannotDecodeExp,
     SignatureNotMatching,
     ExpNotIso,
     Expired,
+
+    // -- Jwt
+    JwtAlgNotSupported(String),
+    JwtKeyInvalid(String),
+    JwtEncode(String),
+    JwtDecode(String),
+    JwtSaltNotMatching,
 }
 
 // region:         --- Error Boilerplate
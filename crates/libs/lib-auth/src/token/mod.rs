@@ -1,10 +1,15 @@
 // region:    --- Modules
 
+mod csrf;
 mod error;
+pub mod jwt;
 
+pub use self::csrf::{generate_csrf_token, validate_csrf_token};
 pub use self::error::{Error, Result};
 
 use crate::config::auth_config;
+use crate::keyring::KeyEntry;
+use crate::token::jwt::{JwtConfig, JwtToken};
 use hmac::{Hmac, Mac};
 use lib_utils::b64::{b64u_decode_to_string, b64u_encode};
 use lib_utils::time::{now_utc, now_utc_plus_sec_str, parse_utc};
@@ -17,11 +22,13 @@ use uuid::Uuid;
 
 // region:         — Token Type
 
-/// String format: `ident_b64u.exp_b64u.sign_b64u`
+/// String format: `key_id.ident_b64u.session_id.exp_b64u.sign_b64u`
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct Token {
+    pub key_id: String,    // Id of the TOKEN_KEYRING key used to sign.
     pub ident: String,     // Identifier (username, email, etc.)
+    pub session_id: i64,   // Server side user session id.
     pub exp: String,       // Expiration date in RFC3339.
     pub sign_b64u: String, // Signature in base64url.
 }
@@ -31,14 +38,21 @@ impl FromStr for Token {
 
     fn from_str(token_str: &str) -> std::result::Result<Self, Self::Err> {
         let splits: Vec<&str> = token_str.split('.').collect();
-        if splits.len() != 3 {
+        if splits.len() != 5 {
             return Err(Error::InvalidFormat);
         }
-        let (ident_b64u, exp_b64u, sign_b64u) = (splits[0], splits[1], splits[2]);
+        let (key_id, ident_b64u, session_id, exp_b64u, sign_b64u) =
+            (splits[0], splits[1], splits[2], splits[3], splits[4]);
 
         Ok(Self {
+            key_id: key_id.to_string(),
+
             ident: b64u_decode_to_string(ident_b64u).map_err(|_| Error::CannotDecodeIdent)?,
 
+            session_id: session_id
+                .parse()
+                .map_err(|_| Error::CannotDecodeSessionId)?,
+
             exp: b64u_decode_to_string(exp_b64u).map_err(|_| Error::CannotDecodeExp)?,
 
             sign_b64u: sign_b64u.to_string(),
@@ -50,8 +64,10 @@ impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}.{}.{}",
+            "{}.{}.{}.{}.{}",
+            self.key_id,
             b64u_encode(&self.ident),
+            self.session_id,
             b64u_encode(&self.exp),
             self.sign_b64u
         )
@@ -60,18 +76,109 @@ impl Display for Token {
 
 // endregion:      — Token Type
 
+// region:         — Web Token Type
+
+/// Web token format, selected with `SERVICE_TOKEN_FORMAT`.
+pub enum TokenFormat {
+    /// `Token` format (default).
+    Custom,
+    /// Standard JWT (see `jwt` module).
+    Jwt(JwtConfig),
+}
+
+/// Web token of the configured `TokenFormat`.
+#[derive(Debug)]
+pub enum WebToken {
+    Custom(Token),
+    Jwt(JwtToken),
+}
+
+impl WebToken {
+    /// Identifier (username, email, etc.). Not trusted before validation.
+    pub fn ident(&self) -> &str {
+        match self {
+            WebToken::Custom(token) => &token.ident,
+            WebToken::Jwt(token) => &token.claims.sub,
+        }
+    }
+
+    /// Server side user session id. Not trusted before validation.
+    pub fn session_id(&self) -> i64 {
+        match self {
+            WebToken::Custom(token) => token.session_id,
+            WebToken::Jwt(token) => token.claims.sid,
+        }
+    }
+}
+
+impl FromStr for WebToken {
+    type Err = Error;
+
+    fn from_str(token_str: &str) -> std::result::Result<Self, Self::Err> {
+        match auth_config().TOKEN_FORMAT {
+            TokenFormat::Custom => token_str.parse().map(WebToken::Custom),
+            TokenFormat::Jwt(_) => token_str.parse().map(WebToken::Jwt),
+        }
+    }
+}
+
+impl Display for WebToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebToken::Custom(token) => token.fmt(f),
+            WebToken::Jwt(token) => token.fmt(f),
+        }
+    }
+}
+
+// endregion:      — Web Token Type
+
 // region:         — Web Token Gen and Validation
 
-pub fn generate_web_token(user: &str, salt: Uuid) -> Result<Token> {
+/// Generate a web token of the configured `TokenFormat`, signed with
+/// the current `TOKEN_KEYRING` key (or the JWT key pair).
+pub fn generate_web_token(user: &str, session_id: i64, salt: Uuid) -> Result<WebToken> {
     let config = &auth_config();
-    _generate_token(user, config.TOKEN_DURATION_SEC, salt, &config.TOKEN_KEY)
+    match &config.TOKEN_FORMAT {
+        TokenFormat::Custom => _generate_token(
+            user,
+            session_id,
+            config.TOKEN_DURATION_SEC,
+            salt,
+            config.TOKEN_KEYRING.current(),
+        )
+        .map(WebToken::Custom),
+        TokenFormat::Jwt(jwt_config) => jwt::generate_token(
+            jwt_config,
+            &config.TOKEN_KEYRING,
+            user,
+            session_id,
+            config.TOKEN_DURATION_SEC,
+            salt,
+        )
+        .map(WebToken::Jwt),
+    }
 }
 
-pub fn validate_web_token(orig_token: &Token, salt: Uuid) -> Result<()> {
+/// Validate a web token against the `TOKEN_KEYRING` key it was signed with
+/// (current or previous key), or the JWT key pair.
+pub fn validate_web_token(orig_token: &WebToken, salt: Uuid) -> Result<()> {
     let config = &auth_config();
-    _validate_token_sign_and_exp(orig_token, salt, &config.TOKEN_KEY)?;
-
-    Ok(())
+    match (orig_token, &config.TOKEN_FORMAT) {
+        (WebToken::Custom(token), _) => {
+            let key = config
+                .TOKEN_KEYRING
+                .get(&token.key_id)
+                .ok_or_else(|| Error::KeyIdNotFound(token.key_id.to_string()))?;
+
+            _validate_token_sign_and_exp(token, salt, &key.key)
+        }
+        (WebToken::Jwt(token), TokenFormat::Jwt(jwt_config)) => {
+            jwt::validate_token(jwt_config, &config.TOKEN_KEYRING, token, salt)
+        }
+        // JWT parsed while the format was switched back to custom.
+        (WebToken::Jwt(_), TokenFormat::Custom) => Err(Error::InvalidFormat),
+    }
 }
 
 // endregion:      — Web Token Gen and Validation
@@ -79,16 +186,25 @@ pub fn validate_web_token(orig_token: &Token, salt: Uuid) -> Result<()> {
 // region:         — (Private) Token Gen and Validation
 
 // private functions are underscored (preference)
-fn _generate_token(ident: &str, duration_sec: f64, salt: Uuid, key: &[u8]) -> Result<Token> {
-    // -- Compute the two first components.
+fn _generate_token(
+    ident: &str,
+    session_id: i64,
+    duration_sec: f64,
+    salt: Uuid,
+    key: &KeyEntry,
+) -> Result<Token> {
+    // -- Compute the three first components.
+    let key_id = key.id.to_string();
     let ident = ident.to_string();
     let exp = now_utc_plus_sec_str(duration_sec);
 
-    // -- Sign the two first components
-    let sign_b64u = _token_sign_into_b64u(&ident, &exp, salt, key)?;
+    // -- Sign the three first components
+    let sign_b64u = _token_sign_into_b64u(&key_id, &ident, session_id, &exp, salt, &key.key)?;
 
     Ok(Token {
+        key_id,
         ident,
+        session_id,
         exp,
         sign_b64u,
     })
@@ -96,7 +212,14 @@ fn _generate_token(ident: &str, duration_sec: f64, salt: Uuid, key: &[u8]) -> Re
 
 fn _validate_token_sign_and_exp(orig_token: &Token, salt: Uuid, key: &[u8]) -> Result<()> {
     // -- Validate signature.
-    let new_sign_b64u = _token_sign_into_b64u(&orig_token.ident, &orig_token.exp, salt, key)?;
+    let new_sign_b64u = _token_sign_into_b64u(
+        &orig_token.key_id,
+        &orig_token.ident,
+        orig_token.session_id,
+        &orig_token.exp,
+        salt,
+        key,
+    )?;
 
     if new_sign_b64u != orig_token.sign_b64u {
         return Err(Error::SignatureNotMatching);
@@ -114,8 +237,19 @@ fn _validate_token_sign_and_exp(orig_token: &Token, salt: Uuid, key: &[u8]) -> R
 }
 
 /// create token signature from token parts and salt.
-fn _token_sign_into_b64u(ident: &str, exp: &str, salt: Uuid, key: &[u8]) -> Result<String> {
-    let content = format!("{}.{}", b64u_encode(ident), b64u_encode(exp));
+fn _token_sign_into_b64u(
+    key_id: &str,
+    ident: &str,
+    session_id: i64,
+    exp: &str,
+    salt: Uuid,
+    key: &[u8],
+) -> Result<String> {
+    let content = format!(
+        "{key_id}.{}.{session_id}.{}",
+        b64u_encode(ident),
+        b64u_encode(exp)
+    );
 
     // -- Create a HMAC-SHA-512 from key.
     let mut hmac_sha512 =
@@ -143,14 +277,18 @@ mod tests {
     use std::{thread, time::Duration};
 
     use super::*;
+    use crate::keyring::Keyring;
     use anyhow::Result;
 
     #[test]
     fn test_token_display_ok() -> Result<()> {
         // -- Setup & Fixture
-        let fx_token_str = "ZngtaWRlbnQtMDE.MjAyMS0wMS0wMVQwMDowMDowMFo.some-sign-b64u-encoded";
+        let fx_token_str =
+            "k01.ZngtaWRlbnQtMDE.1000.MjAyMS0wMS0wMVQwMDowMDowMFo.some-sign-b64u-encoded";
         let fx_token = Token {
+            key_id: "k01".to_string(),
             ident: "fx-ident-01".to_string(),
+            session_id: 1000,
             exp: "2021-01-01T00:00:00Z".to_string(),
             sign_b64u: "some-sign-b64u-encoded".to_string(),
         };
@@ -164,9 +302,12 @@ mod tests {
     #[test]
     fn test_token_from_str_ok() -> Result<()> {
         // -- Fixtures
-        let fx_token_str = "ZngtaWRlbnQtMDE.MjAyMS0wMS0wMVQwMDowMDowMFo.some-sign-b64u-encoded";
+        let fx_token_str =
+            "k01.ZngtaWRlbnQtMDE.1000.MjAyMS0wMS0wMVQwMDowMDowMFo.some-sign-b64u-encoded";
         let fx_token = Token {
+            key_id: "k01".to_string(),
             ident: "fx-ident-01".to_string(),
+            session_id: 1000,
             exp: "2021-01-01T00:00:00Z".to_string(),
             sign_b64u: "some-sign-b64u-encoded".to_string(),
         };
@@ -187,12 +328,12 @@ mod tests {
         let fx_user = "user_one";
         let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453").unwrap();
         let fx_duration_sec = 0.02; // 20ms
-        let token_key = &auth_config().TOKEN_KEY;
-        let fx_token = _generate_token(fx_user, fx_duration_sec, fx_salt, token_key)?;
+        let token_key = auth_config().TOKEN_KEYRING.current();
+        let fx_token = _generate_token(fx_user, 1000, fx_duration_sec, fx_salt, token_key)?;
 
         // -- Exec
         thread::sleep(Duration::from_millis(10));
-        let res = validate_web_token(&fx_token, fx_salt);
+        let res = validate_web_token(&WebToken::Custom(fx_token), fx_salt);
 
         // -- Check
         res?;
@@ -206,12 +347,12 @@ mod tests {
         let fx_user = "user_one";
         let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453").unwrap();
         let fx_duration_sec = 0.01; // 10ms
-        let token_key = &auth_config().TOKEN_KEY;
-        let fx_token = _generate_token(fx_user, fx_duration_sec, fx_salt, token_key)?;
+        let token_key = auth_config().TOKEN_KEYRING.current();
+        let fx_token = _generate_token(fx_user, 1000, fx_duration_sec, fx_salt, token_key)?;
 
         // -- Exec
         thread::sleep(Duration::from_millis(20));
-        let res = validate_web_token(&fx_token, fx_salt);
+        let res = validate_web_token(&WebToken::Custom(fx_token), fx_salt);
 
         // -- Check
         assert!(
@@ -221,5 +362,49 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_validate_web_token_previous_key_ok() -> Result<()> {
+        // -- Setup and Fixtures
+        let fx_user = "user_one";
+        let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453").unwrap();
+        let fx_keyring: Keyring = "k02:a2V5LTAy,k01:a2V5LTAx".parse()?;
+        let fx_key_previous = fx_keyring.get("k01").unwrap();
+        let fx_token = _generate_token(fx_user, 1000, 10., fx_salt, fx_key_previous)?;
+
+        // -- Exec
+        let res = _validate_token_sign_and_exp(&fx_token, fx_salt, &fx_key_previous.key);
+        let res_current_key =
+            _validate_token_sign_and_exp(&fx_token, fx_salt, &fx_keyring.current().key);
+
+        // -- Check
+        assert_eq!(fx_token.key_id, "k01");
+        res?;
+        assert!(
+            matches!(res_current_key, Err(Error::SignatureNotMatching)),
+            "Should have matched `Err(Error::SignatureNotMatching)` but was `{res_current_key:?}`"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_web_token_err_unknown_key_id() -> Result<()> {
+        // -- Setup and Fixtures
+        let fx_salt = Uuid::parse_str("f05e8961-d6ad-4086-9e78-a6de065e5453").unwrap();
+        let fx_keyring: Keyring = "k-retired:a2V5LTAx".parse()?;
+        let fx_token = _generate_token("user_one", 1000, 10., fx_salt, fx_keyring.current())?;
+
+        // -- Exec
+        let res = validate_web_token(&WebToken::Custom(fx_token), fx_salt);
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::KeyIdNotFound(ref id)) if id == "k-retired"),
+            "Should have matched `Err(Error::KeyIdNotFound)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
 }
 // endregion: --- Tests
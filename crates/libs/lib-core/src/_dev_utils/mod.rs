@@ -30,17 +30,14 @@ pub async fn init_dev() {
 }
 
 /// Initialize environment for testing.
+///
+/// Note: The dev db is initialized once, but each call gets its own `ModelManager`
+///       since each `#[tokio::test]` runs on its own runtime, and pool connections
+///       cannot outlive the runtime that created them.
 pub async fn init_test() -> ModelManager {
-    static INIT: OnceCell<ModelManager> = OnceCell::const_new();
+    init_dev().await;
 
-    let mm = INIT
-        .get_or_init(|| async {
-            init_dev().await;
-            ModelManager::new().await.unwrap()
-        })
-        .await;
-
-    mm.clone()
+    ModelManager::new().await.unwrap()
 }
 
 pub async fn seed_tasks(ctx: &Ctx, mm: &ModelManager, titles: &[&str]) -> model::Result<Vec<Task>> {
@@ -52,6 +49,7 @@ pub async fn seed_tasks(ctx: &Ctx, mm: &ModelManager, titles: &[&str]) -> model:
             mm,
             TaskForCreate {
                 title: title.to_string(),
+                ..Default::default()
             },
         )
         .await?;
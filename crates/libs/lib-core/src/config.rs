@@ -1,4 +1,4 @@
-use lib_utils::envs::get_env;
+use lib_utils::envs::{get_env, get_env_parse};
 use std::sync::OnceLock;
 
 pub fn core_config() -> &'static CoreConfig {
@@ -15,8 +15,12 @@ pub struct CoreConfig {
     // -- Db
     pub DB_URL: String,
 
-    // -- Web
-    pub WEB_FOLDER: String,
+    // -- User Session
+    pub SESSION_IDLE_TIMEOUT_SEC: i64, // Sessions not seen for longer are expired (and pruned).
+    pub SESSION_TOUCH_INTERVAL_SEC: i64, // Min time between two `last_seen` updates.
+
+    // -- Task
+    pub TASK_OPEN_CHILDREN_ON_DONE: String, // Parsed by the task model (`OpenChildrenPolicy`).
 }
 
 impl CoreConfig {
@@ -25,8 +29,13 @@ impl CoreConfig {
             // -- Db
             DB_URL: get_env("SERVICE_DB_URL")?,
 
-            // -- Web
-            WEB_FOLDER: get_env("SERVICE_WEB_FOLDER")?,
+            // -- User Session
+            SESSION_IDLE_TIMEOUT_SEC: get_env_parse("SERVICE_SESSION_IDLE_TIMEOUT_SEC")?,
+            SESSION_TOUCH_INTERVAL_SEC: get_env_parse("SERVICE_SESSION_TOUCH_INTERVAL_SEC")?,
+
+            // -- Task
+            TASK_OPEN_CHILDREN_ON_DONE: get_env("SERVICE_TASK_OPEN_CHILDREN_ON_DONE")
+                .unwrap_or_else(|_| "fail".to_string()),
         })
     }
 }
@@ -4,26 +4,53 @@ mod error;
 
 pub use self::error::{Error, Result};
 
+use uuid::Uuid;
+
 // endregion:      ---Modules
 
 #[derive(Debug, Clone)]
 pub struct Ctx {
     user_id: i64,
+
+    /// Server side user session of the request (when authenticated by web token).
+    session_id: Option<i64>,
+
+    /// Request id (`X-Request-Id`) of the request (when in a web request).
+    req_id: Option<Uuid>,
 }
 
 // Constructor
 impl Ctx {
     pub fn root_ctx() -> Self {
-        Ctx { user_id: 0 }
+        Ctx {
+            user_id: 0,
+            session_id: None,
+            req_id: None,
+        }
     }
 
     pub fn new(user_id: i64) -> Result<Self> {
         if user_id == 0 {
-            return Err(Error::CtxCannotNewRootCtx);
+            Err(Error::CtxCannotNewRootCtx)
         } else {
-            Ok(Self { user_id })
+            Ok(Self {
+                user_id,
+                session_id: None,
+                req_id: None,
+            })
         }
     }
+
+    pub fn new_for_session(user_id: i64, session_id: i64) -> Result<Self> {
+        let mut ctx = Self::new(user_id)?;
+        ctx.session_id = Some(session_id);
+        Ok(ctx)
+    }
+
+    pub fn with_req_id(mut self, req_id: Uuid) -> Self {
+        self.req_id = Some(req_id);
+        self
+    }
 }
 
 // Property Accessors
@@ -31,4 +58,12 @@ impl Ctx {
     pub fn user_id(&self) -> i64 {
         self.user_id
     }
+
+    pub fn session_id(&self) -> Option<i64> {
+        self.session_id
+    }
+
+    pub fn req_id(&self) -> Option<Uuid> {
+        self.req_id
+    }
 }
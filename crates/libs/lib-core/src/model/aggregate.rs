@@ -0,0 +1,228 @@
+//! Aggregation (group-by) query types for `base::aggregate`.
+//!
+//! - Group bys are column names, optionally truncated to a time unit for
+//!   the timestamp and date columns (e.g., `"done"`, `"ctime:day"`).
+//! - Aggregates are `"count"`, or `"count:col"`, `"min:col"`, `"max:col"`.
+//! - The columns are whitelisted with the entity `Fields` names.
+//! - Each result row is a JSON object with the group by columns and the aggregates
+//!   (named `count`, or `{fn}_{col}`, e.g., `max_version`).
+//! - The groups are limited (`truncated` is true when there are more).
+
+use crate::model::{Error, Result};
+use sea_query::{Alias, Asterisk, Expr, Func, SimpleExpr};
+use serde::Serialize;
+use serde_json::Value;
+use serde_with::DeserializeFromStr;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The pg types that can be truncated to a time unit.
+const TRUNC_TYPES: &[&str] = &["timestamptz", "timestamp", "date"];
+
+/// The aggregated groups (in the group bys order).
+#[derive(Debug, Serialize)]
+pub struct Aggregation {
+    pub groups: Vec<Value>,
+    /// True when there are more groups than the limit (only the first ones are returned).
+    pub truncated: bool,
+}
+
+// region:    --- GroupBy
+
+#[derive(Debug, Clone, DeserializeFromStr)]
+pub struct GroupBy {
+    pub col: String,
+    pub trunc: Option<TimeTrunc>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TimeTrunc {
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeTrunc {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TimeTrunc::Hour => "hour",
+            TimeTrunc::Day => "day",
+            TimeTrunc::Week => "week",
+            TimeTrunc::Month => "month",
+            TimeTrunc::Year => "year",
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(group_by: &str) -> core::result::Result<Self, Self::Err> {
+        let (col, trunc) = match group_by.split_once(':') {
+            Some((col, trunc)) => (col, Some(trunc)),
+            None => (group_by, None),
+        };
+        let trunc = trunc
+            .map(|trunc| match trunc {
+                "hour" => Ok(TimeTrunc::Hour),
+                "day" => Ok(TimeTrunc::Day),
+                "week" => Ok(TimeTrunc::Week),
+                "month" => Ok(TimeTrunc::Month),
+                "year" => Ok(TimeTrunc::Year),
+                other => Err(format!("group by time unit '{other}' not supported")),
+            })
+            .transpose()?;
+
+        Ok(GroupBy {
+            col: col.to_string(),
+            trunc,
+        })
+    }
+}
+
+impl GroupBy {
+    /// The result name (e.g., `done`, `deleted_at_day`).
+    pub(in crate::model) fn alias(&self) -> String {
+        match self.trunc {
+            Some(trunc) => format!("{}_{}", self.col, trunc.as_str()),
+            None => self.col.clone(),
+        }
+    }
+
+    pub(in crate::model) fn expr(&self) -> SimpleExpr {
+        let col = Expr::col(Alias::new(&self.col));
+        match self.trunc {
+            // Note: The unit is inlined (not bound), so that the select and group by
+            //       expressions are the same for Postgres (and it is a static str).
+            Some(trunc) => Func::cust(Alias::new("date_trunc"))
+                .arg(Expr::cust(format!("'{}'", trunc.as_str())))
+                .arg(col)
+                .into(),
+            None => col.into(),
+        }
+    }
+}
+
+// endregion: --- GroupBy
+
+// region:    --- Aggregate
+
+#[derive(Debug, Clone, DeserializeFromStr)]
+pub struct Aggregate {
+    pub func: AggregateFn,
+    pub col: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AggregateFn {
+    Count,
+    Min,
+    Max,
+}
+
+impl AggregateFn {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AggregateFn::Count => "count",
+            AggregateFn::Min => "min",
+            AggregateFn::Max => "max",
+        }
+    }
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(aggregate: &str) -> core::result::Result<Self, Self::Err> {
+        let (func, col) = match aggregate.split_once(':') {
+            Some((func, col)) => (func, Some(col.to_string())),
+            None => (aggregate, None),
+        };
+        let func = match func {
+            "count" => AggregateFn::Count,
+            "min" => AggregateFn::Min,
+            "max" => AggregateFn::Max,
+            other => return Err(format!("aggregate function '{other}' not supported")),
+        };
+        if col.is_none() && !matches!(func, AggregateFn::Count) {
+            return Err(format!(
+                "aggregate function '{}' needs a column",
+                func.as_str()
+            ));
+        }
+
+        Ok(Aggregate { func, col })
+    }
+}
+
+impl Aggregate {
+    /// The result name (e.g., `count`, `max_version`).
+    pub(in crate::model) fn alias(&self) -> String {
+        match &self.col {
+            Some(col) => format!("{}_{col}", self.func.as_str()),
+            None => self.func.as_str().to_string(),
+        }
+    }
+
+    pub(in crate::model) fn expr(&self) -> SimpleExpr {
+        let col = || match &self.col {
+            Some(col) => Expr::col(Alias::new(col)),
+            None => Expr::col(Asterisk),
+        };
+        match self.func {
+            AggregateFn::Count => col().count(),
+            AggregateFn::Min => col().min(),
+            AggregateFn::Max => col().max(),
+        }
+    }
+}
+
+// endregion: --- Aggregate
+
+/// Check that the group by and aggregate columns are entity fields.
+pub(in crate::model) fn check_columns(
+    entity: &'static str,
+    field_names: &[&str],
+    group_bys: &[GroupBy],
+    aggregates: &[Aggregate],
+) -> Result<()> {
+    let cols = group_bys
+        .iter()
+        .map(|group_by| &group_by.col)
+        .chain(aggregates.iter().filter_map(|agg| agg.col.as_ref()));
+
+    for col in cols {
+        if !field_names.contains(&col.as_str()) {
+            return Err(Error::ColumnNotAllowed {
+                entity,
+                column: col.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that the truncated group by columns are timestamp or date columns,
+/// with the `col_types` the table columns pg type names.
+pub(in crate::model) fn check_trunc_types(
+    entity: &'static str,
+    col_types: &HashMap<String, String>,
+    group_bys: &[GroupBy],
+) -> Result<()> {
+    for group_by in group_bys.iter().filter(|group_by| group_by.trunc.is_some()) {
+        let is_time = col_types
+            .get(&group_by.col)
+            .is_some_and(|col_type| TRUNC_TYPES.contains(&col_type.as_str()));
+        if !is_time {
+            return Err(Error::ColumnNotAllowed {
+                entity,
+                column: group_by.col.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
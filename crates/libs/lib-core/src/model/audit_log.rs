@@ -0,0 +1,377 @@
+//! Audit trail of the data changes (create, update, delete) of the `DbBmc` that
+//! opt in (`DbBmc::AUDITED`).
+//!
+//! - The entries are written by the `base` functions, in the same transaction as the change.
+//! - Each entry has the acting `Ctx` user (and request id), the entity table and id,
+//!   the operation, and the old/new values (only the changed columns for an update).
+//! - Listing the entries is reserved to the admin users.
+
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::search::SearchIden;
+use crate::model::user::UserBmc;
+use crate::model::ModelManager;
+use crate::model::Result;
+use modql::field::{Fields, HasFields};
+use modql::filter::ListOptions;
+use sea_query::{Expr, Iden, PostgresQueryBuilder, Query};
+use sea_query_binder::SqlxBinder;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{FromRow, PgConnection};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// region:         — AuditLog Types
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditOp {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Purge,
+}
+
+impl AuditOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOp::Create => "create",
+            AuditOp::Update => "update",
+            AuditOp::Delete => "delete",
+            AuditOp::Restore => "restore",
+            AuditOp::Purge => "purge",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Fields, FromRow)]
+pub struct AuditLog {
+    pub id: i64,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub ctime: OffsetDateTime,
+    pub user_id: i64,
+    pub req_id: Option<Uuid>,
+
+    pub entity: String,
+    pub entity_id: i64,
+    pub op: String,
+
+    pub old_values: Option<Value>,
+    pub new_values: Option<Value>,
+}
+
+#[derive(Fields)]
+struct AuditLogForInsert {
+    user_id: i64,
+    req_id: Option<Uuid>,
+
+    entity: String,
+    entity_id: i64,
+    op: String,
+
+    old_values: Option<Value>,
+    new_values: Option<Value>,
+}
+
+/// The entries of an entity table (optionally of one entity id), within a time range.
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub entity: String,
+    pub entity_id: Option<i64>,
+    /// Inclusive (rfc3339).
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    /// Exclusive (rfc3339).
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+}
+
+#[derive(Iden)]
+enum AuditLogIden {
+    Ctime,
+    Entity,
+    EntityId,
+}
+
+// endregion:      — AuditLog Types
+
+pub struct AuditLogBmc;
+
+impl DbBmc for AuditLogBmc {
+    const TABLE: &'static str = "audit_log";
+}
+
+impl AuditLogBmc {
+    /// List the audit entries (admin users only).
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        query: AuditLogQuery,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<AuditLog>> {
+        UserBmc::check_admin(ctx, mm, Self::TABLE).await?;
+
+        let AuditLogQuery {
+            entity,
+            entity_id,
+            from,
+            to,
+        } = query;
+
+        // -- Build query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(AuditLog::field_column_refs())
+            .and_where(Expr::col(AuditLogIden::Entity).eq(entity));
+        if let Some(entity_id) = entity_id {
+            query.and_where(Expr::col(AuditLogIden::EntityId).eq(entity_id));
+        }
+        if let Some(from) = from {
+            query.and_where(Expr::col(AuditLogIden::Ctime).gte(from));
+        }
+        if let Some(to) = to {
+            query.and_where(Expr::col(AuditLogIden::Ctime).lt(to));
+        }
+
+        let list_options = base::finalize_list_options(list_options)?;
+        list_options.apply_to_sea_query(&mut query);
+
+        // -- Execute query
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let entries = sqlx::query_as_with::<_, AuditLog, _>(&sql, values)
+            .fetch_all(mm.db())
+            .await?;
+
+        Ok(entries)
+    }
+}
+
+// region:    --- Audit Hook (for base)
+
+/// The sql expression of a table row as JSON (without the generated `search_tsv`,
+/// when the table has one).
+pub(in crate::model) fn row_json_sql<MC>(row: &str) -> String
+where
+    MC: DbBmc,
+{
+    if MC::SEARCH_COLUMNS.is_empty() {
+        format!("to_jsonb({row})")
+    } else {
+        format!("to_jsonb({row}) - '{}'", SearchIden::Tsv.to_string())
+    }
+}
+
+/// The entity row as JSON (locked until the end of the transaction).
+pub(in crate::model) async fn row_json<MC>(
+    conn: &mut PgConnection,
+    id: i64,
+) -> Result<Option<Value>>
+where
+    MC: DbBmc,
+{
+    // Note: `MC::TABLE` is a compile time constant (no injection).
+    let sql = format!(
+        r#"SELECT {} FROM "{}" t WHERE id = $1 FOR UPDATE"#,
+        row_json_sql::<MC>("t"),
+        MC::TABLE
+    );
+    let row = sqlx::query_as::<_, (Value,)>(&sql)
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(row.map(|(value,)| value))
+}
+
+/// Insert the audit entry of a change (in the change transaction).
+pub(in crate::model) async fn record<MC>(
+    ctx: &Ctx,
+    conn: &mut PgConnection,
+    entity_id: i64,
+    op: AuditOp,
+    old_values: Option<Value>,
+    new_values: Option<Value>,
+) -> Result<()>
+where
+    MC: DbBmc,
+{
+    let (old_values, new_values) = match (op, old_values, new_values) {
+        (AuditOp::Update, Some(old), Some(new)) => changed_values(old, new),
+        (_, old_values, new_values) => (old_values, new_values),
+    };
+
+    let audit_fi = AuditLogForInsert {
+        user_id: ctx.user_id(),
+        req_id: ctx.req_id(),
+        entity: MC::TABLE.to_string(),
+        entity_id,
+        op: op.as_str().to_string(),
+        old_values,
+        new_values,
+    };
+
+    let fields = audit_fi.not_none_fields();
+    let (columns, sea_values) = fields.for_sea_insert();
+    let mut query = Query::insert();
+    query
+        .into_table(AuditLogBmc::table_ref())
+        .columns(columns)
+        .values(sea_values)?;
+
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    sqlx::query_with(&sql, values).execute(conn).await?;
+
+    Ok(())
+}
+
+/// Keep only the changed properties of the old and new JSON objects.
+fn changed_values(old: Value, new: Value) -> (Option<Value>, Option<Value>) {
+    let (Value::Object(mut old), Value::Object(new)) = (old, new) else {
+        return (None, None);
+    };
+
+    let mut old_changed = Map::new();
+    let mut new_changed = Map::new();
+    for (key, new_value) in new {
+        let old_value = old.remove(&key).unwrap_or(Value::Null);
+        if old_value != new_value {
+            old_changed.insert(key.clone(), old_value);
+            new_changed.insert(key, new_value);
+        }
+    }
+
+    (
+        Some(Value::Object(old_changed)),
+        Some(Value::Object(new_changed)),
+    )
+}
+
+// endregion: --- Audit Hook (for base)
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::task::{TaskBmc, TaskForUpdate, TaskStatus};
+    use crate::model::user::UserForCreate;
+    use crate::model::Error;
+    use anyhow::Result;
+    use serde_json::json;
+    use serial_test::serial;
+
+    #[test]
+    fn test_changed_values_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_old = json!({"id": 1, "title": "a", "done": false});
+        let fx_new = json!({"id": 1, "title": "b", "done": false});
+
+        // -- Exec
+        let (old, new) = changed_values(fx_old, fx_new);
+
+        // -- Check
+        assert_eq!(old, Some(json!({"title": "a"})));
+        assert_eq!(new, Some(json!({"title": "b"})));
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_task_changes_audited_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let root_ctx = Ctx::root_ctx();
+        let fx_req_id = Uuid::new_v4();
+        let ctx = Ctx::new(1000)?.with_req_id(fx_req_id);
+        let fx_title = "test_task_changes_audited_ok title";
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+            .await?
+            .remove(0);
+
+        // -- Exec
+        let task_u = TaskForUpdate {
+            status: Some(TaskStatus::Done),
+            ..Default::default()
+        };
+        TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+
+        // -- Check
+        let query = AuditLogQuery {
+            entity: TaskBmc::TABLE.to_string(),
+            entity_id: Some(fx_task.id),
+            from: None,
+            to: None,
+        };
+        let entries = AuditLogBmc::list(&root_ctx, &mm, query, None).await?;
+        let ops: Vec<&str> = entries.iter().map(|e| e.op.as_str()).collect();
+        assert_eq!(ops, ["create", "update", "delete"]);
+        assert!(entries.iter().all(|e| e.user_id == 1000));
+        assert!(entries.iter().all(|e| e.req_id == Some(fx_req_id)));
+        assert_eq!(
+            entries[1].old_values,
+            Some(json!({"status": "todo", "done": false, "version": 1}))
+        );
+        assert_eq!(
+            entries[1].new_values,
+            Some(json!({"status": "done", "done": true, "version": 2}))
+        );
+        assert_eq!(
+            entries[2].old_values.as_ref().and_then(|v| v.get("title")),
+            Some(&json!(fx_title))
+        );
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_err_access_denied() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let root_ctx = Ctx::root_ctx();
+        let fx_username = "test_list_err_access_denied-user";
+        let user_id = UserBmc::create(
+            &root_ctx,
+            &mm,
+            UserForCreate {
+                username: fx_username.to_string(),
+                pwd_clear: "Correct-Horse-Battery-42".to_string(),
+            },
+        )
+        .await?;
+        let ctx = Ctx::new(user_id)?;
+
+        // -- Exec
+        let query = AuditLogQuery {
+            entity: TaskBmc::TABLE.to_string(),
+            entity_id: None,
+            from: None,
+            to: None,
+        };
+        let res = AuditLogBmc::list(&ctx, &mm, query, None).await;
+
+        // -- Check
+        assert!(
+            matches!(
+                res,
+                Err(Error::AccessDenied {
+                    entity: "audit_log"
+                })
+            ),
+            "AccessDenied not matched"
+        );
+
+        // -- Clean
+        base::delete::<UserBmc>(&root_ctx, &mm, user_id).await?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -1,13 +1,24 @@
 use crate::ctx::Ctx;
+use crate::model::aggregate::{self, Aggregate, Aggregation, GroupBy};
+use crate::model::audit_log::{self, AuditOp};
+use crate::model::list_cursor::{self, ListCursor, ListPage};
+use crate::model::search::{self, SearchHit, SearchIden};
 use crate::model::ModelManager;
 use crate::model::{Error, Result};
-use modql::field::HasFields;
-use modql::filter::{FilterGroups, ListOptions};
+use modql::field::{Fields, HasFields};
+use modql::filter::{FilterGroups, ListOptions, OrderBys};
 use modql::SIden;
-use sea_query::{Condition, Expr, Iden, IntoIden, PostgresQueryBuilder, Query, TableRef};
+use sea_query::{
+    Alias, Condition, Expr, Iden, IntoIden, Keyword, LockType, Order, PostgresQueryBuilder, Query,
+    SelectStatement, SimpleExpr, TableRef,
+};
 use sea_query_binder::SqlxBinder;
+use serde_json::Value;
 use sqlx::postgres::PgRow;
-use sqlx::FromRow;
+use sqlx::{FromRow, PgConnection, Row};
+use std::collections::HashMap;
+use time::OffsetDateTime;
+use tracing::instrument;
 
 const LIST_LIMIT_DEFAULT: i64 = 300;
 const LIST_LIMIT_MAX: i64 = 1000;
@@ -15,14 +26,43 @@ const LIST_LIMIT_MAX: i64 = 1000;
 #[derive(Iden)]
 pub enum CommonIden {
     Id,
+    DeletedAt,
+    Version,
 }
 
 pub trait DbBmc {
     const TABLE: &'static str;
 
+    /// When true, `create`, `update` and `delete` record an `audit_log` entry
+    /// (in the same transaction).
+    const AUDITED: bool = false;
+
+    /// When true (table with a `deleted_at` column), `delete` marks the row as deleted,
+    /// and `get`, `list` and `update` exclude the deleted rows
+    /// (see `list_deleted`, `restore` and `purge`).
+    const SOFT_DELETE: bool = false;
+
+    /// When true (table with a `version` column), `update` increments the row version,
+    /// and `update_with_version` only updates the row at the expected version.
+    const VERSIONED: bool = false;
+
+    /// The text columns of the full-text `search`, when not empty (table with
+    /// a `search_tsv` tsvector column generated from these columns, and a GIN index).
+    const SEARCH_COLUMNS: &'static [&'static str] = &[];
+
     fn table_ref() -> TableRef {
         TableRef::Table(SIden(Self::TABLE).into_iden())
     }
+
+    /// The not deleted rows condition (always true when not `SOFT_DELETE`).
+    fn not_deleted_cond() -> Condition {
+        let cond = Condition::all();
+        if Self::SOFT_DELETE {
+            cond.add(Expr::col(CommonIden::DeletedAt).is_null())
+        } else {
+            cond
+        }
+    }
 }
 
 pub fn finalize_list_options(list_options: Option<ListOptions>) -> Result<ListOptions> {
@@ -53,13 +93,25 @@ pub fn finalize_list_options(list_options: Option<ListOptions>) -> Result<ListOp
     }
 }
 
-pub async fn create<MC, E>(_ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
+pub async fn create<MC, E>(ctx: &Ctx, mm: &ModelManager, data: E) -> Result<i64>
 where
     MC: DbBmc,
     E: HasFields,
 {
-    let db = mm.db();
+    let mut tx = mm.db().begin().await?;
+    let id = create_in::<MC, E>(ctx, &mut tx, data).await?;
+    tx.commit().await?;
+
+    Ok(id)
+}
 
+/// `create` on the `conn` connection (e.g., with other changes in the same transaction).
+#[instrument(name = "db.create", skip_all, fields(db.table = MC::TABLE))]
+pub async fn create_in<MC, E>(ctx: &Ctx, conn: &mut PgConnection, data: E) -> Result<i64>
+where
+    MC: DbBmc,
+    E: HasFields,
+{
     // -- Prep data
     let fields = data.not_none_fields();
     let (columns, sea_values) = fields.for_sea_insert();
@@ -75,32 +127,72 @@ where
     // -- Exec query
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let (id,) = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
-        .fetch_one(db)
+        .fetch_one(&mut *conn)
         .await?;
 
+    // -- Audit
+    if MC::AUDITED {
+        let new_values = audit_log::row_json::<MC>(conn, id).await?;
+        audit_log::record::<MC>(ctx, conn, id, AuditOp::Create, None, new_values).await?;
+    }
+
     Ok(id)
 }
 
 // MC - Model Controller, E - Entity
-pub async fn get<MC, E>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
+pub async fn get<MC, E>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
 where
     MC: DbBmc,
     E: for<'r> FromRow<'r, PgRow> + Unpin + Send, // for<'r> is a lifetime parameter
     E: HasFields,
 {
-    let db = mm.db();
+    let mut conn = mm.db().acquire().await?;
+    get_in::<MC, E>(ctx, &mut conn, id).await
+}
 
+/// `get` on the `conn` connection (e.g., in a transaction).
+#[instrument(name = "db.get", skip_all, fields(db.table = MC::TABLE))]
+pub async fn get_in<MC, E>(_ctx: &Ctx, conn: &mut PgConnection, id: i64) -> Result<E>
+where
+    MC: DbBmc,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields,
+{
+    get_with_lock::<MC, E>(conn, id, false).await
+}
+
+/// `get_in`, with the row locked (`FOR UPDATE`) until the end of the `conn` transaction.
+#[instrument(name = "db.get_for_update", skip_all, fields(db.table = MC::TABLE))]
+pub async fn get_for_update_in<MC, E>(_ctx: &Ctx, conn: &mut PgConnection, id: i64) -> Result<E>
+where
+    MC: DbBmc,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields,
+{
+    get_with_lock::<MC, E>(conn, id, true).await
+}
+
+async fn get_with_lock<MC, E>(conn: &mut PgConnection, id: i64, for_update: bool) -> Result<E>
+where
+    MC: DbBmc,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields,
+{
     // -- Build query
     let mut query = Query::select();
     query
         .from(MC::table_ref())
         .columns(E::field_column_refs())
-        .and_where(Expr::col(CommonIden::Id).eq(id));
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .cond_where(MC::not_deleted_cond());
+    if for_update {
+        query.lock(LockType::Update);
+    }
 
     // -- Execute query
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let entity = sqlx::query_as_with::<_, E, _>(&sql, values)
-        .fetch_optional(db)
+        .fetch_optional(conn)
         .await?
         .ok_or(Error::EntityNotFound {
             entity: MC::TABLE,
@@ -110,6 +202,7 @@ where
     Ok(entity)
 }
 
+#[instrument(name = "db.list", skip_all, fields(db.table = MC::TABLE))]
 pub async fn list<MC, E, F>(
     _ctx: &Ctx,
     mm: &ModelManager,
@@ -121,42 +214,393 @@ where
     F: Into<FilterGroups>,
     E: for<'r> FromRow<'r, PgRow> + Unpin + Send, // for<'r> is a lifetime parameter
     E: HasFields,
+{
+    list_with_cond::<MC, E, F>(mm, MC::not_deleted_cond(), filters, list_options).await
+}
+
+async fn list_with_cond<MC, E, F>(
+    mm: &ModelManager,
+    base_cond: Condition,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+) -> Result<Vec<E>>
+where
+    MC: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields,
 {
     let db = mm.db();
 
     // -- Build query
+    let mut query = list_query::<MC, E, F>(base_cond, filters)?;
+
+    // list options
+    let list_options = finalize_list_options(list_options)?;
+    list_options.apply_to_sea_query(&mut query);
+
+    // -- Execute query
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let entities = sqlx::query_as_with::<_, E, _>(&sql, values)
+        .fetch_all(db)
+        .await?;
+
+    Ok(entities)
+}
+
+/// The select of the `E` columns, for the rows matching the `base_cond` and the filters
+/// (without the list options).
+fn list_query<MC, E, F>(base_cond: Condition, filters: Option<F>) -> Result<SelectStatement>
+where
+    MC: DbBmc,
+    F: Into<FilterGroups>,
+    E: HasFields,
+{
     let mut query = Query::select();
-    query.from(MC::table_ref()).columns(E::field_column_refs());
+    query
+        .from(MC::table_ref())
+        .columns(E::field_column_refs())
+        .cond_where(base_cond);
 
     // condition from filter
-    if let Some(filters) = filters {
-        let filters: FilterGroups = filters.into();
-        let cond: Condition = filters.try_into()?;
-        query.cond_where(cond);
+    if let Some(filters_cond) = filters_cond(filters)? {
+        query.cond_where(filters_cond);
     }
 
-    // list options
+    Ok(query)
+}
+
+/// Count the (not deleted) rows matching the filters (same filters as `list`).
+#[instrument(name = "db.count", skip_all, fields(db.table = MC::TABLE))]
+pub async fn count<MC, F>(_ctx: &Ctx, mm: &ModelManager, filters: Option<F>) -> Result<i64>
+where
+    MC: DbBmc,
+    F: Into<FilterGroups>,
+{
+    count_with_cond::<MC>(mm, filters_cond(filters)?).await
+}
+
+async fn count_with_cond<MC>(mm: &ModelManager, filters_cond: Option<Condition>) -> Result<i64>
+where
+    MC: DbBmc,
+{
+    let db = mm.db();
+
+    // -- Build query
+    let mut query = Query::select();
+    query
+        .from(MC::table_ref())
+        .expr(Expr::col(CommonIden::Id).count())
+        .cond_where(MC::not_deleted_cond());
+
+    if let Some(filters_cond) = filters_cond {
+        query.cond_where(filters_cond);
+    }
+
+    // -- Execute query
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let (count,) = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
+        .fetch_one(db)
+        .await?;
+
+    Ok(count)
+}
+
+fn filters_cond<F>(filters: Option<F>) -> Result<Option<Condition>>
+where
+    F: Into<FilterGroups>,
+{
+    let cond = filters
+        .map(|filters| Condition::try_from(filters.into()))
+        .transpose()?;
+
+    Ok(cond)
+}
+
+/// Search the (not deleted) rows matching the search `terms` and the filters,
+/// ranked by relevance (then by the list options `order_bys`), i.e., the `list`
+/// of the rows matching the terms, with their rank and snippet.
+///
+/// Note: To only filter (not rank) by the terms, `list` with a `search` filter
+///       (see `search::OpValsSearch`).
+#[instrument(name = "db.search", skip_all, fields(db.table = MC::TABLE))]
+pub async fn search<MC, E, F>(
+    _ctx: &Ctx,
+    mm: &ModelManager,
+    terms: &str,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+) -> Result<Vec<SearchHit<E>>>
+where
+    MC: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields,
+{
+    let db = mm.db();
+
+    if MC::SEARCH_COLUMNS.is_empty() {
+        return Err(Error::SearchNotSupported { entity: MC::TABLE });
+    }
+
+    // -- Build query
+    let base_cond = MC::not_deleted_cond().add(search::matches_cond(terms));
+    let mut query = list_query::<MC, E, F>(base_cond, filters)?;
+    query
+        .expr_as(search::rank_expr(terms), SearchIden::Rank)
+        .expr_as(
+            search::snippet_expr(MC::SEARCH_COLUMNS, terms),
+            SearchIden::Snippet,
+        );
+
+    // list options (after the rank order)
+    query.order_by(SearchIden::Rank, Order::Desc);
     let list_options = finalize_list_options(list_options)?;
     list_options.apply_to_sea_query(&mut query);
 
     // -- Execute query
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
-    let entities = sqlx::query_as_with::<_, E, _>(&sql, values)
+    let rows = sqlx::query_with(&sql, values).fetch_all(db).await?;
+
+    let hits = rows
+        .iter()
+        .map(|row| {
+            Ok(SearchHit {
+                item: E::from_row(row)?,
+                rank: row.try_get(SearchIden::Rank.to_string().as_str())?,
+                snippet: search::snippet_html(
+                    row.try_get(SearchIden::Snippet.to_string().as_str())?,
+                ),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(hits)
+}
+
+/// Aggregate the (not deleted) rows matching the filters, grouped by the `group_bys`
+/// (one JSON object per group, with the group by values and the aggregates), up to
+/// `LIST_LIMIT_MAX` groups (`truncated` when more).
+///
+/// The columns must be fields of `E`, and the truncated ones timestamps or dates
+/// (`Error::ColumnNotAllowed` otherwise).
+#[instrument(name = "db.aggregate", skip_all, fields(db.table = MC::TABLE))]
+pub async fn aggregate<MC, E, F>(
+    _ctx: &Ctx,
+    mm: &ModelManager,
+    filters: Option<F>,
+    group_bys: &[GroupBy],
+    aggregates: &[Aggregate],
+) -> Result<Aggregation>
+where
+    MC: DbBmc,
+    E: HasFields,
+    F: Into<FilterGroups>,
+{
+    let db = mm.db();
+
+    aggregate::check_columns(MC::TABLE, E::field_names(), group_bys, aggregates)?;
+    if group_bys.iter().any(|group_by| group_by.trunc.is_some()) {
+        let col_types = column_pg_types::<MC>(mm).await?;
+        aggregate::check_trunc_types(MC::TABLE, &col_types, group_bys)?;
+    }
+
+    // -- Build query
+    let mut query = Query::select();
+    query
+        .from(MC::table_ref())
+        .cond_where(MC::not_deleted_cond());
+
+    if let Some(filters_cond) = filters_cond(filters)? {
+        query.cond_where(filters_cond);
+    }
+
+    for group_by in group_bys {
+        query
+            .expr_as(group_by.expr(), Alias::new(group_by.alias()))
+            .add_group_by([group_by.expr()])
+            .order_by_expr(group_by.expr(), Order::Asc);
+    }
+    for aggregate in aggregates {
+        query.expr_as(aggregate.expr(), Alias::new(aggregate.alias()));
+    }
+    // Note: One more than the max, to know if truncated.
+    query.limit(LIST_LIMIT_MAX as u64 + 1);
+
+    // -- Execute query (each row as a JSON object)
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let sql = format!("SELECT to_jsonb(agg) FROM ({sql}) agg");
+    let rows = sqlx::query_as_with::<_, (Value,), _>(&sql, values)
         .fetch_all(db)
         .await?;
 
-    Ok(entities)
+    let mut groups: Vec<Value> = rows.into_iter().map(|(row,)| row).collect();
+    let truncated = groups.len() > LIST_LIMIT_MAX as usize;
+    groups.truncate(LIST_LIMIT_MAX as usize);
+
+    Ok(Aggregation { groups, truncated })
+}
+
+/// The `MC::TABLE` columns pg type names (e.g., `int8`, `timestamptz`, `task_status`).
+async fn column_pg_types<MC>(mm: &ModelManager) -> Result<HashMap<String, String>>
+where
+    MC: DbBmc,
+{
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT a.attname::text, t.typname::text FROM pg_attribute a \
+         JOIN pg_type t ON t.oid = a.atttypid \
+         WHERE a.attrelid = to_regclass($1) AND a.attnum > 0 AND NOT a.attisdropped",
+    )
+    .bind(format!(r#""{}""#, MC::TABLE))
+    .fetch_all(mm.db())
+    .await?;
+
+    Ok(rows.into_iter().collect())
 }
 
-pub async fn update<MC, E>(_ctx: &Ctx, mm: &ModelManager, id: i64, data: E) -> Result<()>
+/// List a page, after the `cursor` (the `next_cursor` of the previous page) when `Some`,
+/// with the total count of the filtered rows when `include_total`.
+///
+/// Note: The list options `offset` is ignored (the cursor replaces it).
+#[instrument(name = "db.list_page", skip_all, fields(db.table = MC::TABLE))]
+pub async fn list_page<MC, E, F>(
+    _ctx: &Ctx,
+    mm: &ModelManager,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+    cursor: Option<&str>,
+    include_total: bool,
+) -> Result<ListPage<E>>
 where
     MC: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
     E: HasFields,
 {
     let db = mm.db();
 
+    // -- Prep list options (sort keys, and one more row to know if there is a next page)
+    let list_options = finalize_list_options(list_options)?;
+    let limit = list_options.limit.unwrap_or(LIST_LIMIT_DEFAULT).max(0);
+    let sort_keys = list_cursor::sort_keys(list_options.order_bys.map(OrderBys::order_bys));
+    let list_options = ListOptions {
+        limit: Some(limit + 1),
+        offset: None,
+        order_bys: Some(OrderBys::new(sort_keys.clone())),
+    };
+
+    // -- Build query
+    let mut query = Query::select();
+    query
+        .from(MC::table_ref())
+        .columns(E::field_column_refs())
+        .cond_where(MC::not_deleted_cond());
+
+    let filters_cond = filters_cond(filters)?;
+    if let Some(filters_cond) = filters_cond.clone() {
+        query.cond_where(filters_cond);
+    }
+
+    if let Some(cursor) = cursor {
+        let cursor = ListCursor::decode(cursor)?;
+        let col_types = column_pg_types::<MC>(mm).await?;
+        query.cond_where(cursor.after_cond(&sort_keys, &col_types)?);
+    }
+
+    list_options.apply_to_sea_query(&mut query);
+
+    // -- Execute query
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let mut rows = sqlx::query_with(&sql, values).fetch_all(db).await?;
+
+    // -- Build the page
+    let has_next = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let next_cursor = match rows.last() {
+        Some(last_row) if has_next => Some(ListCursor::from_row(last_row, &sort_keys)?.encode()?),
+        _ => None,
+    };
+    let items = rows
+        .iter()
+        .map(E::from_row)
+        .collect::<core::result::Result<Vec<E>, _>>()?;
+
+    // -- Total (when requested)
+    let total = if include_total {
+        Some(count_with_cond::<MC>(mm, filters_cond).await?)
+    } else {
+        None
+    };
+
+    Ok(ListPage {
+        items,
+        next_cursor,
+        total,
+    })
+}
+
+/// Update, only when the row is at the `expected_version` (when `Some` and `MC::VERSIONED`),
+/// otherwise `Error::VersionConflict` (with the current version).
+pub async fn update_with_version<MC, E>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    id: i64,
+    data: E,
+    expected_version: Option<i64>,
+) -> Result<()>
+where
+    MC: DbBmc,
+    E: HasFields,
+{
+    let mut tx = mm.db().begin().await?;
+    update_with_version_in::<MC, E>(ctx, &mut tx, id, data, expected_version).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// `update_with_version` on the `conn` connection (e.g., with other changes in the same transaction).
+pub async fn update_with_version_in<MC, E>(
+    ctx: &Ctx,
+    conn: &mut PgConnection,
+    id: i64,
+    data: E,
+    expected_version: Option<i64>,
+) -> Result<()>
+where
+    MC: DbBmc,
+    E: HasFields,
+{
+    update_fields::<MC>(ctx, conn, id, data.not_none_fields(), expected_version).await
+}
+
+/// Update with all the `data` fields, the `None` ones set to NULL (e.g., to unset
+/// a reference column), on the `conn` connection (e.g., in a transaction).
+pub async fn update_all_fields_in<MC, E>(
+    ctx: &Ctx,
+    conn: &mut PgConnection,
+    id: i64,
+    data: E,
+) -> Result<()>
+where
+    MC: DbBmc,
+    E: HasFields,
+{
+    update_fields::<MC>(ctx, conn, id, data.all_fields(), None).await
+}
+
+#[instrument(name = "db.update", skip_all, fields(db.table = MC::TABLE))]
+async fn update_fields<MC>(
+    ctx: &Ctx,
+    conn: &mut PgConnection,
+    id: i64,
+    fields: Fields,
+    expected_version: Option<i64>,
+) -> Result<()>
+where
+    MC: DbBmc,
+{
     // -- Prep data
-    let fields = data.not_none_fields();
     let fields = fields.for_sea_update();
 
     // -- Build query
@@ -164,42 +608,176 @@ where
     query
         .table(MC::table_ref())
         .values(fields)
-        .and_where(Expr::col(CommonIden::Id).eq(id));
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .cond_where(MC::not_deleted_cond());
+    if MC::VERSIONED {
+        query.value(CommonIden::Version, Expr::col(CommonIden::Version).add(1));
+        if let Some(expected_version) = expected_version {
+            query.and_where(Expr::col(CommonIden::Version).eq(expected_version));
+        }
+    }
 
     // -- Exec query
+    let old_values = if MC::AUDITED {
+        audit_log::row_json::<MC>(conn, id).await?
+    } else {
+        None
+    };
+
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let count = sqlx::query_with(&sql, values)
-        .execute(db)
+        .execute(&mut *conn)
         .await?
         .rows_affected();
 
     // -- Check result
     if count == 0 {
-        Err(Error::EntityNotFound {
+        // Note: Found, but not at the expected version.
+        if MC::VERSIONED && expected_version.is_some() {
+            if let Some(current) = current_version::<MC>(conn, id).await? {
+                return Err(Error::VersionConflict {
+                    entity: MC::TABLE,
+                    id,
+                    current,
+                });
+            }
+        }
+        return Err(Error::EntityNotFound {
             entity: MC::TABLE,
             id,
-        })
+        });
+    }
+
+    // -- Audit
+    if MC::AUDITED {
+        let new_values = audit_log::row_json::<MC>(conn, id).await?;
+        audit_log::record::<MC>(ctx, conn, id, AuditOp::Update, old_values, new_values).await?;
+    }
+
+    Ok(())
+}
+
+/// The version of a (not deleted) row, `None` if not found.
+async fn current_version<MC>(conn: &mut PgConnection, id: i64) -> Result<Option<i64>>
+where
+    MC: DbBmc,
+{
+    let mut query = Query::select();
+    query
+        .from(MC::table_ref())
+        .column(CommonIden::Version)
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .cond_where(MC::not_deleted_cond());
+
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let version = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(version.map(|(version,)| version))
+}
+
+#[instrument(name = "db.delete", skip_all, fields(db.table = MC::TABLE))]
+pub async fn delete<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+where
+    MC: DbBmc,
+{
+    let db = mm.db();
+
+    // -- Build query (mark as deleted when soft delete)
+    let (sql, values) = if MC::SOFT_DELETE {
+        let mut query = Query::update();
+        query
+            .table(MC::table_ref())
+            .value(CommonIden::DeletedAt, Expr::current_timestamp())
+            .and_where(Expr::col(CommonIden::Id).eq(id))
+            .cond_where(MC::not_deleted_cond());
+        query.build_sqlx(PostgresQueryBuilder)
     } else {
-        Ok(())
+        let mut query = Query::delete();
+        query
+            .from_table(MC::table_ref())
+            .and_where(Expr::col(CommonIden::Id).eq(id));
+        query.build_sqlx(PostgresQueryBuilder)
+    };
+
+    // -- Execute query
+    let mut tx = db.begin().await?;
+    let old_values = if MC::AUDITED {
+        audit_log::row_json::<MC>(&mut tx, id).await?
+    } else {
+        None
+    };
+
+    let count = sqlx::query_with(&sql, values)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    // -- Check result
+    if count == 0 {
+        return Err(Error::EntityNotFound {
+            entity: MC::TABLE,
+            id,
+        });
+    }
+
+    // -- Audit
+    if MC::AUDITED {
+        audit_log::record::<MC>(ctx, &mut tx, id, AuditOp::Delete, old_values, None).await?;
     }
+    tx.commit().await?;
+
+    Ok(())
 }
 
-pub async fn delete<MC>(_ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+// region:    --- Soft Delete
+
+#[instrument(name = "db.list_deleted", skip_all, fields(db.table = MC::TABLE))]
+pub async fn list_deleted<MC, E, F>(
+    _ctx: &Ctx,
+    mm: &ModelManager,
+    filters: Option<F>,
+    list_options: Option<ListOptions>,
+) -> Result<Vec<E>>
 where
     MC: DbBmc,
+    F: Into<FilterGroups>,
+    E: for<'r> FromRow<'r, PgRow> + Unpin + Send,
+    E: HasFields,
 {
+    if !MC::SOFT_DELETE {
+        return Err(Error::SoftDeleteNotSupported { entity: MC::TABLE });
+    }
+
+    let deleted_cond = Condition::all().add(Expr::col(CommonIden::DeletedAt).is_not_null());
+    list_with_cond::<MC, E, F>(mm, deleted_cond, filters, list_options).await
+}
+
+#[instrument(name = "db.restore", skip_all, fields(db.table = MC::TABLE))]
+pub async fn restore<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+where
+    MC: DbBmc,
+{
+    if !MC::SOFT_DELETE {
+        return Err(Error::SoftDeleteNotSupported { entity: MC::TABLE });
+    }
+
     let db = mm.db();
 
     // -- Build query
-    let mut query = Query::delete();
+    let mut query = Query::update();
     query
-        .from_table(MC::table_ref())
-        .and_where(Expr::col(CommonIden::Id).eq(id));
+        .table(MC::table_ref())
+        .value(CommonIden::DeletedAt, SimpleExpr::Keyword(Keyword::Null))
+        .and_where(Expr::col(CommonIden::Id).eq(id))
+        .and_where(Expr::col(CommonIden::DeletedAt).is_not_null());
 
     // -- Execute query
+    let mut tx = db.begin().await?;
     let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
     let count = sqlx::query_with(&sql, values)
-        .execute(db)
+        .execute(&mut *tx)
         .await?
         .rows_affected();
 
@@ -209,7 +787,117 @@ where
             entity: MC::TABLE,
             id,
         });
+    }
+
+    // -- Audit
+    if MC::AUDITED {
+        let new_values = audit_log::row_json::<MC>(&mut tx, id).await?;
+        audit_log::record::<MC>(ctx, &mut tx, id, AuditOp::Restore, None, new_values).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Permanently delete a soft deleted row.
+#[instrument(name = "db.purge", skip_all, fields(db.table = MC::TABLE))]
+pub async fn purge<MC>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()>
+where
+    MC: DbBmc,
+{
+    if !MC::SOFT_DELETE {
+        return Err(Error::SoftDeleteNotSupported { entity: MC::TABLE });
+    }
+
+    let cond = Condition::all()
+        .add(Expr::col(CommonIden::Id).eq(id))
+        .add(Expr::col(CommonIden::DeletedAt).is_not_null());
+
+    let purged_ids = purge_with_cond::<MC>(ctx, mm, cond).await?;
+
+    if purged_ids.is_empty() {
+        Err(Error::EntityNotFound {
+            entity: MC::TABLE,
+            id,
+        })
     } else {
         Ok(())
     }
 }
+
+/// Permanently delete the rows soft deleted before `deleted_before`, by batches of
+/// `batch_size` rows (one transaction each, so that the locks are short).
+/// Returns the number of purged rows.
+#[instrument(name = "db.purge_deleted_before", skip_all, fields(db.table = MC::TABLE))]
+pub async fn purge_deleted_before<MC>(
+    ctx: &Ctx,
+    mm: &ModelManager,
+    deleted_before: OffsetDateTime,
+    batch_size: u64,
+) -> Result<u64>
+where
+    MC: DbBmc,
+{
+    if !MC::SOFT_DELETE {
+        return Err(Error::SoftDeleteNotSupported { entity: MC::TABLE });
+    }
+
+    let mut count = 0;
+    loop {
+        let mut batch_query = Query::select();
+        batch_query
+            .column(CommonIden::Id)
+            .from(MC::table_ref())
+            .and_where(Expr::col(CommonIden::DeletedAt).lt(deleted_before))
+            .limit(batch_size);
+        let cond = Condition::all().add(Expr::col(CommonIden::Id).in_subquery(batch_query));
+
+        let purged_count = purge_with_cond::<MC>(ctx, mm, cond).await?.len() as u64;
+        count += purged_count;
+
+        if purged_count < batch_size {
+            return Ok(count);
+        }
+    }
+}
+
+async fn purge_with_cond<MC>(ctx: &Ctx, mm: &ModelManager, cond: Condition) -> Result<Vec<i64>>
+where
+    MC: DbBmc,
+{
+    let db = mm.db();
+
+    // -- Build query
+    // Note: `MC::TABLE` is a compile time constant (no injection).
+    let row_json = Expr::cust(audit_log::row_json_sql::<MC>(&format!(
+        r#""{}""#,
+        MC::TABLE
+    )));
+    let mut query = Query::delete();
+    query
+        .from_table(MC::table_ref())
+        .cond_where(cond)
+        .returning(Query::returning().exprs([Expr::col(CommonIden::Id).into(), row_json]));
+
+    // -- Execute query
+    let mut tx = db.begin().await?;
+    let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+    let rows = sqlx::query_as_with::<_, (i64, Value), _>(&sql, values)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    // -- Audit
+    let mut ids = Vec::with_capacity(rows.len());
+    for (id, old_values) in rows {
+        if MC::AUDITED {
+            audit_log::record::<MC>(ctx, &mut tx, id, AuditOp::Purge, Some(old_values), None)
+                .await?;
+        }
+        ids.push(id);
+    }
+    tx.commit().await?;
+
+    Ok(ids)
+}
+
+// endregion: --- Soft Delete
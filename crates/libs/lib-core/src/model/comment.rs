@@ -0,0 +1,332 @@
+//! Task comments, threaded with their `parent_id` (a comment of the same task).
+//!
+//! - The author is the `Ctx` user (not the root ctx), and only the author can update
+//!   or delete a comment. The comments are kept when their author is deleted
+//!   (`author_id` set to null).
+//! - The comments are soft deleted, so the replies of a deleted comment keep their
+//!   `parent_id` (the client can show the deleted parent as such).
+
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::task::TaskBmc;
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use lib_utils::time::now_utc;
+use modql::field::Fields;
+use modql::filter::{FilterNodes, ListOptions, OpValsInt64};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+
+// region:         — Comment Types
+
+#[derive(Debug, Clone, Serialize, Fields, FromRow)]
+pub struct Comment {
+    pub id: i64,
+    pub task_id: i64,
+    /// `None` when the author was deleted.
+    pub author_id: Option<i64>,
+    pub parent_id: Option<i64>,
+
+    pub body: String,
+
+    /// Incremented on each update (see `CommentBmc::update_with_version`).
+    pub version: i64,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub ctime: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub mtime: OffsetDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct CommentForCreate {
+    pub task_id: i64,
+    /// The comment replied to (of the same task).
+    pub parent_id: Option<i64>,
+    pub body: String,
+}
+
+#[derive(Deserialize)]
+pub struct CommentForUpdate {
+    pub body: String,
+}
+
+#[derive(Fields)]
+struct CommentForInsert {
+    task_id: i64,
+    author_id: i64,
+    parent_id: Option<i64>,
+    body: String,
+}
+
+#[derive(Fields)]
+struct CommentForUpdateDb {
+    body: String,
+    mtime: OffsetDateTime,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct CommentFilter {
+    task_id: Option<OpValsInt64>,
+    author_id: Option<OpValsInt64>,
+    parent_id: Option<OpValsInt64>,
+}
+
+// endregion:      — Comment Types
+
+pub struct CommentBmc;
+
+impl DbBmc for CommentBmc {
+    const TABLE: &'static str = "comment";
+    const SOFT_DELETE: bool = true;
+    const VERSIONED: bool = true;
+}
+
+impl CommentBmc {
+    /// Add a comment (by the `Ctx` user) to a task, or as a reply to a comment of the task.
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, comment_c: CommentForCreate) -> Result<i64> {
+        let CommentForCreate {
+            task_id,
+            parent_id,
+            body,
+        } = comment_c;
+
+        // Note: The root ctx is not a user (cannot be an author).
+        if ctx.user_id() == 0 {
+            return Err(Error::AccessDenied {
+                entity: Self::TABLE,
+            });
+        }
+
+        // -- Check the task, and the parent comment (of the same task)
+        TaskBmc::get(ctx, mm, task_id).await?;
+        if let Some(parent_id) = parent_id {
+            let parent = Self::get(ctx, mm, parent_id).await?;
+            if parent.task_id != task_id {
+                return Err(Error::EntityNotFound {
+                    entity: Self::TABLE,
+                    id: parent_id,
+                });
+            }
+        }
+
+        let comment_fi = CommentForInsert {
+            task_id,
+            author_id: ctx.user_id(),
+            parent_id,
+            body,
+        };
+
+        base::create::<Self, _>(ctx, mm, comment_fi).await
+    }
+
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Comment> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    /// The comments of a task (by default, in creation order).
+    pub async fn list_for_task(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        task_id: i64,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Comment>> {
+        let filter = CommentFilter {
+            task_id: Some(task_id.into()),
+            ..Default::default()
+        };
+
+        base::list::<Self, _, _>(ctx, mm, Some(filter), list_options).await
+    }
+
+    /// Update the comment body (author only).
+    pub async fn update(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        comment_u: CommentForUpdate,
+    ) -> Result<()> {
+        Self::update_with_version(ctx, mm, id, comment_u, None).await
+    }
+
+    /// Update only if the comment is at the `expected_version` (when `Some`),
+    /// `Error::VersionConflict` otherwise.
+    pub async fn update_with_version(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        comment_u: CommentForUpdate,
+        expected_version: Option<i64>,
+    ) -> Result<()> {
+        Self::check_author(ctx, mm, id).await?;
+
+        let comment_u = CommentForUpdateDb {
+            body: comment_u.body,
+            mtime: now_utc(),
+        };
+
+        base::update_with_version::<Self, _>(ctx, mm, id, comment_u, expected_version).await
+    }
+
+    /// Delete the comment (author only).
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        Self::check_author(ctx, mm, id).await?;
+
+        base::delete::<Self>(ctx, mm, id).await
+    }
+
+    /// `Error::AccessDenied` when the `Ctx` user is not the comment author.
+    async fn check_author(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        let comment = Self::get(ctx, mm, id).await?;
+        if comment.author_id != Some(ctx.user_id()) {
+            return Err(Error::AccessDenied {
+                entity: Self::TABLE,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::user::{UserBmc, UserForCreate};
+    use anyhow::Result;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_thread_and_ownership_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let root_ctx = Ctx::root_ctx();
+        let author_ctx = Ctx::new(1000)?; // demo1
+        let other_id = UserBmc::create(
+            &root_ctx,
+            &mm,
+            UserForCreate {
+                username: "test_thread_and_ownership_ok-user".to_string(),
+                pwd_clear: "Correct-Horse-Battery-42".to_string(),
+            },
+        )
+        .await?;
+        let other_ctx = Ctx::new(other_id)?;
+        let fx_task = _dev_utils::seed_tasks(&root_ctx, &mm, &["test_thread_and_ownership_ok"])
+            .await?
+            .remove(0);
+
+        // -- Exec
+        let comment_id = CommentBmc::create(
+            &author_ctx,
+            &mm,
+            CommentForCreate {
+                task_id: fx_task.id,
+                parent_id: None,
+                body: "first".to_string(),
+            },
+        )
+        .await?;
+        let reply_id = CommentBmc::create(
+            &other_ctx,
+            &mm,
+            CommentForCreate {
+                task_id: fx_task.id,
+                parent_id: Some(comment_id),
+                body: "reply".to_string(),
+            },
+        )
+        .await?;
+        let update = || CommentForUpdate {
+            body: "first (edited)".to_string(),
+        };
+        let other_update_res = CommentBmc::update(&other_ctx, &mm, comment_id, update()).await;
+        let other_delete_res = CommentBmc::delete(&other_ctx, &mm, comment_id).await;
+        CommentBmc::update_with_version(&author_ctx, &mm, comment_id, update(), Some(1)).await?;
+        let stale_update_res =
+            CommentBmc::update_with_version(&author_ctx, &mm, comment_id, update(), Some(1)).await;
+        CommentBmc::delete(&author_ctx, &mm, comment_id).await?;
+
+        // -- Check
+        assert!(
+            matches!(
+                other_update_res,
+                Err(Error::AccessDenied { entity: "comment" })
+            ),
+            "update by other not denied"
+        );
+        assert!(
+            matches!(
+                other_delete_res,
+                Err(Error::AccessDenied { entity: "comment" })
+            ),
+            "delete by other not denied"
+        );
+        assert!(
+            matches!(
+                stale_update_res,
+                Err(Error::VersionConflict {
+                    entity: "comment",
+                    current: 2,
+                    ..
+                })
+            ),
+            "stale update not rejected"
+        );
+        let comments = CommentBmc::list_for_task(&root_ctx, &mm, fx_task.id, None).await?;
+        assert_eq!(comments.len(), 1, "only the reply (comment deleted)");
+        assert_eq!(comments[0].id, reply_id);
+        assert_eq!(comments[0].author_id, Some(other_id));
+        assert_eq!(comments[0].parent_id, Some(comment_id));
+
+        // -- Check - The reply outlives its author
+        base::delete::<UserBmc>(&root_ctx, &mm, other_id).await?;
+        let reply = CommentBmc::get(&root_ctx, &mm, reply_id).await?;
+        assert_eq!(reply.author_id, None);
+
+        // -- Cleanup
+        TaskBmc::delete(&root_ctx, &mm, fx_task.id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_err_root_ctx() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let root_ctx = Ctx::root_ctx();
+        let fx_task = _dev_utils::seed_tasks(&root_ctx, &mm, &["test_create_err_root_ctx"])
+            .await?
+            .remove(0);
+
+        // -- Exec
+        let res = CommentBmc::create(
+            &root_ctx,
+            &mm,
+            CommentForCreate {
+                task_id: fx_task.id,
+                parent_id: None,
+                body: "by root".to_string(),
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::AccessDenied { entity: "comment" })),
+            "root ctx comment not denied"
+        );
+
+        // -- Cleanup
+        TaskBmc::delete(&root_ctx, &mm, fx_task.id).await?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -13,10 +13,59 @@ pub enum Error {
         entity: &'static str,
         id: i64,
     },
+    VersionConflict {
+        entity: &'static str,
+        id: i64,
+        current: i64,
+    },
     ListLimitOverMax {
         max: i64,
         actual: i64,
     },
+    ListCursorInvalid,
+    ListCursorTypeNotSupported {
+        column: String,
+        pg_type: String,
+    },
+    ColumnNotAllowed {
+        entity: &'static str,
+        column: String,
+    },
+    SearchNotSupported {
+        entity: &'static str,
+    },
+    SoftDeleteNotSupported {
+        entity: &'static str,
+    },
+    SchemaVersionMismatch {
+        expected: i32,
+        actual: Option<i32>,
+    },
+    AccessDenied {
+        entity: &'static str,
+    },
+
+    // -- User
+    UserAlreadyExists {
+        username: String,
+    },
+
+    // -- Task
+    TaskCycle {
+        /// `parent` or `dependency`
+        relation: &'static str,
+        task_id: i64,
+        linked_id: i64,
+    },
+    TaskHasOpenChildren {
+        id: i64,
+        open_ids: Vec<i64>,
+    },
+
+    // -- Label
+    LabelAlreadyExists {
+        name: String,
+    },
 
     // -- Modules
     #[from]
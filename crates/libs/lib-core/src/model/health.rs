@@ -0,0 +1,85 @@
+//! Model layer health checks and db pool status (for the service probes and metrics).
+
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+
+/// The schema version the model layer needs (the last `schema_version` applied).
+/// Note: Bump it with the `schema_version` row on any schema change.
+const SCHEMA_VERSION: i32 = 3;
+
+/// Db pool connections snapshot.
+#[derive(Debug, Clone)]
+pub struct DbPoolStatus {
+    pub size: u32,   // Open connections (idle and in use).
+    pub idle: usize, // Open connections not in use.
+    pub max: u32,    // Max connections of the pool.
+}
+
+impl ModelManager {
+    pub fn db_pool_status(&self) -> DbPoolStatus {
+        let db = self.db();
+
+        DbPoolStatus {
+            size: db.size(),
+            idle: db.num_idle(),
+            max: db.options().get_max_connections(),
+        }
+    }
+
+    /// Check that the db pool can run a query.
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(self.db()).await?;
+
+        Ok(())
+    }
+
+    /// Check that the schema is applied, at the model layer version.
+    pub async fn check_schema(&self) -> Result<()> {
+        let db = self.db();
+
+        let (applied,): (bool,) =
+            sqlx::query_as("SELECT to_regclass('schema_version') IS NOT NULL")
+                .fetch_one(db)
+                .await?;
+        let actual = if applied {
+            let (version,): (Option<i32>,) =
+                sqlx::query_as("SELECT max(version) FROM schema_version")
+                    .fetch_one(db)
+                    .await?;
+            version
+        } else {
+            None
+        };
+
+        if actual != Some(SCHEMA_VERSION) {
+            return Err(Error::SchemaVersionMismatch {
+                expected: SCHEMA_VERSION,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use crate::_dev_utils;
+    use anyhow::Result;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_ping_and_check_schema_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+
+        // -- Exec & Check
+        mm.ping().await?;
+        mm.check_schema().await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
@@ -0,0 +1,431 @@
+//! Labels, and their many-to-many links to the tasks (`task_label`).
+//!
+//! - The label names are unique.
+//! - The tasks can be filtered by their labels, with the `TaskFilter` `label_ids`
+//!   (`{"$hasAny": [..]}` or `{"$hasAll": [..]}`), as `EXISTS` subqueries.
+//! - The labels of a list of tasks are fetched in one query (`list_by_task_ids`).
+
+use crate::ctx::Ctx;
+use crate::model::base::{self, CommonIden, DbBmc};
+use crate::model::task::TaskBmc;
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use modql::field::{Fields, HasFields};
+use modql::filter::{FilterNodes, ListOptions, OpValValue, OpValsInt64, OpValsString};
+use sea_query::{
+    Alias, ColumnRef, Condition, ConditionExpression, Expr, Iden, IntoIden, OnConflict,
+    PostgresQueryBuilder, Query, SelectStatement,
+};
+use sea_query_binder::SqlxBinder;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgConnection};
+use std::collections::HashMap;
+
+// region:         — Label Types
+
+#[derive(Debug, Clone, Serialize, Fields, FromRow)]
+pub struct Label {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Fields, Deserialize)]
+pub struct LabelForCreate {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(FilterNodes, Deserialize, Default, Debug)]
+pub struct LabelFilter {
+    id: Option<OpValsInt64>,
+    name: Option<OpValsString>,
+}
+
+#[derive(Iden)]
+enum LabelIden {
+    Name,
+}
+
+#[derive(Iden)]
+enum TaskLabelIden {
+    #[iden = "task_label"]
+    Table,
+    TaskId,
+    LabelId,
+}
+
+// endregion:      — Label Types
+
+pub struct LabelBmc;
+
+impl DbBmc for LabelBmc {
+    const TABLE: &'static str = "label";
+}
+
+impl LabelBmc {
+    /// Create a label (`Error::LabelAlreadyExists` when the name is taken).
+    pub async fn create(_ctx: &Ctx, mm: &ModelManager, label_c: LabelForCreate) -> Result<i64> {
+        let db = mm.db();
+        let name = label_c.name.clone();
+
+        // -- Build query
+        let fields = label_c.not_none_fields();
+        let (columns, sea_values) = fields.for_sea_insert();
+        let mut query = Query::insert();
+        query
+            .into_table(Self::table_ref())
+            .columns(columns)
+            .values(sea_values)?
+            .on_conflict(OnConflict::column(LabelIden::Name).do_nothing().to_owned())
+            .returning(Query::returning().columns([CommonIden::Id]));
+
+        // -- Exec query
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let (id,) = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
+            .fetch_optional(db)
+            .await?
+            .ok_or(Error::LabelAlreadyExists { name })?;
+
+        Ok(id)
+    }
+
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Label> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn list(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<LabelFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Label>> {
+        base::list::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    /// Delete a label (and its task links).
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+
+    /// Attach a label to a task (no-op when already attached).
+    pub async fn attach(ctx: &Ctx, mm: &ModelManager, task_id: i64, label_id: i64) -> Result<()> {
+        // Note: Checks that both exist (and that the task is not deleted).
+        TaskBmc::get(ctx, mm, task_id).await?;
+        Self::get(ctx, mm, label_id).await?;
+
+        let mut query = Query::insert();
+        query
+            .into_table(TaskLabelIden::Table)
+            .columns([TaskLabelIden::TaskId, TaskLabelIden::LabelId])
+            .values([task_id.into(), label_id.into()])?
+            .on_conflict(
+                OnConflict::columns([TaskLabelIden::TaskId, TaskLabelIden::LabelId])
+                    .do_nothing()
+                    .to_owned(),
+            );
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(mm.db()).await?;
+
+        Ok(())
+    }
+
+    /// Attach the labels of the `from_task_id` task to the `to_task_id` task,
+    /// on the `conn` connection (e.g., in the transaction creating the task).
+    pub(in crate::model) async fn copy_to_task_in(
+        conn: &mut PgConnection,
+        from_task_id: i64,
+        to_task_id: i64,
+    ) -> Result<()> {
+        let mut select = Query::select();
+        select
+            .expr(Expr::val(to_task_id))
+            .column(TaskLabelIden::LabelId)
+            .from(TaskLabelIden::Table)
+            .and_where(Expr::col(TaskLabelIden::TaskId).eq(from_task_id));
+
+        let mut query = Query::insert();
+        query
+            .into_table(TaskLabelIden::Table)
+            .columns([TaskLabelIden::TaskId, TaskLabelIden::LabelId])
+            .select_from(select)?
+            .on_conflict(
+                OnConflict::columns([TaskLabelIden::TaskId, TaskLabelIden::LabelId])
+                    .do_nothing()
+                    .to_owned(),
+            );
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(conn).await?;
+
+        Ok(())
+    }
+
+    /// Detach a label from a task (no-op when not attached).
+    pub async fn detach(_ctx: &Ctx, mm: &ModelManager, task_id: i64, label_id: i64) -> Result<()> {
+        let mut query = Query::delete();
+        query
+            .from_table(TaskLabelIden::Table)
+            .and_where(Expr::col(TaskLabelIden::TaskId).eq(task_id))
+            .and_where(Expr::col(TaskLabelIden::LabelId).eq(label_id));
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(mm.db()).await?;
+
+        Ok(())
+    }
+
+    /// The labels of each task (by task id, ordered by name), in one query.
+    pub async fn list_by_task_ids(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        task_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<Label>>> {
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(Label::field_column_refs())
+            .column((TaskLabelIden::Table, TaskLabelIden::TaskId))
+            .inner_join(
+                TaskLabelIden::Table,
+                Expr::col((TaskLabelIden::Table, TaskLabelIden::LabelId))
+                    .equals((Self::table_ref_iden(), CommonIden::Id)),
+            )
+            .and_where(
+                Expr::col((TaskLabelIden::Table, TaskLabelIden::TaskId))
+                    .is_in(task_ids.iter().copied()),
+            )
+            .order_by(
+                (Self::table_ref_iden(), LabelIden::Name),
+                sea_query::Order::Asc,
+            );
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let rows = sqlx::query_as_with::<_, TaskLabelRow, _>(&sql, values)
+            .fetch_all(mm.db())
+            .await?;
+
+        let mut labels_by_task_id: HashMap<i64, Vec<Label>> = HashMap::new();
+        for TaskLabelRow { task_id, label } in rows {
+            labels_by_task_id.entry(task_id).or_default().push(label);
+        }
+
+        Ok(labels_by_task_id)
+    }
+
+    fn table_ref_iden() -> Alias {
+        Alias::new(Self::TABLE)
+    }
+}
+
+#[derive(FromRow)]
+struct TaskLabelRow {
+    task_id: i64,
+    #[sqlx(flatten)]
+    label: Label,
+}
+
+// region:    --- TaskFilter label_ids
+
+/// The `TaskFilter` `label_ids` filter:
+/// - `{"$hasAny": [1, 2]}` the tasks with any of these labels.
+/// - `{"$hasAll": [1, 2]}` the tasks with all of these labels.
+///
+/// Note: As the modql `OpValValue`, `$hasAny` as `In`, and `$hasAll` as `Eq` (of the array).
+#[derive(Debug)]
+pub struct OpValsLabelIds(pub Vec<OpValValue>);
+
+impl<'de> Deserialize<'de> for OpValsLabelIds {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let ops = HashMap::<String, Vec<i64>>::deserialize(deserializer)?;
+
+        let mut op_vals = Vec::new();
+        for (op, ids) in ops {
+            let ids: Vec<Value> = ids.into_iter().map(Value::from).collect();
+            let op_val = match op.as_str() {
+                "$hasAny" => OpValValue::In(ids),
+                "$hasAll" => OpValValue::Eq(Value::Array(ids)),
+                _ => {
+                    return Err(D::Error::custom(format!(
+                        "label_ids operator '{op}' not supported ($hasAny, $hasAll)"
+                    )))
+                }
+            };
+            op_vals.push(op_val);
+        }
+
+        Ok(OpValsLabelIds(op_vals))
+    }
+}
+
+/// The `TaskFilter` `label_ids` condition, as `EXISTS` subqueries
+/// (one for `$hasAny`, one per label for `$hasAll`).
+pub(in crate::model) fn label_ids_to_sea_condition(
+    _col: &ColumnRef,
+    op_val: OpValValue,
+) -> modql::filter::SeaResult<ConditionExpression> {
+    let ids =
+        |values: Vec<Value>| -> Vec<i64> { values.iter().filter_map(Value::as_i64).collect() };
+
+    let cond = match op_val {
+        OpValValue::In(values) => {
+            Condition::all().add(Expr::exists(task_label_subquery(&ids(values))))
+        }
+        OpValValue::Eq(Value::Array(values)) => {
+            ids(values).into_iter().fold(Condition::all(), |cond, id| {
+                cond.add(Expr::exists(task_label_subquery(&[id])))
+            })
+        }
+        // Note: Not produced by the `OpValsLabelIds` deserializer (empty any is false).
+        _ => Condition::any(),
+    };
+
+    Ok(cond.into())
+}
+
+/// `SELECT 1 FROM task_label WHERE task_label.task_id = task.id AND label_id IN (..)`
+fn task_label_subquery(label_ids: &[i64]) -> SelectStatement {
+    Query::select()
+        .expr(Expr::val(1))
+        .from(TaskLabelIden::Table)
+        .and_where(
+            Expr::col((TaskLabelIden::Table, TaskLabelIden::TaskId))
+                .equals((Alias::new(TaskBmc::TABLE), CommonIden::Id.into_iden())),
+        )
+        .and_where(Expr::col(TaskLabelIden::LabelId).is_in(label_ids.iter().copied()))
+        .to_owned()
+}
+
+// endregion: --- TaskFilter label_ids
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use crate::model::task::TaskFilter;
+    use anyhow::Result;
+    use serde_json::json;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_attach_and_filter_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_tasks = _dev_utils::seed_tasks(
+            &ctx,
+            &mm,
+            &[
+                "test_attach_and_filter_ok-task 01",
+                "test_attach_and_filter_ok-task 02",
+                "test_attach_and_filter_ok-task 03",
+            ],
+        )
+        .await?;
+        let mut fx_label_ids = Vec::new();
+        for name in [
+            "test_attach_and_filter_ok-bug",
+            "test_attach_and_filter_ok-ui",
+        ] {
+            let label_c = LabelForCreate {
+                name: name.to_string(),
+                color: None,
+            };
+            fx_label_ids.push(LabelBmc::create(&ctx, &mm, label_c).await?);
+        }
+        let (bug_id, ui_id) = (fx_label_ids[0], fx_label_ids[1]);
+        LabelBmc::attach(&ctx, &mm, fx_tasks[0].id, bug_id).await?;
+        LabelBmc::attach(&ctx, &mm, fx_tasks[0].id, ui_id).await?;
+        LabelBmc::attach(&ctx, &mm, fx_tasks[1].id, ui_id).await?;
+        LabelBmc::attach(&ctx, &mm, fx_tasks[1].id, ui_id).await?; // no-op
+
+        // -- Exec
+        let list_ids = |label_ids: serde_json::Value| {
+            let mm = mm.clone();
+            let ctx = ctx.clone();
+            async move {
+                let filters: Vec<TaskFilter> = serde_json::from_value(json!([{
+                    "title": {"$startsWith": "test_attach_and_filter_ok"},
+                    "label_ids": label_ids
+                }]))?;
+                let tasks = TaskBmc::list(&ctx, &mm, Some(filters), None).await?;
+                Ok::<_, anyhow::Error>(tasks.into_iter().map(|t| t.id).collect::<Vec<_>>())
+            }
+        };
+        let any_ids = list_ids(json!({"$hasAny": [bug_id, ui_id]})).await?;
+        let all_ids = list_ids(json!({"$hasAll": [bug_id, ui_id]})).await?;
+        let task_ids: Vec<i64> = fx_tasks.iter().map(|t| t.id).collect();
+        let labels_by_task_id = LabelBmc::list_by_task_ids(&ctx, &mm, &task_ids).await?;
+        LabelBmc::detach(&ctx, &mm, fx_tasks[0].id, bug_id).await?;
+        let all_ids_detached = list_ids(json!({"$hasAll": [bug_id, ui_id]})).await?;
+
+        // -- Check
+        assert_eq!(any_ids, [fx_tasks[0].id, fx_tasks[1].id]);
+        assert_eq!(all_ids, [fx_tasks[0].id]);
+        assert!(all_ids_detached.is_empty());
+        let names = |task_id: i64| -> Vec<String> {
+            labels_by_task_id
+                .get(&task_id)
+                .map(|labels| labels.iter().map(|l| l.name.clone()).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(
+            names(fx_tasks[0].id),
+            [
+                "test_attach_and_filter_ok-bug",
+                "test_attach_and_filter_ok-ui"
+            ]
+        );
+        assert_eq!(names(fx_tasks[1].id), ["test_attach_and_filter_ok-ui"]);
+        assert!(names(fx_tasks[2].id).is_empty());
+
+        // -- Cleanup
+        for id in fx_label_ids {
+            LabelBmc::delete(&ctx, &mm, id).await?;
+        }
+        for task in fx_tasks {
+            TaskBmc::delete(&ctx, &mm, task.id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_err_already_exists() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_name = "test_create_err_already_exists-label";
+        let label_c = || LabelForCreate {
+            name: fx_name.to_string(),
+            color: Some("red".to_string()),
+        };
+        let id = LabelBmc::create(&ctx, &mm, label_c()).await?;
+
+        // -- Exec
+        let res = LabelBmc::create(&ctx, &mm, label_c()).await;
+
+        // -- Check
+        assert!(
+            matches!(&res, Err(Error::LabelAlreadyExists { name }) if name == fx_name),
+            "LabelAlreadyExists not matched"
+        );
+
+        // -- Cleanup
+        LabelBmc::delete(&ctx, &mm, id).await?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,187 @@
+//! Keyset (cursor) pagination support for `base::list_page`.
+//!
+//! - The sort keys are the list options `order_bys`, plus `id` (when not already present)
+//!   as a unique tie-breaker.
+//! - The cursor is the b64u of the JSON sort key values of the last row of the page,
+//!   as `[[pg_type, text_value_or_null], ...]`, which are cast to the sort column pg type
+//!   (from the db catalog, the cursor pg type must be the same) when building
+//!   the `WHERE` clause (i.e., any `order_bys` columns and directions).
+//! - NULL values sort as Postgres does by default (last for ASC, first for DESC).
+//! - The Postgres enum keys are compared as their enum type (i.e., in the enum order).
+
+use crate::model::{Error, Result};
+use lib_utils::b64::{b64u_decode, b64u_encode};
+use modql::filter::OrderBy;
+use sea_query::{Alias, Condition, Expr, SimpleExpr};
+use serde::Serialize;
+use sqlx::postgres::{PgRow, PgTypeKind};
+use sqlx::{Column, Row, TypeInfo};
+use std::collections::HashMap;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+/// A page of a keyset paginated list.
+#[derive(Debug, Serialize)]
+pub struct ListPage<E> {
+    pub items: Vec<E>,
+    /// `None` when this is the last page.
+    pub next_cursor: Option<String>,
+    /// The total count of the filtered list (when requested).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+/// The sort keys, with the `id` tie-breaker.
+pub(in crate::model) fn sort_keys(order_bys: Option<Vec<OrderBy>>) -> Vec<OrderBy> {
+    let mut keys = order_bys.unwrap_or_default();
+    let has_id = keys.iter().any(|key| key_col(key) == "id");
+    if !has_id {
+        keys.push(OrderBy::Asc("id".to_string()));
+    }
+    keys
+}
+
+fn key_col(key: &OrderBy) -> &str {
+    match key {
+        OrderBy::Asc(col) | OrderBy::Desc(col) => col,
+    }
+}
+
+/// The sort key values (pg type, text value) of a row.
+pub(in crate::model) struct ListCursor(Vec<(String, Option<String>)>);
+
+impl ListCursor {
+    pub fn from_row(row: &PgRow, keys: &[OrderBy]) -> Result<Self> {
+        let values = keys
+            .iter()
+            .map(|key| key_value(row, key_col(key)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ListCursor(values))
+    }
+
+    pub fn encode(&self) -> Result<String> {
+        let json = serde_json::to_vec(&self.0).map_err(|_| Error::ListCursorInvalid)?;
+        Ok(b64u_encode(json))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let json = b64u_decode(cursor).map_err(|_| Error::ListCursorInvalid)?;
+        let values: Vec<(String, Option<String>)> =
+            serde_json::from_slice(&json).map_err(|_| Error::ListCursorInvalid)?;
+
+        Ok(ListCursor(values))
+    }
+
+    /// The condition for the rows after the cursor, for the same sort keys,
+    /// with `col_types` the table columns pg type names.
+    ///
+    /// For the keys `k1..kn`, any of `(k1..ki-1 equal, and ki after)`.
+    pub fn after_cond(
+        &self,
+        keys: &[OrderBy],
+        col_types: &HashMap<String, String>,
+    ) -> Result<Condition> {
+        if keys.len() != self.0.len() {
+            return Err(Error::ListCursorInvalid);
+        }
+
+        // Note: The sql casts use the column types (never the client cursor ones).
+        let key_values = keys
+            .iter()
+            .zip(&self.0)
+            .map(|(key, (pg_type, value))| {
+                let col_type = col_types
+                    .get(key_col(key))
+                    .filter(|col_type| *col_type == pg_type)
+                    .ok_or(Error::ListCursorInvalid)?;
+                Ok((key, (col_type.clone(), value.clone())))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut any = Condition::any();
+        for (i, (key, value)) in key_values.iter().enumerate() {
+            let Some(after) = key_after(key, value) else {
+                continue;
+            };
+
+            let all = key_values[..i]
+                .iter()
+                .fold(Condition::all(), |all, (key, value)| {
+                    all.add(key_eq(key, value))
+                });
+            any = any.add(all.add(after));
+        }
+
+        Ok(any)
+    }
+}
+
+// region:    --- Support
+
+fn key_value(row: &PgRow, col: &str) -> Result<(String, Option<String>)> {
+    let type_info = row.try_column(col)?.type_info();
+    let pg_type = type_info.name().to_lowercase();
+
+    // Note: The enum values are sent as text (hence the unchecked get).
+    if matches!(type_info.kind(), PgTypeKind::Enum(_)) {
+        let value = row.try_get_unchecked::<Option<String>, _>(col)?;
+        return Ok((pg_type, value));
+    }
+
+    let value = match pg_type.as_str() {
+        "int2" => row.try_get::<Option<i16>, _>(col)?.map(|v| v.to_string()),
+        "int4" => row.try_get::<Option<i32>, _>(col)?.map(|v| v.to_string()),
+        "int8" => row.try_get::<Option<i64>, _>(col)?.map(|v| v.to_string()),
+        "float4" => row.try_get::<Option<f32>, _>(col)?.map(|v| v.to_string()),
+        "float8" => row.try_get::<Option<f64>, _>(col)?.map(|v| v.to_string()),
+        "bool" => row.try_get::<Option<bool>, _>(col)?.map(|v| v.to_string()),
+        "text" | "varchar" => row.try_get::<Option<String>, _>(col)?,
+        "uuid" => row.try_get::<Option<Uuid>, _>(col)?.map(|v| v.to_string()),
+        "date" => row.try_get::<Option<Date>, _>(col)?.map(|v| v.to_string()),
+        "timestamptz" => row
+            .try_get::<Option<OffsetDateTime>, _>(col)?
+            .map(|v| v.format(&Rfc3339))
+            .transpose()
+            .map_err(|_| Error::ListCursorInvalid)?,
+        _ => {
+            return Err(Error::ListCursorTypeNotSupported {
+                column: col.to_string(),
+                pg_type,
+            })
+        }
+    };
+
+    Ok((pg_type, value))
+}
+
+fn cast_value(pg_type: &str, value: &str) -> SimpleExpr {
+    Expr::val(value).cast_as(Alias::new(pg_type))
+}
+
+/// The key equal to the cursor value.
+fn key_eq(key: &OrderBy, (pg_type, value): &(String, Option<String>)) -> SimpleExpr {
+    let col = Expr::col(Alias::new(key_col(key)));
+    match value {
+        Some(value) => col.eq(cast_value(pg_type, value)),
+        None => col.is_null(),
+    }
+}
+
+/// The key after the cursor value (`None` when no value can be after).
+fn key_after(key: &OrderBy, (pg_type, value): &(String, Option<String>)) -> Option<SimpleExpr> {
+    let col = || Expr::col(Alias::new(key_col(key)));
+    match (key, value) {
+        // ASC, NULLS LAST
+        (OrderBy::Asc(_), Some(value)) => {
+            Some(col().gt(cast_value(pg_type, value)).or(col().is_null()))
+        }
+        (OrderBy::Asc(_), None) => None,
+        // DESC, NULLS FIRST
+        (OrderBy::Desc(_), Some(value)) => Some(col().lt(cast_value(pg_type, value))),
+        (OrderBy::Desc(_), None) => Some(col().is_not_null()),
+    }
+}
+
+// endregion: --- Support
@@ -6,19 +6,34 @@
 //! -  All application data access should be done through the Model layer.
 //! -  The `ModelManager` holds the internal states/resources needed by ModelControllers to
 //!    access data.
-//!  - Model Controllers (e.g., `TaskBmc`, `ProjectBmc`) implement CRUD and other data
-//!   access methods on a given entity.
-//!   (`Bmc` stands for Business Model Controller).
-//! - In frameworks like Axum, Tauri, `ModelManager` is typically used as App State
-//!   to all Model Controllers functions.
+//! -  Model Controllers (e.g., `TaskBmc`, `ProjectBmc`) implement CRUD and other data
+//!    access methods on a given entity.
+//!    (`Bmc` stands for Business Model Controller).
+//! -  In frameworks like Axum, Tauri, `ModelManager` is typically used as App State
+//!    to all Model Controllers functions.
 // region:         ---Modules
+mod aggregate;
+pub mod audit_log;
 mod base; // private to the model layer
+pub mod comment;
 mod error;
+mod health;
+pub mod label;
+mod list_cursor;
+pub mod recurrence;
+pub mod request_log;
+mod search;
 mod store;
 pub mod task; // only task is public for now
+pub mod task_dependency;
 pub mod user;
+pub mod user_session;
 
+pub use self::aggregate::{Aggregate, Aggregation, GroupBy};
 pub use self::error::{Error, Result};
+pub use self::health::DbPoolStatus;
+pub use self::list_cursor::ListPage;
+pub use self::search::SearchHit;
 
 use crate::model::store::{new_db_pool, Db};
 
@@ -32,10 +47,18 @@ pub struct ModelManager {
 impl ModelManager {
     pub async fn new() -> Result<Self> {
         let db = new_db_pool().await?;
+        // Note: Fails fast (at startup) on a wrong model configuration.
+        task::OpenChildrenPolicy::from_config();
         // FIXME - OTBC
         Ok(ModelManager { db })
     }
 
+    /// Close the database pool (waits for the checked out connections to be returned).
+    /// Typically called on server shutdown.
+    pub async fn close(&self) {
+        self.db.close().await;
+    }
+
     /// Returns a reference to the database pool.
     /// Only for the model layer internal use.
     pub(in crate::model) fn db(&self) -> &Db {
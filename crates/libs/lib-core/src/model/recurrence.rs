@@ -0,0 +1,533 @@
+//! Task recurrence rules, as a subset of the iCalendar RRULE
+//! (e.g., `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH`).
+//!
+//! - `FREQ` is `DAILY`, `WEEKLY` or `MONTHLY`, and `INTERVAL` (default 1) the number
+//!   of days, weeks or months between the occurrences.
+//! - `BYDAY` is a list of weekdays (`MO`..`SU`) for `DAILY` and `WEEKLY` (the weeks start
+//!   on Monday), and a single nth weekday for `MONTHLY` (e.g., `2TU`, or `-1FR` for the last).
+//! - `BYMONTHDAY` (1 to 31, `MONTHLY` only) is the day of the month, clamped to the month
+//!   length (e.g., `31` is the last day of each month). Without `BYMONTHDAY` nor `BYDAY`,
+//!   the monthly occurrences are on the day of the first one (its `BYMONTHDAY` when
+//!   stored, see `anchored`), so that a Jan 31 rule is not on the 28th after February.
+//! - When a recurring task is completed, its next occurrence is created by `TaskBmc`,
+//!   due on the `next_due` date of the completed task due date (or of today).
+
+use lib_utils::time::{add_days, month_day_clamped, month_start_after, nth_weekday_of_month};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use sqlx::postgres::{PgTypeInfo, PgValueRef};
+use sqlx::{Postgres, ValueRef};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use time::{Date, Weekday};
+use tracing::warn;
+
+const INTERVAL_MAX: u32 = 1000;
+
+/// The maximum number of missed occurrences skipped by `next_due` (e.g., about 27 years
+/// of a daily task).
+const NEXT_DUE_SKIP_MAX: usize = 10_000;
+
+/// The maximum `count` of `occurrences_after`.
+pub const OCCURRENCES_MAX: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq, DeserializeFromStr, SerializeDisplay)]
+pub struct Recurrence {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<RuleDay>,
+    pub by_month_day: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// A `BYDAY` weekday, with its nth in the month (`MONTHLY` only).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleDay {
+    pub nth: Option<i8>,
+    pub weekday: Weekday,
+}
+
+// region:    --- Next Occurrences
+
+impl Recurrence {
+    /// The first occurrence after `date` (`None` when the rule has none, e.g.,
+    /// `FREQ=DAILY;INTERVAL=7;BYDAY=MO` after a Tuesday).
+    pub fn next_after(&self, date: Date) -> Option<Date> {
+        match self.freq {
+            Frequency::Daily => self.next_daily(date),
+            Frequency::Weekly => self.next_weekly(date),
+            Frequency::Monthly => self.next_monthly(date),
+        }
+    }
+
+    /// The due date of the next occurrence of a task due on `due_date`, the first occurrence
+    /// after it, and not before `today` (the missed occurrences of an overdue task are skipped).
+    pub fn next_due(&self, due_date: Date, today: Date) -> Option<Date> {
+        let rule = self.clone().anchored(due_date);
+        std::iter::successors(rule.next_after(due_date), |date| rule.next_after(*date))
+            .take(NEXT_DUE_SKIP_MAX)
+            .find(|date| *date >= today)
+    }
+
+    /// The next `count` occurrences after `date` (at most `OCCURRENCES_MAX`).
+    pub fn occurrences_after(&self, date: Date, count: usize) -> Vec<Date> {
+        let rule = self.clone().anchored(date);
+        std::iter::successors(rule.next_after(date), |date| rule.next_after(*date))
+            .take(count.min(OCCURRENCES_MAX))
+            .collect()
+    }
+
+    /// The rule with the day of the `start` date as its `BYMONTHDAY` when `MONTHLY` without
+    /// `BYMONTHDAY` nor `BYDAY` (the other rules are unchanged), so that the monthly
+    /// occurrences keep that day (e.g., Jan 31, Feb 28, Mar 31), also once stored.
+    pub fn anchored(mut self, start: Date) -> Self {
+        if self.freq == Frequency::Monthly && self.by_day.is_empty() {
+            self.by_month_day = self.by_month_day.or(Some(start.day()));
+        }
+        self
+    }
+
+    fn next_daily(&self, date: Date) -> Option<Date> {
+        let interval = self.interval as i64;
+        if self.by_day.is_empty() {
+            return add_days(date, interval);
+        }
+
+        // Note: The weekdays repeat after 7 intervals.
+        (1..=7)
+            .filter_map(|i| add_days(date, i * interval))
+            .find(|date| self.has_weekday(date.weekday()))
+    }
+
+    fn next_weekly(&self, date: Date) -> Option<Date> {
+        if self.by_day.is_empty() {
+            return add_days(date, 7 * self.interval as i64);
+        }
+
+        let mut offsets: Vec<i64> = self
+            .by_day
+            .iter()
+            .map(|day| day.weekday.number_days_from_monday() as i64)
+            .collect();
+        offsets.sort_unstable();
+
+        let date_offset = date.weekday().number_days_from_monday() as i64;
+        let week_start = add_days(date, -date_offset)?;
+        match offsets.iter().find(|offset| **offset > date_offset) {
+            // Later in the same week
+            Some(offset) => add_days(week_start, *offset),
+            // First day of the next week of the rule
+            None => add_days(week_start, 7 * self.interval as i64 + offsets[0]),
+        }
+    }
+
+    fn next_monthly(&self, date: Date) -> Option<Date> {
+        let month_date = |month_start: Date| match self.by_day.first() {
+            Some(day) => nth_weekday_of_month(month_start, day.weekday, day.nth.unwrap_or(1)),
+            None => Some(month_day_clamped(
+                month_start,
+                self.by_month_day.unwrap_or(date.day()),
+            )),
+        };
+
+        // Note: In the same month when still ahead, otherwise in the next months of the rule
+        //       (a nth weekday, e.g., a 5th Monday, is in at least one of 12 months).
+        let month_start = month_start_after(date, 0)?;
+        if let Some(next) = month_date(month_start).filter(|next| *next > date) {
+            return Some(next);
+        }
+        (1..=12)
+            .filter_map(|i| month_start_after(date, i * self.interval))
+            .find_map(month_date)
+    }
+
+    fn has_weekday(&self, weekday: Weekday) -> bool {
+        self.by_day.iter().any(|day| day.weekday == weekday)
+    }
+}
+
+// endregion: --- Next Occurrences
+
+// region:    --- Parse & Display
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim().to_ascii_uppercase();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(&rule);
+
+        let mut freq = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut by_month_day = None;
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("recurrence part '{part}' is not NAME=VALUE"))?;
+            match name {
+                "FREQ" => {
+                    freq = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(format!("recurrence FREQ '{other}' not supported")),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| (1..=INTERVAL_MAX).contains(interval))
+                        .ok_or_else(|| format!("recurrence INTERVAL '{value}' not valid"))?
+                }
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(RuleDay::from_str)
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|day| (1..=31).contains(day))
+                            .ok_or_else(|| format!("recurrence BYMONTHDAY '{value}' not valid"))?,
+                    )
+                }
+                other => return Err(format!("recurrence part '{other}' not supported")),
+            }
+        }
+        let freq = freq.ok_or("recurrence FREQ missing")?;
+
+        // -- Check the parts of the frequency
+        let has_nth = by_day.iter().any(|day: &RuleDay| day.nth.is_some());
+        match freq {
+            Frequency::Daily | Frequency::Weekly if has_nth || by_month_day.is_some() => {
+                return Err("recurrence BYDAY nth and BYMONTHDAY are only for MONTHLY".into())
+            }
+            Frequency::Monthly if by_day.len() > 1 || (!by_day.is_empty() && !has_nth) => {
+                return Err("recurrence MONTHLY BYDAY must be a single nth weekday".into())
+            }
+            Frequency::Monthly if !by_day.is_empty() && by_month_day.is_some() => {
+                return Err("recurrence MONTHLY cannot have both BYDAY and BYMONTHDAY".into())
+            }
+            _ => (),
+        }
+
+        Ok(Recurrence {
+            freq,
+            interval,
+            by_day,
+            by_month_day,
+        })
+    }
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={freq}")?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let by_day: Vec<String> = self.by_day.iter().map(|day| day.to_string()).collect();
+            write!(f, ";BYDAY={}", by_day.join(","))?;
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={day}")?;
+        }
+
+        Ok(())
+    }
+}
+
+const WEEKDAY_CODES: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Monday),
+    ("TU", Weekday::Tuesday),
+    ("WE", Weekday::Wednesday),
+    ("TH", Weekday::Thursday),
+    ("FR", Weekday::Friday),
+    ("SA", Weekday::Saturday),
+    ("SU", Weekday::Sunday),
+];
+
+impl FromStr for RuleDay {
+    type Err = String;
+
+    fn from_str(day: &str) -> Result<Self, Self::Err> {
+        let not_valid = || format!("recurrence BYDAY '{day}' not valid");
+        // Note: ASCII only, for the byte index split below (on a char boundary).
+        if !day.is_ascii() {
+            return Err(not_valid());
+        }
+        let (nth, code) = day.split_at(day.len().checked_sub(2).ok_or_else(not_valid)?);
+        let weekday = WEEKDAY_CODES
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, weekday)| *weekday)
+            .ok_or_else(not_valid)?;
+        let nth = match nth {
+            "" => None,
+            nth => Some(
+                nth.parse::<i8>()
+                    .ok()
+                    .filter(|nth| (-5..=5).contains(nth) && *nth != 0)
+                    .ok_or_else(not_valid)?,
+            ),
+        };
+
+        Ok(RuleDay { nth, weekday })
+    }
+}
+
+impl Display for RuleDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(nth) = self.nth {
+            write!(f, "{nth}")?;
+        }
+        let (code, _) = WEEKDAY_CODES
+            .iter()
+            .find(|(_, weekday)| *weekday == self.weekday)
+            .expect("all weekdays have a code");
+
+        f.write_str(code)
+    }
+}
+
+// endregion: --- Parse & Display
+
+// region:    --- Db Types
+
+// Stored as the rule text (e.g., `varchar` column).
+
+impl From<Recurrence> for sea_query::Value {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string().into()
+    }
+}
+
+impl sea_query::Nullable for Recurrence {
+    fn null() -> sea_query::Value {
+        sea_query::Value::String(None)
+    }
+}
+
+/// The stored (nullable) rule, decoded leniently: a rule that does not parse (e.g., written
+/// around the model) is read as no recurrence (and logged), rather than failing the whole
+/// query (e.g., a task list). Decoded into the `Task` `recurrence` with `#[sqlx(try_from)]`.
+pub struct StoredRecurrence(Option<Recurrence>);
+
+impl From<StoredRecurrence> for Option<Recurrence> {
+    fn from(stored: StoredRecurrence) -> Self {
+        stored.0
+    }
+}
+
+impl sqlx::Type<Postgres> for StoredRecurrence {
+    fn type_info() -> PgTypeInfo {
+        <String as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for StoredRecurrence {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        if value.is_null() {
+            return Ok(StoredRecurrence(None));
+        }
+        let rule = <String as sqlx::Decode<Postgres>>::decode(value)?;
+
+        match rule.parse() {
+            Ok(recurrence) => Ok(StoredRecurrence(Some(recurrence))),
+            Err(ex) => {
+                warn!(
+                    "{:<12} - recurrence '{rule}' read as none - {ex}",
+                    "DB_DECODE"
+                );
+                Ok(StoredRecurrence(None))
+            }
+        }
+    }
+}
+
+// endregion: --- Db Types
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use time::macros::date;
+
+    #[test]
+    fn test_parse_display_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_rules = [
+            ("FREQ=DAILY", "FREQ=DAILY"),
+            (
+                "rrule:freq=weekly;interval=2;byday=mo,th",
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH",
+            ),
+            ("FREQ=MONTHLY;BYDAY=-1FR", "FREQ=MONTHLY;BYDAY=-1FR"),
+            (
+                "FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=31",
+                "FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=31",
+            ),
+        ];
+        let fx_invalid_rules = [
+            "INTERVAL=2",
+            "FREQ=YEARLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=MONTHLY;BYDAY=1MO;BYMONTHDAY=1",
+            "FREQ=DAILY;COUNT=3",
+        ];
+
+        // -- Exec & Check
+        for (rule, display) in fx_rules {
+            let recurrence: Recurrence = rule.parse().map_err(anyhow::Error::msg)?;
+            assert_eq!(recurrence.to_string(), display);
+        }
+        for rule in fx_invalid_rules {
+            assert!(
+                rule.parse::<Recurrence>().is_err(),
+                "'{rule}' should not parse"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_err_byday_not_ascii() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_rules = [
+            "FREQ=WEEKLY;BYDAY=éA",
+            "FREQ=WEEKLY;BYDAY=MOé",
+            "FREQ=MONTHLY;BYDAY=１MO",
+            "FREQ=WEEKLY;BYDAY=é",
+        ];
+
+        // -- Exec & Check
+        for rule in fx_rules {
+            let err = rule.parse::<Recurrence>().err();
+            assert!(
+                err.as_deref().is_some_and(|err| err.contains("BYDAY")),
+                "'{rule}' should not parse (BYDAY), got {err:?}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_occurrences_after_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        // 2026-10-19 is a Monday.
+        let fx_date = date!(2026 - 10 - 19);
+        let fx_cases = [
+            (
+                "FREQ=DAILY;INTERVAL=2",
+                vec![date!(2026 - 10 - 21), date!(2026 - 10 - 23)],
+            ),
+            (
+                "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR",
+                vec![
+                    date!(2026 - 10 - 20),
+                    date!(2026 - 10 - 21),
+                    date!(2026 - 10 - 22),
+                ],
+            ),
+            (
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH",
+                vec![
+                    date!(2026 - 10 - 22),
+                    date!(2026 - 11 - 02),
+                    date!(2026 - 11 - 05),
+                ],
+            ),
+            (
+                "FREQ=MONTHLY;BYMONTHDAY=31",
+                vec![
+                    date!(2026 - 10 - 31),
+                    date!(2026 - 11 - 30),
+                    date!(2026 - 12 - 31),
+                ],
+            ),
+            (
+                "FREQ=MONTHLY;BYDAY=-1FR",
+                vec![date!(2026 - 10 - 30), date!(2026 - 11 - 27)],
+            ),
+            (
+                "FREQ=MONTHLY;BYDAY=5MO",
+                vec![date!(2026 - 11 - 30), date!(2027 - 03 - 29)],
+            ),
+        ];
+
+        // -- Exec & Check
+        for (rule, fx_dates) in fx_cases {
+            let recurrence: Recurrence = rule.parse().map_err(anyhow::Error::msg)?;
+            let dates = recurrence.occurrences_after(fx_date, fx_dates.len());
+            assert_eq!(dates, fx_dates, "rule '{rule}'");
+        }
+        let weekly: Recurrence = "FREQ=WEEKLY;BYDAY=TH".parse().map_err(anyhow::Error::msg)?;
+        let next_due = weekly.next_due(date!(2026 - 09 - 03), fx_date);
+        assert_eq!(
+            next_due,
+            Some(date!(2026 - 10 - 22)),
+            "missed occurrences skipped"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_monthly_anchor_day_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_monthly: Recurrence = "FREQ=MONTHLY".parse().map_err(anyhow::Error::msg)?;
+
+        // -- Exec
+        let dates = fx_monthly.occurrences_after(date!(2027 - 12 - 31), 3);
+        let leap_dates = fx_monthly.occurrences_after(date!(2028 - 01 - 30), 2);
+        let anchored = fx_monthly.clone().anchored(date!(2027 - 01 - 31));
+        // The next occurrence of the Feb 28 task (as created from the Jan 31 one).
+        let next_due = anchored.next_due(date!(2027 - 02 - 28), date!(2027 - 02 - 28));
+
+        // -- Check
+        assert_eq!(
+            dates,
+            [
+                date!(2028 - 01 - 31),
+                date!(2028 - 02 - 29),
+                date!(2028 - 03 - 31)
+            ]
+        );
+        assert_eq!(leap_dates, [date!(2028 - 02 - 29), date!(2028 - 03 - 30)]);
+        assert_eq!(anchored.to_string(), "FREQ=MONTHLY;BYMONTHDAY=31");
+        assert_eq!(next_due, Some(date!(2027 - 03 - 31)), "not on the 28th");
+        let weekly: Recurrence = "FREQ=WEEKLY".parse().map_err(anyhow::Error::msg)?;
+        assert_eq!(weekly.clone().anchored(date!(2027 - 01 - 31)), weekly);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,132 @@
+//! Persisted request log lines (one per served request), for the web-server
+//! `db` request log sink.
+
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::ModelManager;
+use crate::model::Result;
+use modql::field::Fields;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+// region:         — RequestLog Types
+
+#[derive(Debug, Clone, Serialize, Fields, FromRow)]
+pub struct RequestLog {
+    pub id: i64,
+
+    pub uuid: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+    pub duration_ms: f64,
+
+    pub user_id: Option<i64>,
+    pub client_ip: Option<String>,
+
+    pub http_method: String,
+    pub http_path: String,
+    pub http_status: i32,
+
+    pub rpc_id: Option<String>,
+    pub rpc_method: Option<String>,
+
+    pub client_error_type: Option<String>,
+    pub error_type: Option<String>,
+    pub error_data: Option<Value>,
+}
+
+#[derive(Debug, Clone, Fields)]
+pub struct RequestLogForCreate {
+    pub uuid: Uuid,
+    pub timestamp: OffsetDateTime,
+    pub duration_ms: f64,
+
+    pub user_id: Option<i64>,
+    pub client_ip: Option<String>,
+
+    pub http_method: String,
+    pub http_path: String,
+    pub http_status: i32,
+
+    pub rpc_id: Option<String>,
+    pub rpc_method: Option<String>,
+
+    pub client_error_type: Option<String>,
+    pub error_type: Option<String>,
+    pub error_data: Option<Value>,
+}
+
+// endregion:      — RequestLog Types
+
+pub struct RequestLogBmc;
+
+impl DbBmc for RequestLogBmc {
+    const TABLE: &'static str = "request_log";
+}
+
+impl RequestLogBmc {
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, log_c: RequestLogForCreate) -> Result<i64> {
+        base::create::<Self, _>(ctx, mm, log_c).await
+    }
+
+    pub async fn get(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<RequestLog> {
+        base::get::<Self, _>(ctx, mm, id).await
+    }
+
+    pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        base::delete::<Self>(ctx, mm, id).await
+    }
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use anyhow::Result;
+    use lib_utils::time::now_utc;
+    use serde_json::json;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_uuid = Uuid::new_v4();
+        let fx_log_c = RequestLogForCreate {
+            uuid: fx_uuid,
+            timestamp: now_utc(),
+            duration_ms: 1.5,
+            user_id: Some(1000),
+            client_ip: Some("127.0.0.1".to_string()),
+            http_method: "POST".to_string(),
+            http_path: "/api/rpc".to_string(),
+            http_status: 400,
+            rpc_id: Some("1".to_string()),
+            rpc_method: Some("get_task".to_string()),
+            client_error_type: Some("ENTITY_NOT_FOUND".to_string()),
+            error_type: Some("Rpc".to_string()),
+            error_data: Some(json!({"entity": "task", "id": 1})),
+        };
+
+        // -- Exec
+        let id = RequestLogBmc::create(&ctx, &mm, fx_log_c).await?;
+
+        // -- Check
+        let log = RequestLogBmc::get(&ctx, &mm, id).await?;
+        assert_eq!(log.uuid, fx_uuid);
+        assert_eq!(log.http_status, 400);
+        assert_eq!(log.error_data, Some(json!({"entity": "task", "id": 1})));
+
+        // -- Clean
+        RequestLogBmc::delete(&ctx, &mm, id).await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
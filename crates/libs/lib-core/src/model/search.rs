@@ -0,0 +1,169 @@
+//! Full-text search support for `base::search`, and the `search` filters
+//! (e.g., `TaskFilter` `{"search": "some terms"}`, for `base::list`).
+//!
+//! - The searchable tables (`DbBmc::SEARCH_COLUMNS`) have a `search_tsv` tsvector column,
+//!   generated from their text columns (with the `SEARCH_CONFIG` text search config),
+//!   and a GIN index on it.
+//! - The search terms use the web search syntax (e.g., `"exact phrase" -excluded or other`).
+//! - The hits are ranked with `ts_rank`, and have a snippet of the matching text
+//!   (`ts_headline`), HTML escaped, with the matches between `<b>` and `</b>`.
+
+use modql::filter::OpValValue;
+use sea_query::extension::postgres::PgBinOper;
+use sea_query::{Alias, ColumnRef, Condition, ConditionExpression, Expr, Func, Iden, SimpleExpr};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// The Postgres text search config, which must be the one of the `search_tsv` columns
+/// (for the search terms to be normalized the same way).
+const SEARCH_CONFIG: &str = "english";
+
+/// The `ts_headline` match markers (private use chars), replaced by `<b>` and `</b>`
+/// once the snippet is escaped (see `snippet_html`).
+const MATCH_START: char = '\u{E000}';
+const MATCH_STOP: char = '\u{E001}';
+
+fn snippet_options() -> String {
+    format!(
+        r#"MaxFragments=2, MaxWords=24, MinWords=8, StartSel="{MATCH_START}", StopSel="{MATCH_STOP}""#
+    )
+}
+
+#[derive(Iden)]
+pub(in crate::model) enum SearchIden {
+    #[iden = "search_tsv"]
+    Tsv,
+    #[iden = "search_rank"]
+    Rank,
+    #[iden = "search_snippet"]
+    Snippet,
+}
+
+/// A search result, the entity with its rank and snippet.
+#[derive(Debug, Serialize)]
+pub struct SearchHit<E> {
+    #[serde(flatten)]
+    pub item: E,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+/// The tsquery of the search terms.
+fn tsquery(terms: &str) -> SimpleExpr {
+    Func::cust(Alias::new("websearch_to_tsquery"))
+        .arg(Expr::val(SEARCH_CONFIG).cast_as(Alias::new("regconfig")))
+        .arg(terms)
+        .into()
+}
+
+/// The rows matching the search terms.
+pub(in crate::model) fn matches_cond(terms: &str) -> SimpleExpr {
+    Expr::col(SearchIden::Tsv).binary(PgBinOper::Matches, tsquery(terms))
+}
+
+pub(in crate::model) fn rank_expr(terms: &str) -> SimpleExpr {
+    Func::cust(Alias::new("ts_rank"))
+        .arg(Expr::col(SearchIden::Tsv))
+        .arg(tsquery(terms))
+        .into()
+}
+
+/// The snippet of the text columns (concatenated) with the matches between
+/// the markers (see `snippet_html`).
+pub(in crate::model) fn snippet_expr(text_columns: &[&str], terms: &str) -> SimpleExpr {
+    let text = text_columns
+        .iter()
+        .fold(Func::cust(Alias::new("concat_ws")).arg(" "), |text, col| {
+            text.arg(Expr::col(Alias::new(*col)))
+        });
+
+    Func::cust(Alias::new("ts_headline"))
+        .arg(Expr::val(SEARCH_CONFIG).cast_as(Alias::new("regconfig")))
+        .arg(text)
+        .arg(tsquery(terms))
+        .arg(snippet_options())
+        .into()
+}
+
+/// The `snippet_expr` value HTML escaped (it is the user text), with the matches
+/// between `<b>` and `</b>`.
+pub(in crate::model) fn snippet_html(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            MATCH_START => html.push_str("<b>"),
+            MATCH_STOP => html.push_str("</b>"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+// region:    --- Search Filter
+
+/// The `search` filter, the full-text search terms (e.g., `{"search": "some terms"}`),
+/// matched against the entity `search_tsv`.
+///
+/// Note: As the modql `OpValValue`, the terms as `Eq`.
+#[derive(Debug)]
+pub struct OpValsSearch(pub Vec<OpValValue>);
+
+impl<'de> Deserialize<'de> for OpValsSearch {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let terms = String::deserialize(deserializer)?;
+
+        Ok(OpValsSearch(vec![OpValValue::Eq(Value::String(terms))]))
+    }
+}
+
+/// The `search` filter condition (the rows matching the terms).
+pub(in crate::model) fn search_to_sea_condition(
+    _col: &ColumnRef,
+    op_val: OpValValue,
+) -> modql::filter::SeaResult<ConditionExpression> {
+    let cond = match op_val {
+        OpValValue::Eq(Value::String(terms)) => Condition::all().add(matches_cond(&terms)),
+        // Note: Not produced by the `OpValsSearch` deserializer.
+        _ => Condition::any(),
+    };
+
+    Ok(cond.into())
+}
+
+// endregion: --- Search Filter
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_snippet_html_escaped() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_snippet =
+            format!("<script>alert('x')</script> & {MATCH_START}zebrafish{MATCH_STOP} \"ok\"");
+
+        // -- Exec
+        let html = snippet_html(&fx_snippet);
+
+        // -- Check
+        assert_eq!(
+            html,
+            "&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; <b>zebrafish</b> &quot;ok&quot;"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -1,15 +1,33 @@
+use crate::config::core_config;
 use crate::ctx::Ctx;
-use crate::model::base;
-use crate::model::ModelManager;
-use crate::model::Result;
-use modql::field::Fields;
+use crate::model::base::{self, DbBmc};
+use crate::model::label::{label_ids_to_sea_condition, LabelBmc, OpValsLabelIds};
+use crate::model::recurrence::{Recurrence, StoredRecurrence};
+use crate::model::search::{search_to_sea_condition, OpValsSearch};
+use crate::model::user::UserBmc;
+use crate::model::{Aggregate, Aggregation, GroupBy, ListPage, ModelManager, SearchHit};
+use crate::model::{Error, Result};
+use lib_utils::time::today_utc;
+use modql::field::{Field, FieldOptions, Fields, HasFields};
 use modql::filter::FilterNodes;
 use modql::filter::ListOptions;
 use modql::filter::OpValsBool;
 use modql::filter::OpValsInt64;
 use modql::filter::OpValsString;
+use modql::SIden;
+use sea_query::{ColumnRef, DynIden, IntoIden};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, PgConnection};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use time::{Date, OffsetDateTime};
+
+/// Rows per transaction of the `purge_deleted_before` batches.
+const PURGE_BATCH_SIZE: u64 = 500;
+
+/// The transaction advisory lock of the `set_parent` changes, so that two concurrent
+/// changes cannot make a cycle (without locking the whole task table).
+const PARENT_LOCK_KEY: i64 = 0x7461_736b_5f70; // "task_p"
 
 // region:         — Task Types
 
@@ -19,29 +37,374 @@ pub struct Task {
     pub id: i64,
 
     pub title: String,
+    pub description: Option<String>,
+    #[serde(with = "iso_date::option")]
+    pub due_date: Option<Date>,
+    #[field(cast_as = "task_priority")]
+    pub priority: TaskPriority,
+    #[field(cast_as = "task_status")]
+    pub status: TaskStatus,
+    /// Derived from the status (`status = done`).
     pub done: bool,
+    pub assignee_id: Option<i64>,
+    /// The creator (`None` when created with the root ctx).
+    pub owner_id: Option<i64>,
+    pub project: Option<String>,
+    /// The parent task, when a subtask (see `TaskBmc::set_parent`).
+    pub parent_id: Option<i64>,
+    /// When completed, the next occurrence is created (see `recurrence`).
+    #[sqlx(try_from = "StoredRecurrence")]
+    pub recurrence: Option<Recurrence>,
+
+    /// Incremented on each update (see `TaskBmc::update_with_version`).
+    pub version: i64,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub ctime: OffsetDateTime,
+
+    /// Set when soft deleted (see `TaskBmc::list_deleted`).
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub deleted_at: Option<OffsetDateTime>,
 }
 
 //  Sent to the model layer for creating a new task
-#[derive(Fields, Deserialize)]
+#[derive(Fields, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TaskForCreate {
     pub title: String,
+    pub description: Option<String>,
+    #[serde(default, with = "iso_date::option")]
+    pub due_date: Option<Date>,
+    #[field(cast_as = "task_priority")]
+    pub priority: Option<TaskPriority>,
+    #[field(cast_as = "task_status")]
+    pub status: Option<TaskStatus>,
+    pub assignee_id: Option<i64>,
+    /// Set by `TaskBmc::create` (the ctx user).
+    #[serde(skip)]
+    pub owner_id: Option<i64>,
+    pub project: Option<String>,
+    pub parent_id: Option<i64>,
+    pub recurrence: Option<Recurrence>,
 }
 
 // Sent to the model layer for updating a task
-#[derive(Fields, Default, Deserialize)]
+// (the parent is changed with `TaskBmc::set_parent`, which checks the cycles,
+//  and the recurrence with `TaskBmc::set_recurrence`)
+// The nullable columns are `Some(None)` (json `null`) to clear them,
+// and `None` (absent) to leave them unchanged.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TaskForUpdate {
     pub title: Option<String>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "iso_date_double_option")]
+    pub due_date: Option<Option<Date>>,
+    pub priority: Option<TaskPriority>,
+    pub status: Option<TaskStatus>,
+    /// Legacy (before `status`), `true` as the `done` status, `false` as `todo`
+    /// (ignored when `status` is set, not a column).
     pub done: Option<bool>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub assignee_id: Option<Option<i64>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub project: Option<Option<String>>,
+}
+
+impl TaskForUpdate {
+    /// With the legacy `done` as the `status` (when no `status`).
+    fn with_done_as_status(mut self) -> Self {
+        if let (None, Some(done)) = (self.status, self.done.take()) {
+            self.status = Some(if done {
+                TaskStatus::Done
+            } else {
+                TaskStatus::Todo
+            });
+        }
+        self
+    }
+}
+
+/// Note: Not derived (`Fields`), as the derive cannot bind the `Option<Option<T>>`
+///       of the nullable columns (`Some(None)` as `NULL`).
+impl HasFields for TaskForUpdate {
+    fn not_none_fields(self) -> Fields {
+        let TaskForUpdate {
+            title,
+            description,
+            due_date,
+            priority,
+            status,
+            done: _,
+            assignee_id,
+            project,
+        } = self;
+
+        let mut ff: Vec<Field> = Vec::new();
+        if let Some(title) = title {
+            ff.push(Field::new(SIden("title"), title.into()));
+        }
+        if let Some(description) = description {
+            ff.push(Field::new(SIden("description"), description.into()));
+        }
+        if let Some(due_date) = due_date {
+            ff.push(Field::new(SIden("due_date"), due_date.into()));
+        }
+        if let Some(priority) = priority {
+            ff.push(Field::new_with_options(
+                SIden("priority"),
+                priority.into(),
+                cast_as("task_priority"),
+            ));
+        }
+        if let Some(status) = status {
+            ff.push(Field::new_with_options(
+                SIden("status"),
+                status.into(),
+                cast_as("task_status"),
+            ));
+        }
+        if let Some(assignee_id) = assignee_id {
+            ff.push(Field::new(SIden("assignee_id"), assignee_id.into()));
+        }
+        if let Some(project) = project {
+            ff.push(Field::new(SIden("project"), project.into()));
+        }
+
+        Fields::new(ff)
+    }
+
+    /// Note: The unchanged (`None`) nullable columns are set to `NULL`.
+    fn all_fields(self) -> Fields {
+        let ff = vec![
+            Field::new(SIden("title"), self.title.into()),
+            Field::new(SIden("description"), self.description.flatten().into()),
+            Field::new(SIden("due_date"), self.due_date.flatten().into()),
+            Field::new_with_options(
+                SIden("priority"),
+                self.priority.into(),
+                cast_as("task_priority"),
+            ),
+            Field::new_with_options(SIden("status"), self.status.into(), cast_as("task_status")),
+            Field::new(SIden("assignee_id"), self.assignee_id.flatten().into()),
+            Field::new(SIden("project"), self.project.flatten().into()),
+        ];
+
+        Fields::new(ff)
+    }
+
+    fn field_names() -> &'static [&'static str] {
+        &[
+            "title",
+            "description",
+            "due_date",
+            "priority",
+            "status",
+            "assignee_id",
+            "project",
+        ]
+    }
+
+    fn field_idens() -> Vec<DynIden> {
+        Self::field_names()
+            .iter()
+            .map(|name| SIden(name).into_iden())
+            .collect()
+    }
+
+    fn field_column_refs() -> Vec<ColumnRef> {
+        Self::field_names()
+            .iter()
+            .map(|name| ColumnRef::Column(SIden(name).into_iden()))
+            .collect()
+    }
+
+    fn field_column_refs_with_rel(rel: impl IntoIden) -> Vec<ColumnRef> {
+        let rel = rel.into_iden();
+        Self::field_names()
+            .iter()
+            .map(|name| ColumnRef::TableColumn(rel.clone(), SIden(name).into_iden()))
+            .collect()
+    }
 }
 
+fn cast_as(type_name: &str) -> FieldOptions {
+    FieldOptions {
+        cast_as: Some(type_name.to_string()),
+    }
+}
+
+/// Dates as `YYYY-MM-DD` (e.g., `"due_date": {"$lt": "2026-01-31"}`), and
+/// the enums by their names (e.g., `"status": {"$in": ["todo", "blocked"]}`).
 #[derive(FilterNodes, Deserialize, Default, Debug)]
 pub struct TaskFilter {
     id: Option<OpValsInt64>,
 
     title: Option<OpValsString>,
+    description: Option<OpValsString>,
+    #[modql(cast_as = "date")]
+    due_date: Option<OpValsString>,
+    #[modql(cast_as = "task_priority")]
+    priority: Option<OpValsString>,
+    #[modql(cast_as = "task_status")]
+    status: Option<OpValsString>,
     done: Option<OpValsBool>,
+    assignee_id: Option<OpValsInt64>,
+    owner_id: Option<OpValsInt64>,
+    project: Option<OpValsString>,
+    /// e.g., `{"$null": true}` for the top tasks.
+    parent_id: Option<OpValsInt64>,
+    recurrence: Option<OpValsString>,
+    /// RFC 3339 (e.g., `"ctime": {"$gte": "2026-01-01T00:00:00Z"}`).
+    #[modql(cast_as = "timestamptz")]
+    ctime: Option<OpValsString>,
+    /// `{"$hasAny": [..]}` or `{"$hasAll": [..]}` (see `label::OpValsLabelIds`).
+    #[modql(to_sea_condition_fn = "label_ids_to_sea_condition")]
+    label_ids: Option<OpValsLabelIds>,
+    /// Full-text search terms of the title and description (see `search::OpValsSearch`).
+    #[modql(to_sea_condition_fn = "search_to_sea_condition")]
+    search: Option<OpValsSearch>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "task_status", rename_all = "snake_case")]
+pub enum TaskStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Blocked,
+    Done,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskStatus::Todo => "todo",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Blocked => "blocked",
+            TaskStatus::Done => "done",
+        }
+    }
+}
+
+impl From<TaskStatus> for sea_query::Value {
+    fn from(status: TaskStatus) -> Self {
+        status.as_str().into()
+    }
+}
+
+impl sea_query::Nullable for TaskStatus {
+    fn null() -> sea_query::Value {
+        sea_query::Value::String(None)
+    }
+}
+
+/// Sorted by urgency (as the Postgres enum), e.g., `"order_bys": "!priority"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "task_priority", rename_all = "snake_case")]
+pub enum TaskPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
+impl TaskPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskPriority::Low => "low",
+            TaskPriority::Medium => "medium",
+            TaskPriority::High => "high",
+            TaskPriority::Urgent => "urgent",
+        }
+    }
+}
+
+impl From<TaskPriority> for sea_query::Value {
+    fn from(priority: TaskPriority) -> Self {
+        priority.as_str().into()
+    }
+}
+
+impl sea_query::Nullable for TaskPriority {
+    fn null() -> sea_query::Value {
+        sea_query::Value::String(None)
+    }
+}
+
+/// A task of a tree (subtasks or dependencies), with its depth from the root task.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TaskNode {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub task: Task,
+    pub depth: i32,
+}
+
+#[derive(Fields)]
+struct TaskParentForUpdate {
+    parent_id: Option<i64>,
+}
+
+#[derive(Fields)]
+struct TaskRecurrenceForUpdate {
+    recurrence: Option<Recurrence>,
+}
+
+/// What completing (`status: done`) a task with open (not done) subtasks does,
+/// configured with `SERVICE_TASK_OPEN_CHILDREN_ON_DONE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenChildrenPolicy {
+    /// Fails with `Error::TaskHasOpenChildren` (default).
+    Fail,
+    /// Also completes the open subtasks (recursively).
+    Cascade,
+}
+
+impl FromStr for OpenChildrenPolicy {
+    type Err = String;
+
+    fn from_str(policy: &str) -> core::result::Result<Self, Self::Err> {
+        match policy {
+            "fail" => Ok(OpenChildrenPolicy::Fail),
+            "cascade" => Ok(OpenChildrenPolicy::Cascade),
+            other => Err(format!("open children policy '{other}' not supported")),
+        }
+    }
+}
+
+impl OpenChildrenPolicy {
+    /// The configured policy (`SERVICE_TASK_OPEN_CHILDREN_ON_DONE`, `fail` by default),
+    /// parsed once.
+    pub fn from_config() -> Self {
+        static INSTANCE: OnceLock<OpenChildrenPolicy> = OnceLock::new();
+
+        *INSTANCE.get_or_init(|| {
+            core_config()
+                .TASK_OPEN_CHILDREN_ON_DONE
+                .parse()
+                .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING CONF - Cause: {ex}"))
+        })
+    }
 }
+
+// The `YYYY-MM-DD` date serde (`iso_date::option` for the `Option<Date>`).
+time::serde::format_description!(pub iso_date, Date, "[year]-[month]-[day]");
+
+/// The `iso_date` of a nullable update field (with `#[serde(default)]` for the absent field).
+fn iso_date_double_option<'de, D>(
+    deserializer: D,
+) -> core::result::Result<Option<Option<Date>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    iso_date::option::deserialize(deserializer).map(Some)
+}
+
 // endregion:      — Task Types
 
 // region:         — TaskBmc
@@ -49,12 +412,27 @@ pub struct TaskBmc;
 
 impl base::DbBmc for TaskBmc {
     const TABLE: &'static str = "task";
+    const AUDITED: bool = true;
+    const SOFT_DELETE: bool = true;
+    const VERSIONED: bool = true;
+    const SEARCH_COLUMNS: &'static [&'static str] = &["title", "description"];
 }
 
 // Functions for Business Model Component
 // (NOTE) listed in  CRUD order
 impl TaskBmc {
-    pub async fn create(ctx: &Ctx, mm: &ModelManager, task_c: TaskForCreate) -> Result<i64> {
+    /// Create a task owned by the ctx user (no owner for the root ctx).
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, mut task_c: TaskForCreate) -> Result<i64> {
+        // Note: A new task cannot be in a cycle, only check that the parent exists.
+        if let Some(parent_id) = task_c.parent_id {
+            Self::get(ctx, mm, parent_id).await?;
+        }
+
+        // Note: The root ctx user (0) is not a user row.
+        task_c.owner_id = Some(ctx.user_id()).filter(|user_id| *user_id != 0);
+        let start = task_c.due_date.unwrap_or_else(today_utc);
+        task_c.recurrence = task_c.recurrence.map(|rule| rule.anchored(start));
+
         base::create::<Self, _>(ctx, mm, task_c).await
     }
 
@@ -71,30 +449,338 @@ impl TaskBmc {
         base::list::<Self, _, _>(ctx, mm, filters, list_options).await
     }
 
+    pub async fn count(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskFilter>>,
+    ) -> Result<i64> {
+        base::count::<Self, _>(ctx, mm, filters).await
+    }
+
+    /// Full-text search of the tasks title and description (ranked, with snippets),
+    /// within the filtered tasks.
+    pub async fn search(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        terms: &str,
+        filters: Option<Vec<TaskFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<SearchHit<Task>>> {
+        base::search::<Self, _, _>(ctx, mm, terms, filters, list_options).await
+    }
+
+    /// Aggregate the filtered tasks by the `group_bys` (columns of `Task`).
+    pub async fn aggregate(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskFilter>>,
+        group_bys: &[GroupBy],
+        aggregates: &[Aggregate],
+    ) -> Result<Aggregation> {
+        base::aggregate::<Self, Task, _>(ctx, mm, filters, group_bys, aggregates).await
+    }
+
+    /// List a page of tasks, after the `cursor` (from the previous page) when `Some`,
+    /// with the total count of the filtered tasks when `include_total`.
+    pub async fn list_page(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskFilter>>,
+        list_options: Option<ListOptions>,
+        cursor: Option<&str>,
+        include_total: bool,
+    ) -> Result<ListPage<Task>> {
+        base::list_page::<Self, _, _>(ctx, mm, filters, list_options, cursor, include_total).await
+    }
+
     pub async fn update(
         ctx: &Ctx,
         mm: &ModelManager,
         id: i64,
         task_u: TaskForUpdate,
     ) -> Result<()> {
-        base::update::<Self, _>(ctx, mm, id, task_u).await
+        Self::update_with_version(ctx, mm, id, task_u, None).await
+    }
+
+    /// Update only if the task is at the `expected_version` (when `Some`),
+    /// otherwise `Error::VersionConflict`.
+    /// Completing a task with open subtasks follows the configured `OpenChildrenPolicy`.
+    pub async fn update_with_version(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        task_u: TaskForUpdate,
+        expected_version: Option<i64>,
+    ) -> Result<()> {
+        let policy = OpenChildrenPolicy::from_config();
+        Self::update_with_policy(ctx, mm, id, task_u, expected_version, policy).await
+    }
+
+    /// `update_with_version` with the given `OpenChildrenPolicy`, in one transaction
+    /// (the check of the open subtasks, the update, the cascade and the next occurrences),
+    /// with the task locked, so that two concurrent completions create one next occurrence.
+    pub async fn update_with_policy(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        task_u: TaskForUpdate,
+        expected_version: Option<i64>,
+        policy: OpenChildrenPolicy,
+    ) -> Result<()> {
+        let task_u = task_u.with_done_as_status();
+        let mut tx = mm.db().begin().await?;
+        let task = base::get_for_update_in::<Self, Task>(ctx, &mut tx, id).await?;
+
+        let open_ids = match task_u.status {
+            Some(TaskStatus::Done) => Self::lock_open_descendant_ids(&mut tx, id).await?,
+            _ => Vec::new(),
+        };
+        if !open_ids.is_empty() && policy == OpenChildrenPolicy::Fail {
+            return Err(Error::TaskHasOpenChildren { id, open_ids });
+        }
+        // Note: Only completing a not done task creates its next occurrence.
+        let completed = match task_u.status {
+            Some(TaskStatus::Done) => Some(task).filter(|t| !t.done),
+            _ => None,
+        };
+
+        base::update_with_version_in::<Self, _>(ctx, &mut tx, id, task_u, expected_version).await?;
+        if let Some(task) = completed {
+            Self::create_next_occurrence_in(ctx, &mut tx, task).await?;
+        }
+
+        // -- Cascade (each subtask update is audited and versioned)
+        for open_id in open_ids {
+            let task = base::get_in::<Self, Task>(ctx, &mut tx, open_id).await?;
+            let task_u = TaskForUpdate {
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            };
+            base::update_with_version_in::<Self, _>(ctx, &mut tx, open_id, task_u, None).await?;
+            Self::create_next_occurrence_in(ctx, &mut tx, task).await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Set (or unset, when `None`) the recurrence of a task, anchored on its due date
+    /// (or today, see `Recurrence::anchored`).
+    pub async fn set_recurrence(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        recurrence: Option<Recurrence>,
+    ) -> Result<()> {
+        let mut tx = mm.db().begin().await?;
+        let task = base::get_for_update_in::<Self, Task>(ctx, &mut tx, id).await?;
+
+        let start = task.due_date.unwrap_or_else(today_utc);
+        let recurrence = recurrence.map(|rule| rule.anchored(start));
+        let task_u = TaskRecurrenceForUpdate { recurrence };
+        base::update_all_fields_in::<Self, _>(ctx, &mut tx, id, task_u).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Create the next occurrence of a (just completed) recurring task, with its labels,
+    /// and move the recurrence to it (so that the completed task does not recur twice,
+    /// e.g., when reopened and completed again), on the `conn` connection (in the
+    /// transaction completing the task). No-op when not recurring.
+    async fn create_next_occurrence_in(
+        ctx: &Ctx,
+        conn: &mut PgConnection,
+        task: Task,
+    ) -> Result<()> {
+        let Some(recurrence) = task.recurrence else {
+            return Ok(());
+        };
+        let today = today_utc();
+        let start = task.due_date.unwrap_or(today);
+        // Note: Anchors the rules stored before `Recurrence::anchored`.
+        let recurrence = recurrence.anchored(start);
+        let Some(due_date) = recurrence.next_due(start, today) else {
+            return Ok(());
+        };
+
+        let task_c = TaskForCreate {
+            title: task.title,
+            description: task.description,
+            due_date: Some(due_date),
+            priority: Some(task.priority),
+            status: None,
+            assignee_id: task.assignee_id,
+            owner_id: task.owner_id,
+            project: task.project,
+            parent_id: task.parent_id,
+            recurrence: Some(recurrence),
+        };
+        let next_id = base::create_in::<Self, _>(ctx, conn, task_c).await?;
+        LabelBmc::copy_to_task_in(conn, task.id, next_id).await?;
+
+        let task_u = TaskRecurrenceForUpdate { recurrence: None };
+        base::update_all_fields_in::<Self, _>(ctx, conn, task.id, task_u).await
+    }
+
+    /// Set (or unset, when `None`) the parent of a task, `Error::TaskCycle` when
+    /// the parent is the task or one of its subtasks.
+    pub async fn set_parent(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        parent_id: Option<i64>,
+    ) -> Result<()> {
+        let mut tx = mm.db().begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(PARENT_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+
+        base::get_in::<Self, Task>(ctx, &mut tx, id).await?;
+        if let Some(parent_id) = parent_id {
+            base::get_in::<Self, Task>(ctx, &mut tx, parent_id).await?;
+
+            // -- Check the cycle (the parent in the subtree of the task)
+            let sql = format!(
+                "{} SELECT EXISTS (SELECT 1 FROM subtree WHERE id = $2)",
+                subtree_cte(false)
+            );
+            let (is_cycle,) = sqlx::query_as::<_, (bool,)>(&sql)
+                .bind(id)
+                .bind(parent_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            if is_cycle {
+                return Err(Error::TaskCycle {
+                    relation: "parent",
+                    task_id: id,
+                    linked_id: parent_id,
+                });
+            }
+        }
+
+        let task_u = TaskParentForUpdate { parent_id };
+        base::update_all_fields_in::<Self, _>(ctx, &mut tx, id, task_u).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// The task and all of its subtasks (recursively, without the deleted ones and
+    /// their subtasks), by depth (0 for the task).
+    pub async fn list_subtree(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<Vec<TaskNode>> {
+        Self::get(ctx, mm, id).await?;
+
+        let sql = format!(
+            "{} SELECT {}, subtree.depth FROM subtree JOIN task t ON t.id = subtree.id \
+             WHERE NOT subtree.is_cycle \
+             ORDER BY subtree.depth, t.id",
+            subtree_cte(true),
+            task_columns_sql("t")
+        );
+        let nodes = sqlx::query_as::<_, TaskNode>(&sql)
+            .bind(id)
+            .fetch_all(mm.db())
+            .await?;
+
+        Ok(nodes)
+    }
+
+    /// The ids of the open (not done, nor deleted) subtasks of a task (recursively),
+    /// locked (`FOR UPDATE`) until the end of the `conn` transaction.
+    async fn lock_open_descendant_ids(conn: &mut PgConnection, id: i64) -> Result<Vec<i64>> {
+        let sql = format!(
+            "{} SELECT t.id FROM subtree JOIN task t ON t.id = subtree.id \
+             WHERE subtree.depth > 0 AND NOT subtree.is_cycle AND NOT t.done \
+             ORDER BY subtree.depth, t.id \
+             FOR UPDATE OF t",
+            subtree_cte(true)
+        );
+        let ids = sqlx::query_as::<_, (i64,)>(&sql)
+            .bind(id)
+            .fetch_all(conn)
+            .await?;
+
+        Ok(ids.into_iter().map(|(id,)| id).collect())
     }
 
+    /// Soft delete (see `restore` and `purge`).
     pub async fn delete(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
         base::delete::<Self>(ctx, mm, id).await
     }
+
+    pub async fn list_deleted(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        filters: Option<Vec<TaskFilter>>,
+        list_options: Option<ListOptions>,
+    ) -> Result<Vec<Task>> {
+        base::list_deleted::<Self, _, _>(ctx, mm, filters, list_options).await
+    }
+
+    pub async fn restore(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        base::restore::<Self>(ctx, mm, id).await
+    }
+
+    /// Permanently delete a (soft) deleted task (admin users only).
+    pub async fn purge(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<()> {
+        UserBmc::check_admin(ctx, mm, Self::TABLE).await?;
+
+        base::purge::<Self>(ctx, mm, id).await
+    }
+
+    pub async fn purge_deleted_before(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        deleted_before: OffsetDateTime,
+    ) -> Result<u64> {
+        base::purge_deleted_before::<Self>(ctx, mm, deleted_before, PURGE_BATCH_SIZE).await
+    }
 }
 // end region:      — TaskBmc
 
+/// The `subtree (id, depth)` recursive CTE, of the task `$1` (depth 0) and its subtasks,
+/// without the deleted subtasks (and their subtasks) when `skip_deleted`.
+/// Note: The `CYCLE` clause stops at a task already in the path (a row with `is_cycle`),
+///       should a cycle get in (see `set_parent`).
+fn subtree_cte(skip_deleted: bool) -> String {
+    let deleted_cond = if skip_deleted {
+        "AND t.deleted_at IS NULL"
+    } else {
+        ""
+    };
+    format!(
+        "WITH RECURSIVE subtree (id, depth) AS ( \
+           SELECT id, 0 FROM task WHERE id = $1 \
+           UNION ALL \
+           SELECT t.id, subtree.depth + 1 FROM task t \
+           JOIN subtree ON t.parent_id = subtree.id {deleted_cond} \
+         ) CYCLE id SET is_cycle USING path"
+    )
+}
+
+/// The `Task` columns of the `table_alias` (for the hand written queries).
+pub(in crate::model) fn task_columns_sql(table_alias: &str) -> String {
+    Task::field_names()
+        .iter()
+        .map(|name| format!("{table_alias}.{name}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 // region:    --- Tests
 
 #[cfg(test)]
 mod tests {
     // #![allow(unused)]
     use crate::_dev_utils;
+    use crate::model::user::UserForCreate;
 
     use super::*;
     use anyhow::Result;
+    use lib_utils::time::now_utc;
     use serde_json::json;
     use serial_test::serial;
 
@@ -108,6 +794,7 @@ mod tests {
         // -- Exec
         let task_c = TaskForCreate {
             title: fx_title.to_string(),
+            ..Default::default()
         };
 
         let id = TaskBmc::create(&ctx, &mm, task_c).await?;
@@ -233,29 +920,892 @@ mod tests {
 
     #[serial]
     #[tokio::test]
-    async fn test_update_ok() -> Result<()> {
-        // Setup & Fixtures
+    async fn test_list_page_ok() -> Result<()> {
+        // -- Setup & Fixtures
         let mm = _dev_utils::init_test().await;
         let ctx = Ctx::root_ctx();
-        let fx_title = "test_update_ok - task 01";
-        let fx_title_updated = "test_update_ok - task 01 - updated";
-        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
-            .await?
-            .remove(0); // take it out
+        let fx_titles = &[
+            "test_list_page_ok-task 01",
+            "test_list_page_ok-task 02",
+            "test_list_page_ok-task 02",
+            "test_list_page_ok-task 03",
+            "test_list_page_ok-task 04",
+        ];
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, fx_titles).await?;
+        let filters = || -> Result<Vec<TaskFilter>> {
+            Ok(serde_json::from_value(json!([{
+                "title": {"$startsWith": "test_list_page_ok"}
+            }]))?)
+        };
+        let list_options = || -> Result<ListOptions> {
+            Ok(serde_json::from_value(json!({
+                "limit": 2,
+                "order_bys": ["!title"]
+            }))?)
+        };
 
         // -- Exec
-        let task_u = TaskForUpdate {
-            title: Some(fx_title_updated.to_string()),
-            ..Default::default()
+        let mut pages = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = TaskBmc::list_page(
+                &ctx,
+                &mm,
+                Some(filters()?),
+                Some(list_options()?),
+                cursor.as_deref(),
+                pages.is_empty(),
+            )
+            .await?;
+            if pages.is_empty() {
+                assert_eq!(page.total, Some(5), "total on the first page only");
+            }
+            cursor = page.next_cursor;
+            pages.push(page.items);
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        // -- Check
+        let page_lens: Vec<usize> = pages.iter().map(|p| p.len()).collect();
+        assert_eq!(page_lens, [2, 2, 1]);
+        let ids: Vec<i64> = pages.iter().flatten().map(|t| t.id).collect();
+        let fx_ids: Vec<i64> = [4, 3, 1, 2, 0].iter().map(|&i| fx_tasks[i].id).collect();
+        assert_eq!(ids, fx_ids, "title desc, then id asc");
+        let total = TaskBmc::count(&ctx, &mm, Some(filters()?)).await?;
+        assert_eq!(total, 5);
+
+        // -- Cleanup
+        for task in fx_tasks.iter() {
+            TaskBmc::delete(&ctx, &mm, task.id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_page_err_cursor_type_forged() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let list_options: ListOptions = serde_json::from_value(json!({"order_bys": ["!title"]}))?;
+        // Note: `title` is a varchar (and `id` an int8).
+        let fx_cursors = [
+            json!([["text", "x"], ["int8", "1"]]),
+            json!([["varchar) OR (1=1", "x"], ["int8", "1"]]),
+        ];
+
+        for fx_cursor in fx_cursors {
+            let fx_cursor = lib_utils::b64::b64u_encode(fx_cursor.to_string());
+
+            // -- Exec
+            let res = TaskBmc::list_page(
+                &ctx,
+                &mm,
+                None,
+                Some(list_options.clone()),
+                Some(&fx_cursor),
+                false,
+            )
+            .await;
+
+            // -- Check
+            assert!(
+                matches!(res, Err(Error::ListCursorInvalid)),
+                "Should be ListCursorInvalid, but was {res:?}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_page_by_status_priority_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_tasks = [
+            (
+                "test_list_page_by_status_priority_ok-task 01",
+                TaskPriority::Low,
+                TaskStatus::Todo,
+            ),
+            (
+                "test_list_page_by_status_priority_ok-task 02",
+                TaskPriority::Urgent,
+                TaskStatus::Blocked,
+            ),
+            (
+                "test_list_page_by_status_priority_ok-task 03",
+                TaskPriority::High,
+                TaskStatus::Todo,
+            ),
+            (
+                "test_list_page_by_status_priority_ok-task 04",
+                TaskPriority::Urgent,
+                TaskStatus::Done,
+            ),
+        ];
+        let mut fx_ids = Vec::new();
+        for (title, priority, status) in fx_tasks {
+            let task_c = TaskForCreate {
+                title: title.to_string(),
+                due_date: Some(time::macros::date!(2030 - 01 - 15)),
+                priority: Some(priority),
+                status: Some(status),
+                assignee_id: Some(1000),
+                ..Default::default()
+            };
+            fx_ids.push(TaskBmc::create(&ctx, &mm, task_c).await?);
+        }
+        let filters = || -> Result<Vec<TaskFilter>> {
+            Ok(serde_json::from_value(json!([{
+                "title": {"$startsWith": "test_list_page_by_status_priority_ok"},
+                "status": {"$in": ["todo", "blocked"]},
+                "due_date": {"$lt": "2030-02-01"},
+                "assignee_id": 1000
+            }]))?)
         };
-        TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+        let list_options = || -> Result<ListOptions> {
+            Ok(serde_json::from_value(json!({
+                "limit": 1,
+                "order_bys": "!priority"
+            }))?)
+        };
+
+        // -- Exec
+        let mut ids = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = TaskBmc::list_page(
+                &ctx,
+                &mm,
+                Some(filters()?),
+                Some(list_options()?),
+                cursor.as_deref(),
+                false,
+            )
+            .await?;
+            ids.extend(page.items.iter().map(|t| t.id));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
 
         // -- Check
-        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        assert_eq!(ids, [fx_ids[1], fx_ids[2], fx_ids[0]], "urgent, high, low");
+        let task = TaskBmc::get(&ctx, &mm, fx_ids[3]).await?;
+        assert!(task.done, "done derived from the status");
+        assert_eq!(task.due_date, Some(time::macros::date!(2030 - 01 - 15)));
+
+        // -- Cleanup
+        for id in fx_ids {
+            TaskBmc::delete(&ctx, &mm, id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_search_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_tasks = [
+            (
+                "test_search_ok-task 01 deploy",
+                Some("Rotate the zebrafish certificates"),
+            ),
+            ("test_search_ok-task 02 zebrafish", None),
+            ("test_search_ok-task 03", Some("Nothing to see")),
+        ];
+        let mut fx_ids = Vec::new();
+        for (title, description) in fx_tasks {
+            let task_c = TaskForCreate {
+                title: title.to_string(),
+                description: description.map(|d| d.to_string()),
+                ..Default::default()
+            };
+            fx_ids.push(TaskBmc::create(&ctx, &mm, task_c).await?);
+        }
+        let filters: Vec<TaskFilter> = serde_json::from_value(json!([{
+            "title": {"$startsWith": "test_search_ok"}
+        }]))?;
+
+        // -- Exec
+        let hits = TaskBmc::search(&ctx, &mm, "zebrafish", Some(filters), None).await?;
+
+        // -- Check
+        let ids: Vec<i64> = hits.iter().map(|hit| hit.item.id).collect();
+        assert_eq!(ids, [fx_ids[1], fx_ids[0]], "title match ranked first");
+        assert!(hits[0].rank > hits[1].rank);
+        assert!(
+            hits[1].snippet.contains("<b>zebrafish</b>"),
+            "snippet: {}",
+            hits[1].snippet
+        );
+
+        // -- Exec & Check - as a list filter (with the other filters)
+        let filters: Vec<TaskFilter> = serde_json::from_value(json!([{
+            "title": {"$startsWith": "test_search_ok"},
+            "search": "zebrafish"
+        }]))?;
+        let tasks = TaskBmc::list(&ctx, &mm, Some(filters), None).await?;
+        let mut ids: Vec<i64> = tasks.iter().map(|task| task.id).collect();
+        ids.sort();
+        assert_eq!(ids, [fx_ids[0], fx_ids[1]]);
+
+        // -- Cleanup
+        for id in fx_ids {
+            TaskBmc::delete(&ctx, &mm, id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_aggregate_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_titles = &[
+            "test_aggregate_ok-task 01",
+            "test_aggregate_ok-task 02",
+            "test_aggregate_ok-task 03",
+        ];
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, fx_titles).await?;
+        let task_u = TaskForUpdate {
+            status: Some(TaskStatus::Done),
+            ..Default::default()
+        };
+        TaskBmc::update(&ctx, &mm, fx_tasks[2].id, task_u).await?;
+        let filters: Vec<TaskFilter> = serde_json::from_value(json!([{
+            "title": {"$startsWith": "test_aggregate_ok"}
+        }]))?;
+        let group_bys: Vec<GroupBy> = serde_json::from_value(json!(["done"]))?;
+        let aggregates: Vec<Aggregate> =
+            serde_json::from_value(json!(["count", "max:title", "min:version"]))?;
+
+        // -- Exec
+        let aggregation =
+            TaskBmc::aggregate(&ctx, &mm, Some(filters), &group_bys, &aggregates).await?;
+
+        // -- Check
+        assert!(!aggregation.truncated);
+        assert_eq!(
+            aggregation.groups,
+            [
+                json!({"done": false, "count": 2, "max_title": fx_titles[1], "min_version": 1}),
+                json!({"done": true, "count": 1, "max_title": fx_titles[2], "min_version": 2}),
+            ]
+        );
+
+        // -- Cleanup
+        for task in fx_tasks.iter() {
+            TaskBmc::delete(&ctx, &mm, task.id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_aggregate_by_owner_project_day_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        // demo1 (see the dev seed).
+        let ctx = Ctx::new(1000)?;
+        let fx_title = "test_aggregate_by_owner_project_day_ok";
+        let mut fx_ids = Vec::new();
+        for project in ["proj-a", "proj-a", "proj-b"] {
+            let task_c = TaskForCreate {
+                title: fx_title.to_string(),
+                project: Some(project.to_string()),
+                ..Default::default()
+            };
+            fx_ids.push(TaskBmc::create(&ctx, &mm, task_c).await?);
+        }
+        let filters: Vec<TaskFilter> =
+            serde_json::from_value(json!([{"title": {"$eq": fx_title}}]))?;
+        let group_bys: Vec<GroupBy> =
+            serde_json::from_value(json!(["owner_id", "project", "ctime:day"]))?;
+        let aggregates: Vec<Aggregate> = serde_json::from_value(json!(["count"]))?;
+
+        // -- Exec
+        let aggregation =
+            TaskBmc::aggregate(&ctx, &mm, Some(filters), &group_bys, &aggregates).await?;
+
+        // -- Check
+        let groups: Vec<_> = aggregation
+            .groups
+            .iter()
+            .map(|group| (&group["owner_id"], &group["project"], &group["count"]))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (&json!(1000), &json!("proj-a"), &json!(2)),
+                (&json!(1000), &json!("proj-b"), &json!(1)),
+            ]
+        );
+        assert!(aggregation.groups[0]["ctime_day"].is_string());
+
+        // -- Cleanup
+        for id in fx_ids {
+            TaskBmc::delete(&Ctx::root_ctx(), &mm, id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_aggregate_err_column_not_allowed() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let group_bys: Vec<GroupBy> = serde_json::from_value(json!(["owner_pwd"]))?;
+
+        // -- Exec
+        let res = TaskBmc::aggregate(&ctx, &mm, None, &group_bys, &[]).await;
+
+        // -- Check
+        assert!(
+            matches!(
+                res,
+                Err(crate::model::Error::ColumnNotAllowed {
+                    entity: "task",
+                    ref column,
+                }) if column == "owner_pwd"
+            ),
+            "ColumnNotAllowed not matched"
+        );
+
+        // -- Exec & Check - truncated, but not a timestamp
+        let group_bys: Vec<GroupBy> = serde_json::from_value(json!(["title:day"]))?;
+        let res = TaskBmc::aggregate(&ctx, &mm, None, &group_bys, &[]).await;
+        assert!(
+            matches!(
+                res,
+                Err(crate::model::Error::ColumnNotAllowed {
+                    entity: "task",
+                    ref column,
+                }) if column == "title"
+            ),
+            "ColumnNotAllowed (title:day) not matched"
+        );
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_ok() -> Result<()> {
+        // Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_title = "test_update_ok - task 01";
+        let fx_title_updated = "test_update_ok - task 01 - updated";
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+            .await?
+            .remove(0); // take it out
+
+        // -- Exec
+        let task_u = TaskForUpdate {
+            title: Some(fx_title_updated.to_string()),
+            ..Default::default()
+        };
+        TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+
+        // -- Check
+        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
         assert_eq!(task.title, fx_title_updated);
         Ok(())
     }
 
+    #[serial]
+    #[tokio::test]
+    async fn test_update_clear_nullable_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_title = "test_update_clear_nullable_ok - task 01";
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+            .await?
+            .remove(0);
+        let fx_set: TaskForUpdate = serde_json::from_value(json!({
+            "description": "some description",
+            "due_date": "2030-01-15",
+            "project": "some project"
+        }))?;
+        TaskBmc::update(&ctx, &mm, fx_task.id, fx_set).await?;
+
+        // -- Exec
+        // Note: The absent `project` is left unchanged.
+        let task_u: TaskForUpdate = serde_json::from_value(json!({
+            "description": null,
+            "due_date": null
+        }))?;
+        TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+
+        // -- Check
+        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        assert_eq!(task.description, None);
+        assert_eq!(task.due_date, None);
+        assert_eq!(task.project.as_deref(), Some("some project"));
+        assert_eq!(task.title, fx_title);
+
+        // -- Cleanup
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_legacy_done_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_title = "test_update_legacy_done_ok - task 01";
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+            .await?
+            .remove(0);
+
+        // -- Exec
+        let task_u: TaskForUpdate = serde_json::from_value(json!({"done": true}))?;
+        TaskBmc::update(&ctx, &mm, fx_task.id, task_u).await?;
+
+        // -- Check
+        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        assert_eq!(task.status, TaskStatus::Done);
+        assert!(task.done);
+        let res = serde_json::from_value::<TaskForUpdate>(json!({"titel": "typo"}));
+        assert!(res.is_err(), "Unknown fields should be rejected");
+
+        // -- Cleanup
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_err_version_conflict() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_title = "test_update_err_version_conflict - task 01";
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+            .await?
+            .remove(0);
+        let fx_version = fx_task.version;
+
+        // -- Exec
+        let task_u = || TaskForUpdate {
+            status: Some(TaskStatus::Done),
+            ..Default::default()
+        };
+        TaskBmc::update_with_version(&ctx, &mm, fx_task.id, task_u(), Some(fx_version)).await?;
+        let res =
+            TaskBmc::update_with_version(&ctx, &mm, fx_task.id, task_u(), Some(fx_version)).await;
+
+        // -- Check
+        let current = fx_version + 1;
+        assert_eq!(TaskBmc::get(&ctx, &mm, fx_task.id).await?.version, current);
+        assert!(
+            matches!(
+                res,
+                Err(crate::model::Error::VersionConflict { entity: "task", id, current: c })
+                    if id == fx_task.id && c == current
+            ),
+            "VersionConflict not matched"
+        );
+
+        // -- Cleanup
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_subtree_and_open_children_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_root_id = TaskBmc::create(
+            &ctx,
+            &mm,
+            TaskForCreate {
+                title: "test_subtree_and_open_children_ok-root".to_string(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let mut fx_ids = vec![fx_root_id];
+        for (title, parent_idx) in [("child", 0), ("grandchild", 1)] {
+            let task_c = TaskForCreate {
+                title: format!("test_subtree_and_open_children_ok-{title}"),
+                parent_id: Some(fx_ids[parent_idx]),
+                ..Default::default()
+            };
+            fx_ids.push(TaskBmc::create(&ctx, &mm, task_c).await?);
+        }
+        let done = || TaskForUpdate {
+            status: Some(TaskStatus::Done),
+            ..Default::default()
+        };
+
+        // -- Exec
+        let cycle_res = TaskBmc::set_parent(&ctx, &mm, fx_root_id, Some(fx_ids[2])).await;
+        let subtree = TaskBmc::list_subtree(&ctx, &mm, fx_root_id).await?;
+        let fail_res = TaskBmc::update_with_policy(
+            &ctx,
+            &mm,
+            fx_root_id,
+            done(),
+            None,
+            OpenChildrenPolicy::Fail,
+        )
+        .await;
+        let stale_res = TaskBmc::update_with_policy(
+            &ctx,
+            &mm,
+            fx_root_id,
+            done(),
+            Some(0),
+            OpenChildrenPolicy::Cascade,
+        )
+        .await;
+        let child_after_stale = TaskBmc::get(&ctx, &mm, fx_ids[1]).await?;
+        TaskBmc::update_with_policy(
+            &ctx,
+            &mm,
+            fx_root_id,
+            done(),
+            None,
+            OpenChildrenPolicy::Cascade,
+        )
+        .await?;
+
+        // -- Check
+        assert!(
+            matches!(
+                cycle_res,
+                Err(Error::TaskCycle {
+                    relation: "parent",
+                    ..
+                })
+            ),
+            "parent cycle not rejected"
+        );
+        let nodes: Vec<(i64, i32)> = subtree.iter().map(|n| (n.task.id, n.depth)).collect();
+        assert_eq!(nodes, [(fx_ids[0], 0), (fx_ids[1], 1), (fx_ids[2], 2)]);
+        match fail_res {
+            Err(Error::TaskHasOpenChildren { id, open_ids }) => {
+                assert_eq!(id, fx_root_id);
+                assert_eq!(open_ids, fx_ids[1..]);
+            }
+            other => panic!("TaskHasOpenChildren not matched: {other:?}"),
+        }
+        assert!(
+            matches!(stale_res, Err(Error::VersionConflict { .. })),
+            "stale cascade not rejected"
+        );
+        assert!(!child_after_stale.done, "cascade not rolled back");
+        for id in fx_ids.iter() {
+            let task = TaskBmc::get(&ctx, &mm, *id).await?;
+            assert!(task.done, "task {id} not done (cascade)");
+        }
+        TaskBmc::set_parent(&ctx, &mm, fx_ids[2], None).await?;
+        let subtree = TaskBmc::list_subtree(&ctx, &mm, fx_root_id).await?;
+        assert_eq!(subtree.len(), 2, "grandchild unlinked");
+
+        // -- Cleanup
+        for id in fx_ids {
+            TaskBmc::delete(&ctx, &mm, id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_subtree_cycle_guard_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_titles = &[
+            "test_list_subtree_cycle_guard_ok-task 01",
+            "test_list_subtree_cycle_guard_ok-task 02",
+        ];
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, fx_titles).await?;
+        let [id_01, id_02] = [fx_tasks[0].id, fx_tasks[1].id];
+        TaskBmc::set_parent(&ctx, &mm, id_02, Some(id_01)).await?;
+        // Note: A cycle written around `set_parent`.
+        sqlx::query("UPDATE task SET parent_id = $1 WHERE id = $2")
+            .bind(id_02)
+            .bind(id_01)
+            .execute(mm.db())
+            .await?;
+
+        // -- Exec
+        let subtree = TaskBmc::list_subtree(&ctx, &mm, id_01).await?;
+
+        // -- Check
+        let nodes: Vec<(i64, i32)> = subtree.iter().map(|n| (n.task.id, n.depth)).collect();
+        assert_eq!(nodes, [(id_01, 0), (id_02, 1)]);
+
+        // -- Cleanup
+        for id in [id_01, id_02] {
+            TaskBmc::set_parent(&ctx, &mm, id, None).await?;
+            TaskBmc::delete(&ctx, &mm, id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_done_recurring_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_title = "test_update_done_recurring_ok";
+        let task_c = TaskForCreate {
+            title: fx_title.to_string(),
+            due_date: Some(time::macros::date!(2099 - 01 - 15)),
+            priority: Some(TaskPriority::High),
+            recurrence: Some(
+                "FREQ=MONTHLY;BYMONTHDAY=31"
+                    .parse()
+                    .map_err(anyhow::Error::msg)?,
+            ),
+            ..Default::default()
+        };
+        let fx_id = TaskBmc::create(&ctx, &mm, task_c).await?;
+        let done = || TaskForUpdate {
+            status: Some(TaskStatus::Done),
+            ..Default::default()
+        };
+
+        // -- Exec
+        // Two concurrent completions (the second one sees the task done).
+        let (res_1, res_2) = tokio::join!(
+            TaskBmc::update(&ctx, &mm, fx_id, done()),
+            TaskBmc::update(&ctx, &mm, fx_id, done())
+        );
+        res_1?;
+        res_2?;
+        // Already done, no other occurrence.
+        TaskBmc::update(&ctx, &mm, fx_id, done()).await?;
+
+        // -- Check
+        let filters: Vec<TaskFilter> = serde_json::from_value(json!([{
+            "title": {"$eq": fx_title}
+        }]))?;
+        let tasks = TaskBmc::list(&ctx, &mm, Some(filters), None).await?;
+        assert_eq!(tasks.len(), 2, "completed task and next occurrence");
+        let (completed, next) = (&tasks[0], &tasks[1]);
+        assert_eq!(completed.id, fx_id);
+        assert!(completed.done);
+        assert_eq!(completed.recurrence, None, "recurrence moved");
+        assert!(!next.done);
+        assert_eq!(next.due_date, Some(time::macros::date!(2099 - 01 - 31)));
+        assert_eq!(next.priority, TaskPriority::High);
+        assert_eq!(
+            next.recurrence.as_ref().map(|r| r.to_string()).as_deref(),
+            Some("FREQ=MONTHLY;BYMONTHDAY=31")
+        );
+
+        // -- Cleanup
+        for task in tasks.iter() {
+            TaskBmc::delete(&ctx, &mm, task.id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_list_recurrence_malformed_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_title = "test_list_recurrence_malformed_ok";
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+            .await?
+            .remove(0);
+        // Note: A rule written around the model.
+        sqlx::query("UPDATE task SET recurrence = 'FREQ=YEARLY' WHERE id = $1")
+            .bind(fx_task.id)
+            .execute(mm.db())
+            .await?;
+
+        // -- Exec
+        let filters: Vec<TaskFilter> = serde_json::from_value(json!([{
+            "title": {"$eq": fx_title}
+        }]))?;
+        let tasks = TaskBmc::list(&ctx, &mm, Some(filters), None).await?;
+
+        // -- Check
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].recurrence, None);
+
+        // -- Cleanup
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_delete_restore_purge_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_title = "test_delete_restore_purge_ok - task 01";
+        let fx_task = _dev_utils::seed_tasks(&ctx, &mm, &[fx_title])
+            .await?
+            .remove(0);
+        let fx_filters: Vec<TaskFilter> =
+            serde_json::from_value(json!([{"id": {"$eq": fx_task.id}}]))?;
+
+        // -- Exec & Check - soft delete
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+        assert!(TaskBmc::get(&ctx, &mm, fx_task.id).await.is_err());
+        let deleted = TaskBmc::list_deleted(&ctx, &mm, Some(fx_filters), None).await?;
+        assert_eq!(deleted.len(), 1);
+        assert!(deleted[0].deleted_at.is_some());
+
+        // -- Exec & Check - restore
+        TaskBmc::restore(&ctx, &mm, fx_task.id).await?;
+        let task = TaskBmc::get(&ctx, &mm, fx_task.id).await?;
+        assert_eq!(task.deleted_at, None);
+        // Note: Only a soft deleted task can be purged.
+        assert!(TaskBmc::purge(&ctx, &mm, fx_task.id).await.is_err());
+
+        // -- Exec & Check - purge
+        TaskBmc::delete(&ctx, &mm, fx_task.id).await?;
+        TaskBmc::purge(&ctx, &mm, fx_task.id).await?;
+        assert!(TaskBmc::restore(&ctx, &mm, fx_task.id).await.is_err());
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_purge_err_access_denied() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let root_ctx = Ctx::root_ctx();
+        let fx_title = "test_purge_err_access_denied - task 01";
+        let fx_task = _dev_utils::seed_tasks(&root_ctx, &mm, &[fx_title])
+            .await?
+            .remove(0);
+        TaskBmc::delete(&root_ctx, &mm, fx_task.id).await?;
+        let user_id = UserBmc::create(
+            &root_ctx,
+            &mm,
+            UserForCreate {
+                username: "test_purge_err_access_denied-user".to_string(),
+                pwd_clear: "Correct-Horse-Battery-42".to_string(),
+            },
+        )
+        .await?;
+        let ctx = Ctx::new(user_id)?;
+
+        // -- Exec
+        let res = TaskBmc::purge(&ctx, &mm, fx_task.id).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::AccessDenied { entity: "task" })),
+            "AccessDenied not matched"
+        );
+        // Note: Still restorable.
+        TaskBmc::restore(&root_ctx, &mm, fx_task.id).await?;
+
+        // -- Cleanup
+        TaskBmc::delete(&root_ctx, &mm, fx_task.id).await?;
+        TaskBmc::purge(&root_ctx, &mm, fx_task.id).await?;
+        base::delete::<UserBmc>(&root_ctx, &mm, user_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_purge_deleted_before_batches_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_titles = &[
+            "test_purge_deleted_before_batches_ok - task 01",
+            "test_purge_deleted_before_batches_ok - task 02",
+            "test_purge_deleted_before_batches_ok - task 03",
+            "test_purge_deleted_before_batches_ok - task 04",
+        ];
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, fx_titles).await?;
+        let fx_ids: Vec<i64> = fx_tasks.iter().map(|t| t.id).collect();
+        for id in &fx_ids {
+            TaskBmc::delete(&ctx, &mm, *id).await?;
+        }
+        // The first 3 deleted 10 days ago, the last one just now.
+        sqlx::query("UPDATE task SET deleted_at = now() - interval '10 days' WHERE id = ANY($1)")
+            .bind(&fx_ids[..3])
+            .execute(mm.db())
+            .await?;
+        let fx_filters: Vec<TaskFilter> = serde_json::from_value(json!([{"id": {"$in": fx_ids}}]))?;
+
+        // -- Exec
+        // Note: Batches of 2, so more than one batch.
+        let count = base::purge_deleted_before::<TaskBmc>(
+            &ctx,
+            &mm,
+            now_utc() - time::Duration::days(5),
+            2,
+        )
+        .await?;
+
+        // -- Check
+        assert!(count >= 3, "Should purge at least the 3 old tasks");
+        let deleted = TaskBmc::list_deleted(&ctx, &mm, Some(fx_filters), None).await?;
+        let deleted_ids: Vec<i64> = deleted.iter().map(|t| t.id).collect();
+        assert_eq!(deleted_ids, &fx_ids[3..]);
+
+        // -- Cleanup
+        TaskBmc::purge(&ctx, &mm, fx_ids[3]).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_soft_delete_err_not_supported() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+
+        // -- Exec
+        let res = base::restore::<LabelBmc>(&ctx, &mm, 1000).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::SoftDeleteNotSupported { entity: "label" })),
+            "Should be SoftDeleteNotSupported, but was {res:?}"
+        );
+
+        Ok(())
+    }
+
     #[serial]
     #[tokio::test]
     async fn test_delete_err_not_found() -> Result<()> {
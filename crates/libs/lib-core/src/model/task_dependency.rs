@@ -0,0 +1,248 @@
+//! Task dependencies (`task_dependency`), a task blocked by the tasks it depends on.
+//!
+//! - The dependencies cannot have a cycle (`Error::TaskCycle`), checked on `link`
+//!   with the table locked (so that two concurrent links cannot make one).
+//! - The dependency chain of a task (all the tasks it transitively depends on)
+//!   is fetched in one recursive query (`list_chain`).
+
+use crate::ctx::Ctx;
+use crate::model::base::DbBmc;
+use crate::model::task::{task_columns_sql, TaskBmc, TaskNode};
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use sea_query::{Expr, Iden, OnConflict, PostgresQueryBuilder, Query};
+use sea_query_binder::SqlxBinder;
+
+/// The `reach (id)` recursive CTE, of the tasks the task `$1` depends on.
+/// Note: `UNION` visits each task once (whatever the number of paths, even with a cycle).
+const REACH_CTE: &str = "WITH RECURSIVE reach (id) AS ( \
+       SELECT depends_on_id FROM task_dependency WHERE task_id = $1 \
+       UNION \
+       SELECT d.depends_on_id FROM task_dependency d \
+       JOIN reach ON d.task_id = reach.id \
+     )";
+
+/// The `chain (id, depth)` recursive CTE, of the tasks the task `$1` depends on
+/// (depth 1 for its direct dependencies).
+/// Note: `UNION` keeps a row per task and depth (not per path), so at most
+///       tasks x depths rows (the dependencies have no cycle, see `link`).
+const CHAIN_CTE: &str = "WITH RECURSIVE chain (id, depth) AS ( \
+       SELECT depends_on_id, 1 FROM task_dependency WHERE task_id = $1 \
+       UNION \
+       SELECT d.depends_on_id, chain.depth + 1 FROM task_dependency d \
+       JOIN chain ON d.task_id = chain.id \
+     )";
+
+#[derive(Iden)]
+enum TaskDependencyIden {
+    TaskId,
+    DependsOnId,
+}
+
+pub struct TaskDependencyBmc;
+
+impl DbBmc for TaskDependencyBmc {
+    const TABLE: &'static str = "task_dependency";
+}
+
+impl TaskDependencyBmc {
+    /// Make the task depend on (be blocked by) the `depends_on_id` task,
+    /// `Error::TaskCycle` when that task is the task or depends on it (no-op when linked).
+    pub async fn link(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        task_id: i64,
+        depends_on_id: i64,
+    ) -> Result<()> {
+        // Note: Checks that both exist (and that they are not deleted).
+        TaskBmc::get(ctx, mm, task_id).await?;
+        TaskBmc::get(ctx, mm, depends_on_id).await?;
+
+        let mut tx = mm.db().begin().await?;
+        sqlx::query("LOCK TABLE task_dependency IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+
+        // -- Check the cycle (the task reached from the depends_on task)
+        let sql = format!("{REACH_CTE} SELECT EXISTS (SELECT 1 FROM reach WHERE id = $2)");
+        let (in_chain,) = sqlx::query_as::<_, (bool,)>(&sql)
+            .bind(depends_on_id)
+            .bind(task_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if in_chain || task_id == depends_on_id {
+            return Err(Error::TaskCycle {
+                relation: "dependency",
+                task_id,
+                linked_id: depends_on_id,
+            });
+        }
+
+        let mut query = Query::insert();
+        query
+            .into_table(Self::table_ref())
+            .columns([TaskDependencyIden::TaskId, TaskDependencyIden::DependsOnId])
+            .values([task_id.into(), depends_on_id.into()])?
+            .on_conflict(
+                OnConflict::columns([TaskDependencyIden::TaskId, TaskDependencyIden::DependsOnId])
+                    .do_nothing()
+                    .to_owned(),
+            );
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Remove the dependency (no-op when not linked).
+    pub async fn unlink(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        task_id: i64,
+        depends_on_id: i64,
+    ) -> Result<()> {
+        let mut query = Query::delete();
+        query
+            .from_table(Self::table_ref())
+            .and_where(Expr::col(TaskDependencyIden::TaskId).eq(task_id))
+            .and_where(Expr::col(TaskDependencyIden::DependsOnId).eq(depends_on_id));
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(mm.db()).await?;
+
+        Ok(())
+    }
+
+    /// All the tasks the task depends on (recursively, without the deleted ones),
+    /// by their shortest depth (1 for the direct dependencies).
+    pub async fn list_chain(ctx: &Ctx, mm: &ModelManager, task_id: i64) -> Result<Vec<TaskNode>> {
+        TaskBmc::get(ctx, mm, task_id).await?;
+
+        let sql = format!(
+            "{CHAIN_CTE} SELECT {}, c.depth \
+             FROM (SELECT id, min(depth) AS depth FROM chain GROUP BY id) c \
+             JOIN task t ON t.id = c.id \
+             WHERE t.deleted_at IS NULL \
+             ORDER BY c.depth, t.id",
+            task_columns_sql("t")
+        );
+        let nodes = sqlx::query_as::<_, TaskNode>(&sql)
+            .bind(task_id)
+            .fetch_all(mm.db())
+            .await?;
+
+        Ok(nodes)
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use anyhow::Result;
+    use serial_test::serial;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_link_chain_and_cycle_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_titles = &[
+            "test_link_chain_and_cycle_err-task 01",
+            "test_link_chain_and_cycle_err-task 02",
+            "test_link_chain_and_cycle_err-task 03",
+        ];
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, fx_titles).await?;
+        let [id_01, id_02, id_03] = [fx_tasks[0].id, fx_tasks[1].id, fx_tasks[2].id];
+
+        // -- Exec
+        // 01 depends on 02, which depends on 03 (and 01 also directly on 03).
+        TaskDependencyBmc::link(&ctx, &mm, id_01, id_02).await?;
+        TaskDependencyBmc::link(&ctx, &mm, id_02, id_03).await?;
+        TaskDependencyBmc::link(&ctx, &mm, id_01, id_03).await?;
+        let cycle_res = TaskDependencyBmc::link(&ctx, &mm, id_03, id_01).await;
+        let self_res = TaskDependencyBmc::link(&ctx, &mm, id_02, id_02).await;
+
+        // -- Check
+        assert!(
+            matches!(
+                cycle_res,
+                Err(Error::TaskCycle {
+                    relation: "dependency",
+                    ..
+                })
+            ),
+            "cycle not rejected"
+        );
+        assert!(
+            matches!(self_res, Err(Error::TaskCycle { .. })),
+            "self dependency not rejected"
+        );
+        let chain = TaskDependencyBmc::list_chain(&ctx, &mm, id_01).await?;
+        let chain: Vec<(i64, i32)> = chain.iter().map(|n| (n.task.id, n.depth)).collect();
+        assert_eq!(chain, [(id_02, 1), (id_03, 1)], "shortest depths");
+        TaskDependencyBmc::unlink(&ctx, &mm, id_01, id_03).await?;
+        let chain = TaskDependencyBmc::list_chain(&ctx, &mm, id_01).await?;
+        let chain: Vec<(i64, i32)> = chain.iter().map(|n| (n.task.id, n.depth)).collect();
+        assert_eq!(chain, [(id_02, 1), (id_03, 2)], "after unlink");
+
+        // -- Cleanup
+        for task in fx_tasks.iter() {
+            TaskBmc::delete(&ctx, &mm, task.id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_chain_diamonds_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_levels = 16;
+        let fx_titles: Vec<String> = (0..fx_levels * 3 + 1)
+            .map(|i| format!("test_chain_diamonds_ok-task {i:02}"))
+            .collect();
+        let fx_titles: Vec<&str> = fx_titles.iter().map(|t| t.as_str()).collect();
+        let fx_tasks = _dev_utils::seed_tasks(&ctx, &mm, &fx_titles).await?;
+        let ids: Vec<i64> = fx_tasks.iter().map(|t| t.id).collect();
+
+        // -- Exec
+        // Each level is a diamond (top on left and right, both on the next top),
+        // so 2^16 paths from the first top to the last one.
+        for level in 0..fx_levels {
+            let [top, left, right, next] = [0, 1, 2, 3].map(|i| ids[level * 3 + i]);
+            TaskDependencyBmc::link(&ctx, &mm, top, left).await?;
+            TaskDependencyBmc::link(&ctx, &mm, top, right).await?;
+            TaskDependencyBmc::link(&ctx, &mm, left, next).await?;
+            TaskDependencyBmc::link(&ctx, &mm, right, next).await?;
+        }
+        let cycle_res = TaskDependencyBmc::link(&ctx, &mm, ids[ids.len() - 1], ids[0]).await;
+        let chain = TaskDependencyBmc::list_chain(&ctx, &mm, ids[0]).await?;
+
+        // -- Check
+        assert!(
+            matches!(cycle_res, Err(Error::TaskCycle { .. })),
+            "cycle not rejected"
+        );
+        assert_eq!(chain.len(), ids.len() - 1);
+        let last = &chain[chain.len() - 1];
+        assert_eq!(last.task.id, ids[ids.len() - 1]);
+        assert_eq!(last.depth, fx_levels as i32 * 2);
+
+        // -- Cleanup
+        for task in fx_tasks.iter() {
+            TaskBmc::delete(&ctx, &mm, task.id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -1,14 +1,14 @@
 use crate::ctx::Ctx;
 use crate::model::base::{self, DbBmc};
 use crate::model::ModelManager;
-use crate::model::Result;
+use crate::model::{Error, Result};
 use lib_auth::pwd::{self, ContentToHash};
 use modql::field::{Fields, HasFields};
-use sea_query::{Expr, Iden, PostgresQueryBuilder, Query, SimpleExpr};
+use sea_query::{Expr, Iden, OnConflict, PostgresQueryBuilder, Query, SimpleExpr};
 use sea_query_binder::SqlxBinder;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgRow;
-use sqlx::FromRow;
+use sqlx::{FromRow, PgConnection};
 use uuid::Uuid;
 
 // region:         — User Types
@@ -17,6 +17,7 @@ use uuid::Uuid;
 pub struct User {
     pub id: i64,
     pub username: String,
+    pub is_admin: bool,
 }
 
 #[derive(Deserialize)]
@@ -25,10 +26,29 @@ pub struct UserForCreate {
     pub pwd_clear: String,
 }
 
-// #[derive(Fields)]
-// struct UserForInsert {
-//     username: String,
-// }
+#[derive(Fields)]
+struct UserForInsert {
+    username: String,
+}
+
+/// Identity from an OIDC (SSO) login.
+#[derive(Debug, Clone)]
+pub struct UserForOidc {
+    pub issuer: String,
+    pub subject: String,
+    /// Username for provisioning (or linking).
+    pub username: String,
+    /// Link to an existing (not yet linked) user with the same username.
+    /// Only when the provider vouches for it (e.g., verified email).
+    pub link_by_username: bool,
+}
+
+#[derive(Fields)]
+struct UserForOidcInsert {
+    username: String,
+    oidc_iss: String,
+    oidc_sub: String,
+}
 
 // login logic
 #[derive(Clone, FromRow, Fields, Debug)]
@@ -67,6 +87,8 @@ enum UserIden {
     Id,
     Username,
     Pwd,
+    OidcIss,
+    OidcSub,
 }
 
 // endregion:      — User Types
@@ -78,6 +100,63 @@ impl DbBmc for UserBmc {
 }
 
 impl UserBmc {
+    /// Create a new user (registration), enforcing the password policy.
+    pub async fn create(ctx: &Ctx, mm: &ModelManager, user_c: UserForCreate) -> Result<i64> {
+        let UserForCreate {
+            username,
+            pwd_clear,
+        } = user_c;
+
+        // -- Check username availability and password policy
+        if Self::first_by_username::<User>(ctx, mm, &username)
+            .await?
+            .is_some()
+        {
+            return Err(Error::UserAlreadyExists { username });
+        }
+        pwd::check_pwd_policy(&pwd_clear, &username).await?;
+
+        // -- Create the user with its password (in one transaction, so that a failure
+        //    does not leave a user without password)
+        let mut tx = mm.db().begin().await?;
+        let user_id = Self::insert_in(&mut tx, username).await?;
+        Self::update_pwd_in(ctx, &mut tx, user_id, &pwd_clear).await?;
+        tx.commit().await?;
+
+        Ok(user_id)
+    }
+
+    /// Insert the user row, `Error::UserAlreadyExists` when the username is taken
+    /// (also when taken by a concurrent registration, after the availability check).
+    async fn insert_in(conn: &mut PgConnection, username: String) -> Result<i64> {
+        // -- Build query
+        let fields = UserForInsert {
+            username: username.clone(),
+        }
+        .not_none_fields();
+        let (columns, sea_values) = fields.for_sea_insert();
+        let mut query = Query::insert();
+        query
+            .into_table(Self::table_ref())
+            .columns(columns)
+            .values(sea_values)?
+            .on_conflict(
+                OnConflict::column(UserIden::Username)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .returning(Query::returning().columns([UserIden::Id]));
+
+        // -- Exec query
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let (id,) = sqlx::query_as_with::<_, (i64,), _>(&sql, values)
+            .fetch_optional(conn)
+            .await?
+            .ok_or(Error::UserAlreadyExists { username })?;
+
+        Ok(id)
+    }
+
     pub async fn get<E>(ctx: &Ctx, mm: &ModelManager, id: i64) -> Result<E>
     where
         E: UserBy,
@@ -111,11 +190,128 @@ impl UserBmc {
         Ok(user)
     }
 
-    pub async fn update_pwd(ctx: &Ctx, mm: &ModelManager, id: i64, pwd_clear: &str) -> Result<()> {
+    /// Get the user of an OIDC identity, linking or provisioning it when needed.
+    ///
+    /// Note: Provisioned users have no password (SSO login only).
+    pub async fn first_or_create_by_oidc<E>(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        user_oidc: UserForOidc,
+    ) -> Result<E>
+    where
+        E: UserBy,
+    {
         let db = mm.db();
+        let UserForOidc {
+            issuer,
+            subject,
+            username,
+            link_by_username,
+        } = user_oidc;
+
+        // -- Already linked user
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(E::field_idens())
+            .and_where(Expr::col(UserIden::OidcIss).eq(&issuer))
+            .and_where(Expr::col(UserIden::OidcSub).eq(&subject));
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        if let Some(user) = sqlx::query_as_with::<_, E, _>(&sql, values)
+            .fetch_optional(db)
+            .await?
+        {
+            return Ok(user);
+        }
+
+        // -- Existing user with the same username (link, if allowed and not yet linked)
+        if let Some(user) = Self::first_by_username::<User>(ctx, mm, &username).await? {
+            if !link_by_username {
+                return Err(Error::UserAlreadyExists { username });
+            }
+
+            let mut query = Query::update();
+            query
+                .table(Self::table_ref())
+                .value(UserIden::OidcIss, SimpleExpr::from(issuer))
+                .value(UserIden::OidcSub, SimpleExpr::from(subject))
+                .and_where(Expr::col(UserIden::Id).eq(user.id))
+                .and_where(Expr::col(UserIden::OidcSub).is_null());
+
+            let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+            let count = sqlx::query_with(&sql, values)
+                .execute(db)
+                .await?
+                .rows_affected();
+            if count == 0 {
+                return Err(Error::UserAlreadyExists { username });
+            }
+
+            return Self::get(ctx, mm, user.id).await;
+        }
+
+        // -- Provision a new user
+        let user_fi = UserForOidcInsert {
+            username,
+            oidc_iss: issuer,
+            oidc_sub: subject,
+        };
+        let user_id = base::create::<Self, _>(ctx, mm, user_fi).await?;
 
+        Self::get(ctx, mm, user_id).await
+    }
+
+    /// Change the user password (e.g., password change or reset), enforcing
+    /// the password policy.
+    pub async fn change_pwd(ctx: &Ctx, mm: &ModelManager, id: i64, pwd_clear: &str) -> Result<()> {
+        let user: User = Self::get(ctx, mm, id).await?;
+        pwd::check_pwd_policy(pwd_clear, &user.username).await?;
+
+        Self::update_pwd(ctx, mm, id, pwd_clear).await
+    }
+
+    /// Reset the password of a user (admin users only), enforcing the password policy.
+    pub async fn reset_pwd(ctx: &Ctx, mm: &ModelManager, id: i64, pwd_clear: &str) -> Result<()> {
+        Self::check_admin(ctx, mm, Self::TABLE).await?;
+
+        Self::change_pwd(ctx, mm, id, pwd_clear).await
+    }
+
+    /// `Error::AccessDenied` (on the `entity`) unless the root ctx or an admin user.
+    pub(in crate::model) async fn check_admin(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        entity: &'static str,
+    ) -> Result<()> {
+        if ctx.user_id() != 0 {
+            let user: User = Self::get(ctx, mm, ctx.user_id()).await?;
+            if !user.is_admin {
+                return Err(Error::AccessDenied { entity });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hash and set the user password, without any password policy check.
+    ///
+    /// Note: Only for re-hashing an already accepted password (e.g., scheme update)
+    ///       or dev seeding. New passwords should go through `create`, `change_pwd`
+    ///       or `reset_pwd`.
+    pub async fn update_pwd(ctx: &Ctx, mm: &ModelManager, id: i64, pwd_clear: &str) -> Result<()> {
+        let mut conn = mm.db().acquire().await?;
+        Self::update_pwd_in(ctx, &mut conn, id, pwd_clear).await
+    }
+
+    async fn update_pwd_in(
+        ctx: &Ctx,
+        conn: &mut PgConnection,
+        id: i64,
+        pwd_clear: &str,
+    ) -> Result<()> {
         // -- Prep password
-        let user: UserForLogin = Self::get(ctx, mm, id).await?;
+        let user: UserForLogin = base::get_in::<Self, _>(ctx, conn, id).await?;
         let pwd = pwd::hash_pwd(ContentToHash {
             content: pwd_clear.to_string(),
             salt: user.pwd_salt,
@@ -132,7 +328,7 @@ impl UserBmc {
         // -- Exec query
         let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
         let _count = sqlx::query_with(&sql, values)
-            .execute(db)
+            .execute(conn)
             .await?
             .rows_affected();
 
@@ -168,5 +364,286 @@ mod tests {
 
         Ok(())
     }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_username = "test_create_ok-user-01";
+        let fx_pwd_clear = "Correct-Horse-Battery-42";
+
+        // -- Exec
+        let user_id = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: fx_username.to_string(),
+                pwd_clear: fx_pwd_clear.to_string(),
+            },
+        )
+        .await?;
+
+        // -- Check
+        let user: UserForLogin = UserBmc::get(&ctx, &mm, user_id).await?;
+        assert_eq!(user.username, fx_username);
+        pwd::validate_pwd(
+            ContentToHash {
+                content: fx_pwd_clear.to_string(),
+                salt: user.pwd_salt,
+            },
+            &user.pwd.context("Should have a pwd")?,
+        )
+        .await?;
+
+        // -- Cleanup
+        base::delete::<UserBmc>(&ctx, &mm, user_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_err_already_exists_concurrent() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_username = "test_create_err_already_exists_concurrent-user-01";
+        let user_c = || UserForCreate {
+            username: fx_username.to_string(),
+            pwd_clear: "Correct-Horse-Battery-42".to_string(),
+        };
+
+        // -- Exec
+        // Note: Both pass the availability check before either inserts.
+        let (res_1, res_2) = tokio::join!(
+            UserBmc::create(&ctx, &mm, user_c()),
+            UserBmc::create(&ctx, &mm, user_c())
+        );
+
+        // -- Check
+        let (user_id, err_res) = match (res_1, res_2) {
+            (Ok(user_id), err_res) | (err_res, Ok(user_id)) => (user_id, err_res),
+            other => panic!("one create should succeed, but was `{other:?}`"),
+        };
+        assert!(
+            matches!(&err_res, Err(Error::UserAlreadyExists { username }) if username == fx_username),
+            "Should have matched UserAlreadyExists but was `{err_res:?}`"
+        );
+
+        // -- Cleanup
+        base::delete::<UserBmc>(&ctx, &mm, user_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_err_pwd_policy() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_username = "test_create_err_pwd_policy-user-01";
+
+        // -- Exec
+        let res = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: fx_username.to_string(),
+                pwd_clear: "welcome".to_string(),
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(
+                res,
+                Err(Error::Pwd(pwd::Error::Policy(
+                    pwd::policy::Error::Violations(_)
+                )))
+            ),
+            "Should have matched a policy violation but was `{res:?}`"
+        );
+        let user: Option<User> = UserBmc::first_by_username(&ctx, &mm, fx_username).await?;
+        assert!(user.is_none(), "user should not have been created");
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_err_already_exists() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+
+        // -- Exec
+        let res = UserBmc::create(
+            &ctx,
+            &mm,
+            UserForCreate {
+                username: "demo1".to_string(),
+                pwd_clear: "Correct-Horse-Battery-42".to_string(),
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::UserAlreadyExists { .. })),
+            "Should have matched `Err(Error::UserAlreadyExists)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_reset_pwd_ok_and_err() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let root_ctx = Ctx::root_ctx();
+        let admin_ctx = Ctx::new(1000)?; // demo1 (admin)
+        let fx_new_pwd = "Staple-Orbit-Lantern-77";
+        let user_id = UserBmc::create(
+            &root_ctx,
+            &mm,
+            UserForCreate {
+                username: "test_reset_pwd_ok_and_err-user-01".to_string(),
+                pwd_clear: "Correct-Horse-Battery-42".to_string(),
+            },
+        )
+        .await?;
+        let user_ctx = Ctx::new(user_id)?;
+
+        // -- Exec
+        let not_admin_res = UserBmc::reset_pwd(&user_ctx, &mm, 1000, fx_new_pwd).await;
+        let policy_res = UserBmc::reset_pwd(&admin_ctx, &mm, user_id, "welcome").await;
+        UserBmc::reset_pwd(&admin_ctx, &mm, user_id, fx_new_pwd).await?;
+
+        // -- Check
+        assert!(
+            matches!(not_admin_res, Err(Error::AccessDenied { entity: "user" })),
+            "Should have matched AccessDenied but was `{not_admin_res:?}`"
+        );
+        assert!(
+            matches!(
+                policy_res,
+                Err(Error::Pwd(pwd::Error::Policy(
+                    pwd::policy::Error::Violations(_)
+                )))
+            ),
+            "Should have matched a policy violation but was `{policy_res:?}`"
+        );
+        let user: UserForLogin = UserBmc::get(&root_ctx, &mm, user_id).await?;
+        pwd::validate_pwd(
+            ContentToHash {
+                content: fx_new_pwd.to_string(),
+                salt: user.pwd_salt,
+            },
+            &user.pwd.context("Should have a pwd")?,
+        )
+        .await?;
+
+        // -- Cleanup
+        base::delete::<UserBmc>(&root_ctx, &mm, user_id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_first_or_create_by_oidc_provision_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_user_oidc = UserForOidc {
+            issuer: "https://fx-issuer".to_string(),
+            subject: "fx-sub-01".to_string(),
+            username: "test_first_or_create_by_oidc_provision_ok@example.com".to_string(),
+            link_by_username: false,
+        };
+
+        // -- Exec
+        let user: UserForLogin =
+            UserBmc::first_or_create_by_oidc(&ctx, &mm, fx_user_oidc.clone()).await?;
+        let user_again: User = UserBmc::first_or_create_by_oidc(&ctx, &mm, fx_user_oidc).await?;
+
+        // -- Check
+        assert_eq!(
+            user.username,
+            "test_first_or_create_by_oidc_provision_ok@example.com"
+        );
+        assert!(user.pwd.is_none(), "provisioned user should have no pwd");
+        assert_eq!(user_again.id, user.id);
+
+        // -- Cleanup
+        base::delete::<UserBmc>(&ctx, &mm, user.id).await?;
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_first_or_create_by_oidc_link() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_user_oidc = UserForOidc {
+            issuer: "https://fx-issuer".to_string(),
+            subject: "fx-sub-02".to_string(),
+            username: "demo1".to_string(),
+            link_by_username: false,
+        };
+
+        // -- Exec
+        let res_no_link =
+            UserBmc::first_or_create_by_oidc::<User>(&ctx, &mm, fx_user_oidc.clone()).await;
+        let user: User = UserBmc::first_or_create_by_oidc(
+            &ctx,
+            &mm,
+            UserForOidc {
+                link_by_username: true,
+                ..fx_user_oidc.clone()
+            },
+        )
+        .await?;
+        let res_other_sub = UserBmc::first_or_create_by_oidc::<User>(
+            &ctx,
+            &mm,
+            UserForOidc {
+                subject: "fx-sub-03".to_string(),
+                link_by_username: true,
+                ..fx_user_oidc
+            },
+        )
+        .await;
+
+        // -- Check
+        assert!(
+            matches!(res_no_link, Err(Error::UserAlreadyExists { .. })),
+            "Should have matched `Err(Error::UserAlreadyExists)` but was `{res_no_link:?}`"
+        );
+        assert_eq!(user.username, "demo1");
+        assert!(
+            matches!(res_other_sub, Err(Error::UserAlreadyExists { .. })),
+            "Should have matched `Err(Error::UserAlreadyExists)` but was `{res_other_sub:?}`"
+        );
+
+        // -- Cleanup
+        let mut query = Query::update();
+        query
+            .table(UserBmc::table_ref())
+            .value(UserIden::OidcIss, Option::<String>::None)
+            .value(UserIden::OidcSub, Option::<String>::None)
+            .and_where(Expr::col(UserIden::Id).eq(user.id));
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(mm.db()).await?;
+
+        Ok(())
+    }
 }
 // endregion: --- Tests
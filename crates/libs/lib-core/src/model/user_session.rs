@@ -0,0 +1,350 @@
+//! Server side user sessions (one per login), so that a user can see where
+//! they are logged in and revoke a single device.
+//!
+//! The web token carries the session id, and is only valid while the session exists.
+
+use crate::core_config;
+use crate::ctx::Ctx;
+use crate::model::base::{self, DbBmc};
+use crate::model::ModelManager;
+use crate::model::{Error, Result};
+use lib_utils::time::now_utc;
+use modql::field::{Fields, HasFields};
+use sea_query::{Expr, Iden, Order, PostgresQueryBuilder, Query, SimpleExpr};
+use sea_query_binder::SqlxBinder;
+use serde::Serialize;
+use sqlx::FromRow;
+use time::{Duration, OffsetDateTime};
+
+// region:         — UserSession Types
+
+#[derive(Debug, Clone, Serialize, Fields, FromRow)]
+pub struct UserSession {
+    pub id: i64,
+    pub user_id: i64,
+
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub ctime: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_seen: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Fields)]
+pub struct UserSessionForCreate {
+    pub user_id: i64,
+
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+#[derive(Iden)]
+enum UserSessionIden {
+    Id,
+    UserId,
+    Ip,
+    LastSeen,
+}
+
+// endregion:      — UserSession Types
+
+pub struct UserSessionBmc;
+
+impl DbBmc for UserSessionBmc {
+    const TABLE: &'static str = "user_session";
+}
+
+impl UserSessionBmc {
+    pub async fn create(
+        ctx: &Ctx,
+        mm: &ModelManager,
+        session_c: UserSessionForCreate,
+    ) -> Result<i64> {
+        base::create::<Self, _>(ctx, mm, session_c).await
+    }
+
+    /// Check that the session exists for this user and did not expire (not seen for
+    /// `SESSION_IDLE_TIMEOUT_SEC`), and update its `last_seen` (and `ip`) on token refresh,
+    /// at most once per `SESSION_TOUCH_INTERVAL_SEC` (not a write per request).
+    /// Returns `Error::EntityNotFound` when the session does not exist (anymore)
+    /// for this user.
+    pub async fn touch(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        user_id: i64,
+        ip: Option<String>,
+    ) -> Result<()> {
+        let db = mm.db();
+        let now = now_utc();
+
+        // -- Check the session (not expired)
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .column(UserSessionIden::LastSeen)
+            .and_where(Expr::col(UserSessionIden::Id).eq(id))
+            .and_where(Expr::col(UserSessionIden::UserId).eq(user_id))
+            .and_where(Expr::col(UserSessionIden::LastSeen).gt(expired_before(now)));
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let (last_seen,) = sqlx::query_as_with::<_, (OffsetDateTime,), _>(&sql, values)
+            .fetch_optional(db)
+            .await?
+            .ok_or(Error::EntityNotFound {
+                entity: Self::TABLE,
+                id,
+            })?;
+
+        // -- Touch (when not touched recently)
+        let touch_interval = Duration::seconds(core_config().SESSION_TOUCH_INTERVAL_SEC);
+        if now - last_seen < touch_interval {
+            return Ok(());
+        }
+
+        let mut query = Query::update();
+        query
+            .table(Self::table_ref())
+            .value(UserSessionIden::LastSeen, Expr::current_timestamp())
+            .and_where(Expr::col(UserSessionIden::Id).eq(id));
+        if let Some(ip) = ip {
+            query.value(UserSessionIden::Ip, SimpleExpr::from(ip));
+        }
+
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        sqlx::query_with(&sql, values).execute(db).await?;
+
+        Ok(())
+    }
+
+    /// Delete the expired sessions (typically from a background job).
+    /// Returns the number of deleted sessions.
+    pub async fn prune_expired(_ctx: &Ctx, mm: &ModelManager) -> Result<u64> {
+        let db = mm.db();
+
+        // -- Build query
+        let mut query = Query::delete();
+        query
+            .from_table(Self::table_ref())
+            .and_where(Expr::col(UserSessionIden::LastSeen).lte(expired_before(now_utc())));
+
+        // -- Exec query
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let count = sqlx::query_with(&sql, values)
+            .execute(db)
+            .await?
+            .rows_affected();
+
+        Ok(count)
+    }
+
+    /// The (not expired) user sessions, most recently seen first.
+    pub async fn list_for_user(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        user_id: i64,
+    ) -> Result<Vec<UserSession>> {
+        let db = mm.db();
+
+        // -- Build query
+        let mut query = Query::select();
+        query
+            .from(Self::table_ref())
+            .columns(UserSession::field_column_refs())
+            .and_where(Expr::col(UserSessionIden::UserId).eq(user_id))
+            .and_where(Expr::col(UserSessionIden::LastSeen).gt(expired_before(now_utc())))
+            .order_by(UserSessionIden::LastSeen, Order::Desc);
+
+        // -- Exec query
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let sessions = sqlx::query_as_with::<_, UserSession, _>(&sql, values)
+            .fetch_all(db)
+            .await?;
+
+        Ok(sessions)
+    }
+
+    /// Delete (revoke) a session of the user, returning it.
+    pub async fn delete_for_user(
+        _ctx: &Ctx,
+        mm: &ModelManager,
+        id: i64,
+        user_id: i64,
+    ) -> Result<UserSession> {
+        let db = mm.db();
+
+        // -- Build query
+        let mut query = Query::delete();
+        query
+            .from_table(Self::table_ref())
+            .and_where(Expr::col(UserSessionIden::Id).eq(id))
+            .and_where(Expr::col(UserSessionIden::UserId).eq(user_id))
+            .returning(Query::returning().columns(UserSession::field_column_refs()));
+
+        // -- Exec query
+        let (sql, values) = query.build_sqlx(PostgresQueryBuilder);
+        let session = sqlx::query_as_with::<_, UserSession, _>(&sql, values)
+            .fetch_optional(db)
+            .await?
+            .ok_or(Error::EntityNotFound {
+                entity: Self::TABLE,
+                id,
+            })?;
+
+        Ok(session)
+    }
+}
+
+/// The sessions last seen before it are expired.
+fn expired_before(now: OffsetDateTime) -> OffsetDateTime {
+    now - Duration::seconds(core_config().SESSION_IDLE_TIMEOUT_SEC)
+}
+
+// region:    --- Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_dev_utils;
+    use anyhow::Result;
+    use serial_test::serial;
+
+    const FX_DEMO1_ID: i64 = 1000;
+
+    #[serial]
+    #[tokio::test]
+    async fn test_touch_list_delete_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_session_c = UserSessionForCreate {
+            user_id: FX_DEMO1_ID,
+            user_agent: Some("fx-agent".to_string()),
+            ip: Some("127.0.0.1".to_string()),
+        };
+        let session_id = UserSessionBmc::create(&ctx, &mm, fx_session_c).await?;
+
+        // -- Exec
+        // Not touched (just created), then touched (last seen before the touch interval).
+        UserSessionBmc::touch(&ctx, &mm, session_id, FX_DEMO1_ID, Some("10.0.0.1".into())).await?;
+        let sessions_throttled = UserSessionBmc::list_for_user(&ctx, &mm, FX_DEMO1_ID).await?;
+        let touch_interval = core_config().SESSION_TOUCH_INTERVAL_SEC;
+        set_last_seen(
+            &mm,
+            session_id,
+            now_utc() - Duration::seconds(touch_interval + 1),
+        )
+        .await?;
+        UserSessionBmc::touch(&ctx, &mm, session_id, FX_DEMO1_ID, Some("10.0.0.1".into())).await?;
+        let sessions = UserSessionBmc::list_for_user(&ctx, &mm, FX_DEMO1_ID).await?;
+        let deleted = UserSessionBmc::delete_for_user(&ctx, &mm, session_id, FX_DEMO1_ID).await?;
+
+        // -- Check
+        let find_session = |sessions: &[UserSession]| {
+            sessions
+                .iter()
+                .find(|s| s.id == session_id)
+                .cloned()
+                .expect("Should have the session")
+        };
+        assert_eq!(
+            find_session(&sessions_throttled).ip.as_deref(),
+            Some("127.0.0.1")
+        );
+        let session = find_session(&sessions);
+        assert_eq!(session.user_agent.as_deref(), Some("fx-agent"));
+        assert_eq!(session.ip.as_deref(), Some("10.0.0.1"));
+        assert!(session.last_seen >= session.ctime);
+        assert_eq!(deleted.id, session_id);
+        let res = UserSessionBmc::touch(&ctx, &mm, session_id, FX_DEMO1_ID, None).await;
+        assert!(
+            matches!(res, Err(Error::EntityNotFound { .. })),
+            "Should have matched `Err(Error::EntityNotFound)` but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_touch_err_expired_and_prune() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_session_c = UserSessionForCreate {
+            user_id: FX_DEMO1_ID,
+            user_agent: None,
+            ip: None,
+        };
+        let session_id = UserSessionBmc::create(&ctx, &mm, fx_session_c).await?;
+        let idle_timeout = core_config().SESSION_IDLE_TIMEOUT_SEC;
+        set_last_seen(
+            &mm,
+            session_id,
+            now_utc() - Duration::seconds(idle_timeout + 1),
+        )
+        .await?;
+
+        // -- Exec
+        let res = UserSessionBmc::touch(&ctx, &mm, session_id, FX_DEMO1_ID, None).await;
+        let sessions = UserSessionBmc::list_for_user(&ctx, &mm, FX_DEMO1_ID).await?;
+        let pruned_count = UserSessionBmc::prune_expired(&ctx, &mm).await?;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::EntityNotFound { .. })),
+            "Should have matched `Err(Error::EntityNotFound)` but was `{res:?}`"
+        );
+        assert!(
+            sessions.iter().all(|s| s.id != session_id),
+            "expired listed"
+        );
+        assert!(pruned_count >= 1);
+        let res = UserSessionBmc::delete_for_user(&ctx, &mm, session_id, FX_DEMO1_ID).await;
+        assert!(
+            matches!(res, Err(Error::EntityNotFound { .. })),
+            "Should have been pruned but was `{res:?}`"
+        );
+
+        Ok(())
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_delete_for_user_err_other_user() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        let ctx = Ctx::root_ctx();
+        let fx_session_c = UserSessionForCreate {
+            user_id: FX_DEMO1_ID,
+            user_agent: None,
+            ip: None,
+        };
+        let session_id = UserSessionBmc::create(&ctx, &mm, fx_session_c).await?;
+
+        // -- Exec
+        let res = UserSessionBmc::delete_for_user(&ctx, &mm, session_id, FX_DEMO1_ID + 1).await;
+
+        // -- Check
+        assert!(
+            matches!(res, Err(Error::EntityNotFound { .. })),
+            "Should have matched `Err(Error::EntityNotFound)` but was `{res:?}`"
+        );
+
+        // -- Cleanup
+        UserSessionBmc::delete_for_user(&ctx, &mm, session_id, FX_DEMO1_ID).await?;
+
+        Ok(())
+    }
+
+    async fn set_last_seen(mm: &ModelManager, id: i64, last_seen: OffsetDateTime) -> Result<()> {
+        sqlx::query("UPDATE user_session SET last_seen = $1 WHERE id = $2")
+            .bind(last_seen)
+            .bind(id)
+            .execute(mm.db())
+            .await?;
+
+        Ok(())
+    }
+}
+// endregion: --- Tests
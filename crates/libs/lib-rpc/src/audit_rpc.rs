@@ -0,0 +1,29 @@
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::audit_log::{AuditLog, AuditLogBmc, AuditLogQuery};
+use lib_core::model::ModelManager;
+use modql::filter::ListOptions;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ParamsAuditList {
+    #[serde(flatten)]
+    pub query: AuditLogQuery,
+    pub list_options: Option<ListOptions>,
+}
+
+/// List the audit entries of an entity (admin users only).
+pub async fn list_audit_entries(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsAuditList,
+) -> Result<Vec<AuditLog>> {
+    let ParamsAuditList {
+        query,
+        list_options,
+    } = params;
+
+    let entries = AuditLogBmc::list(&ctx, &mm, query, list_options).await?;
+
+    Ok(entries)
+}
@@ -0,0 +1,70 @@
+use crate::params::{ParamsForCreate, ParamsForUpdate, ParamsIded};
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::comment::{Comment, CommentBmc, CommentForCreate, CommentForUpdate};
+use lib_core::model::ModelManager;
+use modql::filter::ListOptions;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ParamsCommentList {
+    pub task_id: i64,
+    pub list_options: Option<ListOptions>,
+}
+
+pub async fn add_comment(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForCreate<CommentForCreate>,
+) -> Result<Comment> {
+    let ParamsForCreate { data } = params;
+
+    let id = CommentBmc::create(&ctx, &mm, data).await?;
+    let comment = CommentBmc::get(&ctx, &mm, id).await?;
+
+    Ok(comment)
+}
+
+pub async fn list_comments(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsCommentList,
+) -> Result<Vec<Comment>> {
+    let ParamsCommentList {
+        task_id,
+        list_options,
+    } = params;
+
+    let comments = CommentBmc::list_for_task(&ctx, &mm, task_id, list_options).await?;
+
+    Ok(comments)
+}
+
+/// Update a comment body (author only).
+pub async fn update_comment(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForUpdate<CommentForUpdate>,
+) -> Result<Comment> {
+    let ParamsForUpdate {
+        id,
+        data,
+        expected_version,
+    } = params;
+
+    CommentBmc::update_with_version(&ctx, &mm, id, data, expected_version).await?;
+
+    let comment = CommentBmc::get(&ctx, &mm, id).await?;
+
+    Ok(comment)
+}
+
+/// Delete a comment (author only). Its replies are kept.
+pub async fn delete_comment(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Comment> {
+    let ParamsIded { id } = params;
+
+    let comment = CommentBmc::get(&ctx, &mm, id).await?;
+    CommentBmc::delete(&ctx, &mm, id).await?;
+
+    Ok(comment)
+}
@@ -0,0 +1,61 @@
+use crate::params::{ParamsForCreate, ParamsIded, ParamsList};
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::label::{Label, LabelBmc, LabelFilter, LabelForCreate};
+use lib_core::model::ModelManager;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ParamsTaskLabel {
+    pub task_id: i64,
+    pub label_id: i64,
+}
+
+pub async fn create_label(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsForCreate<LabelForCreate>,
+) -> Result<Label> {
+    let ParamsForCreate { data } = params;
+
+    let id = LabelBmc::create(&ctx, &mm, data).await?;
+    let label = LabelBmc::get(&ctx, &mm, id).await?;
+
+    Ok(label)
+}
+
+pub async fn list_labels(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsList<LabelFilter>,
+) -> Result<Vec<Label>> {
+    let labels = LabelBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+
+    Ok(labels)
+}
+
+/// Delete a label (detached from all of its tasks).
+pub async fn delete_label(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Label> {
+    let ParamsIded { id } = params;
+
+    let label = LabelBmc::get(&ctx, &mm, id).await?;
+    LabelBmc::delete(&ctx, &mm, id).await?;
+
+    Ok(label)
+}
+
+pub async fn attach_label(ctx: Ctx, mm: ModelManager, params: ParamsTaskLabel) -> Result<()> {
+    let ParamsTaskLabel { task_id, label_id } = params;
+
+    LabelBmc::attach(&ctx, &mm, task_id, label_id).await?;
+
+    Ok(())
+}
+
+pub async fn detach_label(ctx: Ctx, mm: ModelManager, params: ParamsTaskLabel) -> Result<()> {
+    let ParamsTaskLabel { task_id, label_id } = params;
+
+    LabelBmc::detach(&ctx, &mm, task_id, label_id).await?;
+
+    Ok(())
+}
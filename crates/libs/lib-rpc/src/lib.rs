@@ -1,16 +1,31 @@
 // region:    --- Modules
 
+mod audit_rpc;
+mod comment_rpc;
 mod error;
+mod label_rpc;
 mod params;
 mod task_rpc;
+mod user_rpc;
+mod user_session_rpc;
 
 pub use self::error::{Error, Result};
 
+use audit_rpc::list_audit_entries;
+use comment_rpc::{add_comment, delete_comment, list_comments, update_comment};
+use label_rpc::{attach_label, create_label, delete_label, detach_label, list_labels};
 use lib_core::ctx::Ctx;
 use lib_core::model::ModelManager;
 use serde::Deserialize;
 use serde_json::{from_value, to_value, Value};
-use task_rpc::{create_task, delete_task, list_tasks, update_task};
+use task_rpc::{
+    aggregate_tasks, count_tasks, create_task, delete_task, link_dependency, link_subtask,
+    list_deleted_tasks, list_task_dependencies, list_task_subtree, list_tasks, preview_recurrence,
+    purge_task, restore_task, search_tasks, set_task_recurrence, unlink_dependency, unlink_subtask,
+    update_task,
+};
+use user_rpc::reset_user_pwd;
+use user_session_rpc::{list_my_sessions, revoke_session};
 
 // endregion: --- Modules
 
@@ -52,8 +67,45 @@ pub async fn exec_rpc(ctx: Ctx, mm: ModelManager, rpc_req: RpcRequest) -> Result
         // -- Task RPC methods.
         "create_task" => exec_rpc_fn!(create_task, ctx, mm, rpc_params),
         "list_tasks" => exec_rpc_fn!(list_tasks, ctx, mm, rpc_params),
+        "search_tasks" => exec_rpc_fn!(search_tasks, ctx, mm, rpc_params),
+        "count_tasks" => exec_rpc_fn!(count_tasks, ctx, mm, rpc_params),
+        "aggregate_tasks" => exec_rpc_fn!(aggregate_tasks, ctx, mm, rpc_params),
         "update_task" => exec_rpc_fn!(update_task, ctx, mm, rpc_params),
         "delete_task" => exec_rpc_fn!(delete_task, ctx, mm, rpc_params),
+        "list_deleted_tasks" => exec_rpc_fn!(list_deleted_tasks, ctx, mm, rpc_params),
+        "restore_task" => exec_rpc_fn!(restore_task, ctx, mm, rpc_params),
+        "purge_task" => exec_rpc_fn!(purge_task, ctx, mm, rpc_params),
+        "link_subtask" => exec_rpc_fn!(link_subtask, ctx, mm, rpc_params),
+        "unlink_subtask" => exec_rpc_fn!(unlink_subtask, ctx, mm, rpc_params),
+        "list_task_subtree" => exec_rpc_fn!(list_task_subtree, ctx, mm, rpc_params),
+        "link_dependency" => exec_rpc_fn!(link_dependency, ctx, mm, rpc_params),
+        "unlink_dependency" => exec_rpc_fn!(unlink_dependency, ctx, mm, rpc_params),
+        "list_task_dependencies" => exec_rpc_fn!(list_task_dependencies, ctx, mm, rpc_params),
+        "set_task_recurrence" => exec_rpc_fn!(set_task_recurrence, ctx, mm, rpc_params),
+        "preview_recurrence" => exec_rpc_fn!(preview_recurrence, ctx, mm, rpc_params),
+
+        // -- Comment RPC methods.
+        "add_comment" => exec_rpc_fn!(add_comment, ctx, mm, rpc_params),
+        "list_comments" => exec_rpc_fn!(list_comments, ctx, mm, rpc_params),
+        "update_comment" => exec_rpc_fn!(update_comment, ctx, mm, rpc_params),
+        "delete_comment" => exec_rpc_fn!(delete_comment, ctx, mm, rpc_params),
+
+        // -- Label RPC methods.
+        "create_label" => exec_rpc_fn!(create_label, ctx, mm, rpc_params),
+        "list_labels" => exec_rpc_fn!(list_labels, ctx, mm, rpc_params),
+        "delete_label" => exec_rpc_fn!(delete_label, ctx, mm, rpc_params),
+        "attach_label" => exec_rpc_fn!(attach_label, ctx, mm, rpc_params),
+        "detach_label" => exec_rpc_fn!(detach_label, ctx, mm, rpc_params),
+
+        // -- User RPC methods.
+        "reset_user_pwd" => exec_rpc_fn!(reset_user_pwd, ctx, mm, rpc_params),
+
+        // -- User Session RPC methods.
+        "list_my_sessions" => exec_rpc_fn!(list_my_sessions, ctx, mm),
+        "revoke_session" => exec_rpc_fn!(revoke_session, ctx, mm, rpc_params),
+
+        // -- Audit RPC methods.
+        "list_audit_entries" => exec_rpc_fn!(list_audit_entries, ctx, mm, rpc_params),
 
         // -- Fallback as Err.
         _ => return Err(Error::RpcMethodUnknown(rpc_method)),
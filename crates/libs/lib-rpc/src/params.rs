@@ -4,6 +4,7 @@
 //! Most of these base constructs use generics for their respective data elements, allowing
 //! each rpc handler function to receive the exact desired type.
 //!
+use lib_core::model::{Aggregate, GroupBy};
 use modql::filter::ListOptions;
 use serde::{de::DeserializeOwned, Deserialize};
 use serde_with::{serde_as, OneOrMany};
@@ -17,6 +18,9 @@ pub struct ParamsForCreate<D> {
 pub struct ParamsForUpdate<D> {
     pub id: i64,
     pub data: D,
+    /// When set, the update fails with a version conflict if the entity is
+    /// not at this version (optimistic concurrency).
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Deserialize)]
@@ -24,8 +28,11 @@ pub struct ParamsIded {
     pub id: i64,
 }
 
+/// The params of the lists without pagination (e.g., `list_labels`).
+/// Note: The unknown fields (e.g., a `cursor`) are rejected rather than ignored.
 #[serde_as]
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ParamsList<F>
 where
     F: DeserializeOwned,
@@ -34,3 +41,58 @@ where
     pub filters: Option<Vec<F>>,
     pub list_options: Option<ListOptions>,
 }
+
+/// The params of the paginated lists (e.g., `list_tasks`).
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsListPage<F>
+where
+    F: DeserializeOwned,
+{
+    #[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+    pub filters: Option<Vec<F>>,
+    pub list_options: Option<ListOptions>,
+    /// The `next_cursor` of the previous page.
+    pub cursor: Option<String>,
+    /// Also return the total count of the filtered list (an additional count query).
+    #[serde(default)]
+    pub include_total: bool,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsSearch<F>
+where
+    F: DeserializeOwned,
+{
+    /// The full-text search terms (web search syntax, e.g., `"some phrase" -other`).
+    pub terms: String,
+    #[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+    pub filters: Option<Vec<F>>,
+    pub list_options: Option<ListOptions>,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsCount<F>
+where
+    F: DeserializeOwned,
+{
+    #[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+    pub filters: Option<Vec<F>>,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct ParamsAggregate<F>
+where
+    F: DeserializeOwned,
+{
+    #[serde_as(deserialize_as = "Option<OneOrMany<_>>")]
+    pub filters: Option<Vec<F>>,
+    /// e.g., `["done"]` or `["deleted_at:day"]` (none for a single total row).
+    #[serde(default)]
+    pub group_bys: Vec<GroupBy>,
+    /// e.g., `["count", "max:version"]`.
+    pub aggregates: Vec<Aggregate>,
+}
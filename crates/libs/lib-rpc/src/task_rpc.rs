@@ -1,8 +1,68 @@
-use crate::params::{ParamsForCreate, ParamsForUpdate, ParamsIded, ParamsList};
+use crate::params::{
+    ParamsAggregate, ParamsCount, ParamsForCreate, ParamsForUpdate, ParamsIded, ParamsList,
+    ParamsListPage, ParamsSearch,
+};
 use crate::Result;
 use lib_core::ctx::Ctx;
-use lib_core::model::task::{Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate};
-use lib_core::model::ModelManager;
+use lib_core::model::label::{Label, LabelBmc};
+use lib_core::model::recurrence::Recurrence;
+use lib_core::model::task::{
+    iso_date, Task, TaskBmc, TaskFilter, TaskForCreate, TaskForUpdate, TaskNode,
+};
+use lib_core::model::task_dependency::TaskDependencyBmc;
+use lib_core::model::{Aggregation, ListPage, ModelManager, SearchHit};
+use lib_utils::time::today_utc;
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+/// The default `count` of `preview_recurrence`.
+const PREVIEW_COUNT_DEFAULT: usize = 5;
+
+#[derive(Deserialize)]
+pub struct ParamsListTasks {
+    #[serde(flatten)]
+    pub list: ParamsListPage<TaskFilter>,
+    /// Embed the labels of each task (one additional query for the page).
+    #[serde(default)]
+    pub include_labels: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsSubtask {
+    pub parent_id: i64,
+    pub task_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsTaskDependency {
+    pub task_id: i64,
+    pub depends_on_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsTaskRecurrence {
+    pub id: i64,
+    /// e.g., `"FREQ=WEEKLY;BYDAY=MO"`, or `null` to stop the recurrence.
+    pub recurrence: Option<Recurrence>,
+}
+
+#[derive(Deserialize)]
+pub struct ParamsPreviewRecurrence {
+    pub recurrence: Recurrence,
+    /// The occurrences after this date (`YYYY-MM-DD`, default today).
+    #[serde(default, with = "iso_date::option")]
+    pub after: Option<Date>,
+    /// Default `PREVIEW_COUNT_DEFAULT` (at most `recurrence::OCCURRENCES_MAX`).
+    pub count: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct TaskWithLabels {
+    #[serde(flatten)]
+    pub task: Task,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<Label>>,
+}
 
 pub async fn create_task(
     ctx: Ctx,
@@ -20,11 +80,97 @@ pub async fn create_task(
 pub async fn list_tasks(
     ctx: Ctx,
     mm: ModelManager,
-    params: ParamsList<TaskFilter>,
-) -> Result<Vec<Task>> {
-    let tasks = TaskBmc::list(&ctx, &mm, params.filters, params.list_options).await?;
+    params: ParamsListTasks,
+) -> Result<ListPage<TaskWithLabels>> {
+    let ParamsListTasks {
+        list:
+            ParamsListPage {
+                filters,
+                list_options,
+                cursor,
+                include_total,
+            },
+        include_labels,
+    } = params;
 
-    Ok(tasks)
+    let page = TaskBmc::list_page(
+        &ctx,
+        &mm,
+        filters,
+        list_options,
+        cursor.as_deref(),
+        include_total,
+    )
+    .await?;
+
+    // -- Embed the labels (when requested, with one query for the page)
+    let mut labels_by_task_id = if include_labels {
+        let task_ids: Vec<i64> = page.items.iter().map(|task| task.id).collect();
+        Some(LabelBmc::list_by_task_ids(&ctx, &mm, &task_ids).await?)
+    } else {
+        None
+    };
+    let items = page
+        .items
+        .into_iter()
+        .map(|task| {
+            let labels = labels_by_task_id
+                .as_mut()
+                .map(|by_id| by_id.remove(&task.id).unwrap_or_default());
+            TaskWithLabels { task, labels }
+        })
+        .collect();
+
+    Ok(ListPage {
+        items,
+        next_cursor: page.next_cursor,
+        total: page.total,
+    })
+}
+
+/// Full-text search of the tasks (ranked, with highlighted snippets).
+pub async fn search_tasks(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsSearch<TaskFilter>,
+) -> Result<Vec<SearchHit<Task>>> {
+    let ParamsSearch {
+        terms,
+        filters,
+        list_options,
+    } = params;
+
+    let hits = TaskBmc::search(&ctx, &mm, &terms, filters, list_options).await?;
+
+    Ok(hits)
+}
+
+pub async fn count_tasks(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsCount<TaskFilter>,
+) -> Result<i64> {
+    let count = TaskBmc::count(&ctx, &mm, params.filters).await?;
+
+    Ok(count)
+}
+
+/// The filtered tasks aggregates, by group (one JSON object per group,
+/// and `truncated` when over the groups limit).
+pub async fn aggregate_tasks(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsAggregate<TaskFilter>,
+) -> Result<Aggregation> {
+    let ParamsAggregate {
+        filters,
+        group_bys,
+        aggregates,
+    } = params;
+
+    let aggregation = TaskBmc::aggregate(&ctx, &mm, filters, &group_bys, &aggregates).await?;
+
+    Ok(aggregation)
 }
 
 pub async fn update_task(
@@ -32,9 +178,13 @@ pub async fn update_task(
     mm: ModelManager,
     params: ParamsForUpdate<TaskForUpdate>,
 ) -> Result<Task> {
-    let ParamsForUpdate { id, data } = params;
+    let ParamsForUpdate {
+        id,
+        data,
+        expected_version,
+    } = params;
 
-    TaskBmc::update(&ctx, &mm, id, data).await?;
+    TaskBmc::update_with_version(&ctx, &mm, id, data, expected_version).await?;
 
     let task = TaskBmc::get(&ctx, &mm, id).await?;
 
@@ -49,3 +199,148 @@ pub async fn delete_task(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Resu
 
     Ok(task)
 }
+
+pub async fn list_deleted_tasks(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsList<TaskFilter>,
+) -> Result<Vec<Task>> {
+    let tasks = TaskBmc::list_deleted(&ctx, &mm, params.filters, params.list_options).await?;
+
+    Ok(tasks)
+}
+
+pub async fn restore_task(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Task> {
+    let ParamsIded { id } = params;
+
+    TaskBmc::restore(&ctx, &mm, id).await?;
+    let task = TaskBmc::get(&ctx, &mm, id).await?;
+
+    Ok(task)
+}
+
+/// Permanently delete a (soft) deleted task (admin users only).
+pub async fn purge_task(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<()> {
+    let ParamsIded { id } = params;
+
+    TaskBmc::purge(&ctx, &mm, id).await?;
+
+    Ok(())
+}
+
+// region:    --- Subtasks & Dependencies
+
+/// Make the task a subtask of the parent (fails on a cycle).
+pub async fn link_subtask(ctx: Ctx, mm: ModelManager, params: ParamsSubtask) -> Result<Task> {
+    let ParamsSubtask { parent_id, task_id } = params;
+
+    TaskBmc::set_parent(&ctx, &mm, task_id, Some(parent_id)).await?;
+    let task = TaskBmc::get(&ctx, &mm, task_id).await?;
+
+    Ok(task)
+}
+
+/// Make the subtask a top task.
+pub async fn unlink_subtask(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<Task> {
+    let ParamsIded { id } = params;
+
+    TaskBmc::set_parent(&ctx, &mm, id, None).await?;
+    let task = TaskBmc::get(&ctx, &mm, id).await?;
+
+    Ok(task)
+}
+
+/// The task and all of its subtasks, with their depth.
+pub async fn list_task_subtree(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsIded,
+) -> Result<Vec<TaskNode>> {
+    let nodes = TaskBmc::list_subtree(&ctx, &mm, params.id).await?;
+
+    Ok(nodes)
+}
+
+/// Make the task depend on another task (fails on a cycle).
+pub async fn link_dependency(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsTaskDependency,
+) -> Result<()> {
+    let ParamsTaskDependency {
+        task_id,
+        depends_on_id,
+    } = params;
+
+    TaskDependencyBmc::link(&ctx, &mm, task_id, depends_on_id).await?;
+
+    Ok(())
+}
+
+pub async fn unlink_dependency(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsTaskDependency,
+) -> Result<()> {
+    let ParamsTaskDependency {
+        task_id,
+        depends_on_id,
+    } = params;
+
+    TaskDependencyBmc::unlink(&ctx, &mm, task_id, depends_on_id).await?;
+
+    Ok(())
+}
+
+/// All the tasks the task depends on (its dependency chain), with their depth.
+pub async fn list_task_dependencies(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsIded,
+) -> Result<Vec<TaskNode>> {
+    let nodes = TaskDependencyBmc::list_chain(&ctx, &mm, params.id).await?;
+
+    Ok(nodes)
+}
+
+// endregion: --- Subtasks & Dependencies
+
+// region:    --- Recurrence
+
+pub async fn set_task_recurrence(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsTaskRecurrence,
+) -> Result<Task> {
+    let ParamsTaskRecurrence { id, recurrence } = params;
+
+    TaskBmc::set_recurrence(&ctx, &mm, id, recurrence).await?;
+    let task = TaskBmc::get(&ctx, &mm, id).await?;
+
+    Ok(task)
+}
+
+/// The next occurrence dates (`YYYY-MM-DD`) of a recurrence rule.
+pub async fn preview_recurrence(
+    _ctx: Ctx,
+    _mm: ModelManager,
+    params: ParamsPreviewRecurrence,
+) -> Result<Vec<String>> {
+    let ParamsPreviewRecurrence {
+        recurrence,
+        after,
+        count,
+    } = params;
+
+    let after = after.unwrap_or_else(today_utc);
+    let count = count.unwrap_or(PREVIEW_COUNT_DEFAULT);
+    let dates = recurrence
+        .occurrences_after(after, count)
+        .iter()
+        .map(Date::to_string)
+        .collect();
+
+    Ok(dates)
+}
+
+// endregion: --- Recurrence
@@ -0,0 +1,25 @@
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::user::{User, UserBmc};
+use lib_core::model::ModelManager;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ParamsUserPwdReset {
+    pub id: i64,
+    pub pwd: String,
+}
+
+/// Reset the password of a user (admin users only), enforcing the password policy.
+pub async fn reset_user_pwd(
+    ctx: Ctx,
+    mm: ModelManager,
+    params: ParamsUserPwdReset,
+) -> Result<User> {
+    let ParamsUserPwdReset { id, pwd } = params;
+
+    UserBmc::reset_pwd(&ctx, &mm, id, &pwd).await?;
+    let user = UserBmc::get(&ctx, &mm, id).await?;
+
+    Ok(user)
+}
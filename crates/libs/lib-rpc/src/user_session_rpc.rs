@@ -0,0 +1,37 @@
+use crate::params::ParamsIded;
+use crate::Result;
+use lib_core::ctx::Ctx;
+use lib_core::model::user_session::{UserSession, UserSessionBmc};
+use lib_core::model::ModelManager;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct MySession {
+    #[serde(flatten)]
+    pub session: UserSession,
+    /// True for the session of this request.
+    pub current: bool,
+}
+
+pub async fn list_my_sessions(ctx: Ctx, mm: ModelManager) -> Result<Vec<MySession>> {
+    let sessions = UserSessionBmc::list_for_user(&ctx, &mm, ctx.user_id()).await?;
+
+    let sessions = sessions
+        .into_iter()
+        .map(|session| MySession {
+            current: Some(session.id) == ctx.session_id(),
+            session,
+        })
+        .collect();
+
+    Ok(sessions)
+}
+
+/// Revoke one of the user sessions (the next request with its token will fail).
+pub async fn revoke_session(ctx: Ctx, mm: ModelManager, params: ParamsIded) -> Result<UserSession> {
+    let ParamsIded { id } = params;
+
+    let session = UserSessionBmc::delete_for_user(&ctx, &mm, id, ctx.user_id()).await?;
+
+    Ok(session)
+}
@@ -1,7 +1,7 @@
 // region:         — Modules
 
 use time::format_description::well_known::Rfc3339;
-use time::{Duration, OffsetDateTime};
+use time::{Date, Duration, Month, OffsetDateTime, Weekday};
 
 // endregion:      — Modules
 
@@ -24,6 +24,68 @@ pub fn parse_utc(moment: &str) -> Result<OffsetDateTime> {
 }
 // endregion:      — Time
 
+// region:         — Date
+
+pub fn today_utc() -> Date {
+    now_utc().date()
+}
+
+/// The first day of the month `months` after the month of `date`
+/// (`None` when out of the supported dates).
+pub fn month_start_after(date: Date, months: u32) -> Option<Date> {
+    let month_index = date.year() as i64 * 12 + date.month() as i64 - 1 + months as i64;
+    let year = i32::try_from(month_index.div_euclid(12)).ok()?;
+    let month = Month::try_from(month_index.rem_euclid(12) as u8 + 1).ok()?;
+
+    Date::from_calendar_date(year, month, 1).ok()
+}
+
+/// The `day` of the month of `month_start`, clamped to the month length
+/// (e.g., the 31st is Feb 28 or 29).
+pub fn month_day_clamped(month_start: Date, day: u8) -> Date {
+    let (year, month) = (month_start.year(), month_start.month());
+    let day = day.clamp(1, month.length(year));
+
+    month_start.replace_day(day).unwrap_or(month_start)
+}
+
+/// The `nth` `weekday` of the month of `month_start` (from the month end when negative,
+/// e.g., -1 for the last), `None` when the month does not have it (e.g., a 5th Monday).
+pub fn nth_weekday_of_month(month_start: Date, weekday: Weekday, nth: i8) -> Option<Date> {
+    let (year, month) = (month_start.year(), month_start.month());
+    let days_in_month = month.length(year) as i64;
+
+    let day = if nth > 0 {
+        let first = (weekday.number_days_from_monday() as i64
+            - month_start.weekday().number_days_from_monday() as i64)
+            .rem_euclid(7)
+            + 1;
+        first + (nth as i64 - 1) * 7
+    } else if nth < 0 {
+        let month_end = month_start.replace_day(days_in_month as u8).ok()?;
+        let last = days_in_month
+            - (month_end.weekday().number_days_from_monday() as i64
+                - weekday.number_days_from_monday() as i64)
+                .rem_euclid(7);
+        last + (nth as i64 + 1) * 7
+    } else {
+        return None;
+    };
+
+    if (1..=days_in_month).contains(&day) {
+        month_start.replace_day(day as u8).ok()
+    } else {
+        None
+    }
+}
+
+/// The `date` plus the `days` (`None` when out of the supported dates).
+pub fn add_days(date: Date, days: i64) -> Option<Date> {
+    date.checked_add(Duration::days(days))
+}
+
+// endregion:      — Date
+
 // region:    --- Error
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -43,3 +105,156 @@ impl core::fmt::Display for Error {
 impl std::error::Error for Error {}
 // endregion:      --- Error Boilerplate
 // endregion: --- Error
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use time::macros::date;
+
+    #[test]
+    fn test_month_start_after_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_cases = [
+            (date!(2027 - 01 - 31), 0, date!(2027 - 01 - 01)),
+            (date!(2027 - 01 - 31), 1, date!(2027 - 02 - 01)),
+            (date!(2027 - 11 - 15), 2, date!(2028 - 01 - 01)),
+            (date!(2027 - 12 - 31), 25, date!(2030 - 01 - 01)),
+        ];
+
+        // -- Exec & Check
+        for (date, months, fx_start) in fx_cases {
+            assert_eq!(
+                month_start_after(date, months),
+                Some(fx_start),
+                "{date} +{months}"
+            );
+        }
+        assert_eq!(month_start_after(Date::MAX, 1), None, "out of the dates");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_month_day_clamped_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_cases = [
+            (date!(2027 - 01 - 01), 31, date!(2027 - 01 - 31)),
+            (date!(2027 - 02 - 01), 31, date!(2027 - 02 - 28)),
+            (date!(2028 - 02 - 01), 31, date!(2028 - 02 - 29)), // Leap year
+            (date!(2100 - 02 - 01), 29, date!(2100 - 02 - 28)), // Not a leap year (century)
+            (date!(2000 - 02 - 01), 30, date!(2000 - 02 - 29)), // Leap year (400)
+            (date!(2027 - 04 - 01), 31, date!(2027 - 04 - 30)),
+            (date!(2027 - 04 - 01), 0, date!(2027 - 04 - 01)),
+        ];
+
+        // -- Exec & Check
+        for (month_start, day, fx_date) in fx_cases {
+            assert_eq!(
+                month_day_clamped(month_start, day),
+                fx_date,
+                "{month_start} {day}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nth_weekday_of_month_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        // 2027-02-01 is a Monday, and 2028-02 has 29 days (5 Tuesdays).
+        let fx_cases = [
+            (
+                date!(2027 - 02 - 01),
+                Weekday::Monday,
+                1,
+                Some(date!(2027 - 02 - 01)),
+            ),
+            (
+                date!(2027 - 02 - 01),
+                Weekday::Sunday,
+                1,
+                Some(date!(2027 - 02 - 07)),
+            ),
+            (
+                date!(2027 - 02 - 01),
+                Weekday::Sunday,
+                -1,
+                Some(date!(2027 - 02 - 28)),
+            ),
+            (
+                date!(2027 - 02 - 01),
+                Weekday::Monday,
+                -1,
+                Some(date!(2027 - 02 - 22)),
+            ),
+            (
+                date!(2027 - 02 - 01),
+                Weekday::Monday,
+                4,
+                Some(date!(2027 - 02 - 22)),
+            ),
+            (date!(2027 - 02 - 01), Weekday::Monday, 5, None),
+            (
+                date!(2028 - 02 - 01),
+                Weekday::Tuesday,
+                5,
+                Some(date!(2028 - 02 - 29)),
+            ),
+            (
+                date!(2028 - 02 - 01),
+                Weekday::Tuesday,
+                -5,
+                Some(date!(2028 - 02 - 01)),
+            ),
+            (date!(2028 - 02 - 01), Weekday::Wednesday, -5, None),
+            (
+                date!(2027 - 03 - 01),
+                Weekday::Wednesday,
+                5,
+                Some(date!(2027 - 03 - 31)),
+            ),
+            (date!(2027 - 03 - 01), Weekday::Monday, 0, None),
+        ];
+
+        // -- Exec & Check
+        for (month_start, weekday, nth, fx_date) in fx_cases {
+            assert_eq!(
+                nth_weekday_of_month(month_start, weekday, nth),
+                fx_date,
+                "{month_start} {weekday} {nth}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_days_ok() -> Result<()> {
+        // -- Exec & Check
+        assert_eq!(
+            add_days(date!(2027 - 02 - 28), 1),
+            Some(date!(2027 - 03 - 01))
+        );
+        assert_eq!(
+            add_days(date!(2028 - 02 - 28), 1),
+            Some(date!(2028 - 02 - 29))
+        );
+        assert_eq!(
+            add_days(date!(2027 - 12 - 31), 1),
+            Some(date!(2028 - 01 - 01))
+        );
+        assert_eq!(
+            add_days(date!(2028 - 03 - 01), -1),
+            Some(date!(2028 - 02 - 29))
+        );
+        assert_eq!(add_days(Date::MAX, 1), None, "out of the dates");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
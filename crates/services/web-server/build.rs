@@ -0,0 +1,19 @@
+//! Build info for the `/version` route (`BUILD_GIT_SHA`, when built from a git checkout).
+
+use std::process::Command;
+
+fn main() {
+    let git_sha = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(git_sha) = git_sha {
+        println!("cargo:rustc-env=BUILD_GIT_SHA={}", git_sha.trim());
+    }
+
+    println!("cargo:rerun-if-changed=../../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../../.git/refs");
+}
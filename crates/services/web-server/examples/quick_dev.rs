@@ -1,4 +1,5 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, COOKIE};
 use serde_json::json;
 
 #[tokio::main]
@@ -17,6 +18,22 @@ async fn main() -> Result<()> {
     );
     req_login.await?.print().await?;
 
+    // -- Rpc client sending the CSRF token header
+    // (with the auth token cookie, since the httpc-test cookie store cannot be shared)
+    let csrf_token = hc
+        .cookie_value("csrf-token")
+        .context("no csrf-token cookie")?;
+    let auth_token = hc
+        .cookie_value("auth-token")
+        .context("no auth-token cookie")?;
+    let mut headers = HeaderMap::new();
+    headers.insert("x-csrf-token", csrf_token.parse()?);
+    headers.insert(COOKIE, format!("auth-token={auth_token}").parse()?);
+    let hc = httpc_test::new_client_with_reqwest(
+        "http://localhost:8080",
+        reqwest::Client::builder().default_headers(headers),
+    )?;
+
     // -- Create Tasks
     let mut task_ids: Vec<i64> = Vec::new();
     for i in 0..=4 {
@@ -45,7 +62,10 @@ async fn main() -> Result<()> {
             "params": {
                 "id": task_ids[0],
                 "data": {
-                    "title": "task BB"
+                    "title": "task BB",
+                    "status": "in_progress",
+                    "priority": "high",
+                    "due_date": "2026-12-31"
                 }
             }
         }),
@@ -65,6 +85,153 @@ async fn main() -> Result<()> {
     );
     req_delete_task.await?.print().await?;
 
+    // -- Create a label, and attach it to the 3rd task
+    let req_create_label = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "create_label",
+            "params": {
+                "data": {
+                    "name": "label AAA",
+                    "color": "blue"
+                }
+            }
+        }),
+    );
+    let label_id = req_create_label.await?.json_value::<i64>("/result/id")?;
+    let req_attach_label = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "attach_label",
+            "params": {
+                "task_id": task_ids[2],
+                "label_id": label_id
+            }
+        }),
+    );
+    req_attach_label.await?.print().await?;
+
+    // -- Make the 4th task a subtask of the 3rd, which depends on the 1st
+    let req_link_subtask = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "link_subtask",
+            "params": {
+                "parent_id": task_ids[2],
+                "task_id": task_ids[3]
+            }
+        }),
+    );
+    req_link_subtask.await?;
+    let req_link_dependency = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "link_dependency",
+            "params": {
+                "task_id": task_ids[2],
+                "depends_on_id": task_ids[0]
+            }
+        }),
+    );
+    req_link_dependency.await?;
+    let req_list_task_subtree = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "list_task_subtree",
+            "params": {
+                "id": task_ids[2]
+            }
+        }),
+    );
+    req_list_task_subtree.await?.print().await?;
+
+    // -- Make the 5th task recur weekly, and complete it (creates the next occurrence)
+    let req_preview_recurrence = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "preview_recurrence",
+            "params": {
+                "recurrence": "FREQ=WEEKLY;BYDAY=MO,TH",
+                "count": 4
+            }
+        }),
+    );
+    req_preview_recurrence.await?.print().await?;
+    let req_set_task_recurrence = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "set_task_recurrence",
+            "params": {
+                "id": task_ids[4],
+                "recurrence": "FREQ=WEEKLY;BYDAY=MO,TH"
+            }
+        }),
+    );
+    req_set_task_recurrence.await?;
+    let req_complete_task = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "update_task",
+            "params": {
+                "id": task_ids[4],
+                "data": {
+                    "status": "done"
+                }
+            }
+        }),
+    );
+    req_complete_task.await?.print().await?;
+
+    // -- Comment the 1st task, and reply to the comment
+    let req_add_comment = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "add_comment",
+            "params": {
+                "data": {
+                    "task_id": task_ids[0],
+                    "body": "comment AAA"
+                }
+            }
+        }),
+    );
+    let comment_id = req_add_comment.await?.json_value::<i64>("/result/id")?;
+    let req_add_reply = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "add_comment",
+            "params": {
+                "data": {
+                    "task_id": task_ids[0],
+                    "parent_id": comment_id,
+                    "body": "reply AAA"
+                }
+            }
+        }),
+    );
+    req_add_reply.await?;
+    let req_list_comments = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "list_comments",
+            "params": {
+                "task_id": task_ids[0]
+            }
+        }),
+    );
+    req_list_comments.await?.print().await?;
+
     // -- List Tasks with filters
     let req_list_tasks = hc.do_post(
         "/api/rpc",
@@ -79,19 +246,61 @@ async fn main() -> Result<()> {
                 }],
                 "list_options": {
                     "order_bys": "!id"
-                }
+                },
+                "include_labels": true
             }
         }),
     );
     req_list_tasks.await?.print().await?;
 
+    // -- Search Tasks (full-text)
+    let req_search_tasks = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "search_tasks",
+            "params": {
+                "terms": "task"
+            }
+        }),
+    );
+    req_search_tasks.await?.print().await?;
+
+    // -- Aggregate Tasks (count by done)
+    let req_aggregate_tasks = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "aggregate_tasks",
+            "params": {
+                "group_bys": ["done"],
+                "aggregates": ["count", "max:version"]
+            }
+        }),
+    );
+    req_aggregate_tasks.await?.print().await?;
+
+    // -- List the audit entries of the 2nd task (demo1 is admin)
+    let req_list_audit_entries = hc.do_post(
+        "/api/rpc",
+        json!({
+            "id": 1,
+            "method": "list_audit_entries",
+            "params": {
+                "entity": "task",
+                "entity_id": task_ids[1]
+            }
+        }),
+    );
+    req_list_audit_entries.await?.print().await?;
+
     let req_logoff = hc.do_post(
         "/api/logoff",
         json!({
             "logoff": true
         }),
     );
-    // req_logoff.await?.print().await?;
+    req_logoff.await?.print().await?;
 
     Ok(())
 }
@@ -1,5 +1,10 @@
-use lib_utils::envs::get_env;
+use crate::log::RequestLogSinkKind;
+use crate::web::mw_rate_limit::RpcCosts;
+use lib_utils::envs::{get_env, get_env_parse, Error};
+use std::net::IpAddr;
+use std::str::FromStr;
 use std::sync::OnceLock;
+use tower_cookies::cookie::SameSite;
 
 pub fn web_config() -> &'static WebConfig {
     static INSTANCE: OnceLock<WebConfig> = OnceLock::new();
@@ -12,13 +17,243 @@ pub fn web_config() -> &'static WebConfig {
 
 #[allow(non_snake_case)]
 pub struct WebConfig {
+    // -- Server
+    pub WEB_HOST: IpAddr,
+    pub WEB_PORT: u16,
+    pub TLS_FILES: Option<TlsFiles>, // https when set (hot reloaded on file change).
+    pub SHUTDOWN_TIMEOUT_SEC: u64,   // Max time to drain the in-flight requests.
+
     pub WEB_FOLDER: String,
+
+    // -- Tracing
+    pub OTLP_ENDPOINT: Option<String>, // OTLP/HTTP traces endpoint, export when set.
+
+    // -- Request Log
+    pub REQUEST_LOG_SINKS: Vec<RequestLogSinkKind>, // "stdout", "file", "db" (can be empty).
+    pub REQUEST_LOG_DIR: String,                    // For the "file" sink.
+    pub REQUEST_LOG_FILE_MAX_BYTES: u64,            // Rotated over it (at least 1).
+    pub REQUEST_LOG_FILE_MAX_FILES: usize,          // Kept files, with the active one (at least 1).
+    pub REQUEST_LOG_CHANNEL_CAPACITY: usize,        // Lines over it are dropped (at least 1).
+
+    // -- Cookies
+    pub COOKIE_SAME_SITE: SameSite,
+    pub COOKIE_SECURE: bool, // Should be true when served over https.
+
+    // -- Rate Limit (token buckets per user, and per IP before the auth)
+    pub RATE_LIMIT_CAPACITY: f64,       // At least 1.
+    pub RATE_LIMIT_REFILL_PER_SEC: f64, // More than 0.
+    pub RATE_LIMIT_RPC_COSTS: RpcCosts, // Costs up to the capacity.
+    pub RATE_LIMIT_IP_CAPACITY: f64,
+    pub RATE_LIMIT_IP_REFILL_PER_SEC: f64,
+
+    // -- Soft Delete Purge
+    pub SOFT_DELETE_RETENTION_DAYS: i64, // Deleted tasks older than it are purged.
+    pub SOFT_DELETE_PURGE_INTERVAL_SEC: u64, // At least 1.
 }
 
 impl WebConfig {
     fn load_from_env() -> lib_utils::envs::Result<WebConfig> {
+        let rate_limit_capacity =
+            get_env_parse_valid("SERVICE_RATE_LIMIT_CAPACITY", is_rate_limit_capacity)?;
+
         Ok(WebConfig {
+            // -- Server
+            WEB_HOST: get_env_parse("SERVICE_WEB_HOST")?,
+            WEB_PORT: get_env_parse("SERVICE_WEB_PORT")?,
+            TLS_FILES: load_tls_files()?,
+            SHUTDOWN_TIMEOUT_SEC: get_env_parse("SERVICE_SHUTDOWN_TIMEOUT_SEC")?,
+
             WEB_FOLDER: get_env("SERVICE_WEB_FOLDER")?,
+
+            // -- Tracing
+            OTLP_ENDPOINT: get_env("SERVICE_OTLP_ENDPOINT").ok(),
+
+            // -- Request Log
+            REQUEST_LOG_SINKS: parse_request_log_sinks("SERVICE_REQUEST_LOG_SINKS")?,
+            REQUEST_LOG_DIR: get_env("SERVICE_REQUEST_LOG_DIR")?,
+            REQUEST_LOG_FILE_MAX_BYTES: get_env_parse_valid(
+                "SERVICE_REQUEST_LOG_FILE_MAX_BYTES",
+                |max_bytes: &u64| *max_bytes > 0,
+            )?,
+            REQUEST_LOG_FILE_MAX_FILES: get_env_parse_valid(
+                "SERVICE_REQUEST_LOG_FILE_MAX_FILES",
+                |max_files: &usize| *max_files > 0,
+            )?,
+            REQUEST_LOG_CHANNEL_CAPACITY: get_env_parse_valid(
+                "SERVICE_REQUEST_LOG_CHANNEL_CAPACITY",
+                |capacity: &usize| *capacity > 0,
+            )?,
+
+            // -- Cookies
+            COOKIE_SAME_SITE: parse_same_site("SERVICE_COOKIE_SAME_SITE")?,
+            COOKIE_SECURE: get_env_parse("SERVICE_COOKIE_SECURE")?,
+
+            // -- Rate Limit
+            RATE_LIMIT_CAPACITY: rate_limit_capacity,
+            RATE_LIMIT_REFILL_PER_SEC: get_env_parse_valid(
+                "SERVICE_RATE_LIMIT_REFILL_PER_SEC",
+                is_rate_limit_refill,
+            )?,
+            RATE_LIMIT_RPC_COSTS: get_env_parse_valid(
+                "SERVICE_RATE_LIMIT_RPC_COSTS",
+                |costs: &RpcCosts| costs.max_cost() <= rate_limit_capacity,
+            )?,
+            RATE_LIMIT_IP_CAPACITY: get_env_parse_valid(
+                "SERVICE_RATE_LIMIT_IP_CAPACITY",
+                is_rate_limit_capacity,
+            )?,
+            RATE_LIMIT_IP_REFILL_PER_SEC: get_env_parse_valid(
+                "SERVICE_RATE_LIMIT_IP_REFILL_PER_SEC",
+                is_rate_limit_refill,
+            )?,
+
+            // -- Soft Delete Purge
+            SOFT_DELETE_RETENTION_DAYS: get_env_parse("SERVICE_SOFT_DELETE_RETENTION_DAYS")?,
+            SOFT_DELETE_PURGE_INTERVAL_SEC: get_env_parse_valid(
+                "SERVICE_SOFT_DELETE_PURGE_INTERVAL_SEC",
+                |interval_sec: &u64| *interval_sec > 0,
+            )?,
         })
     }
 }
+
+/// `get_env_parse`, but `Error::WrongFormat` when the value is not `valid` (e.g., out of range).
+fn get_env_parse_valid<T: FromStr>(
+    name: &'static str,
+    valid: impl Fn(&T) -> bool,
+) -> lib_utils::envs::Result<T> {
+    let value = get_env_parse(name)?;
+    if valid(&value) {
+        Ok(value)
+    } else {
+        Err(Error::WrongFormat(name))
+    }
+}
+
+fn is_rate_limit_capacity(capacity: &f64) -> bool {
+    capacity.is_finite() && *capacity >= 1.
+}
+
+fn is_rate_limit_refill(refill_per_sec: &f64) -> bool {
+    refill_per_sec.is_finite() && *refill_per_sec > 0.
+}
+
+/// PEM cert (chain) and private key files.
+pub struct TlsFiles {
+    pub cert: String,
+    pub key: String,
+}
+
+/// Both `SERVICE_TLS_CERT_FILE` and `SERVICE_TLS_KEY_FILE`, or none (plain http).
+fn load_tls_files() -> lib_utils::envs::Result<Option<TlsFiles>> {
+    tls_files(
+        get_env("SERVICE_TLS_CERT_FILE"),
+        get_env("SERVICE_TLS_KEY_FILE"),
+    )
+}
+
+fn tls_files(
+    cert: lib_utils::envs::Result<String>,
+    key: lib_utils::envs::Result<String>,
+) -> lib_utils::envs::Result<Option<TlsFiles>> {
+    match (cert, key) {
+        (Ok(cert), Ok(key)) => Ok(Some(TlsFiles { cert, key })),
+        (Err(_), Err(_)) => Ok(None),
+        (Ok(_), Err(ex)) | (Err(ex), Ok(_)) => Err(ex),
+    }
+}
+
+/// Comma separated sink kinds (e.g., "stdout,db").
+fn parse_request_log_sinks(name: &'static str) -> lib_utils::envs::Result<Vec<RequestLogSinkKind>> {
+    get_env(name)?
+        .split(',')
+        .filter(|kind| !kind.trim().is_empty())
+        .map(|kind| kind.parse().map_err(|_| Error::WrongFormat(name)))
+        .collect()
+}
+
+fn parse_same_site(name: &'static str) -> lib_utils::envs::Result<SameSite> {
+    match get_env(name)?.as_str() {
+        "Strict" => Ok(SameSite::Strict),
+        "Lax" => Ok(SameSite::Lax),
+        "None" => Ok(SameSite::None),
+        _ => Err(Error::WrongFormat(name)),
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::env;
+
+    #[test]
+    fn test_tls_files_both_or_none() -> Result<()> {
+        // -- Exec
+        let both = tls_files(Ok("cert.pem".into()), Ok("key.pem".into()))?;
+        let none = tls_files(Err(Error::MissingEnv("C")), Err(Error::MissingEnv("K")))?;
+        let key_only = tls_files(Err(Error::MissingEnv("C")), Ok("key.pem".into()));
+
+        // -- Check
+        let both = both.expect("Should have the tls files");
+        assert_eq!(
+            (both.cert.as_str(), both.key.as_str()),
+            ("cert.pem", "key.pem")
+        );
+        assert!(none.is_none());
+        assert!(matches!(key_only, Err(Error::MissingEnv("C"))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bind_addr_parse() -> Result<()> {
+        // -- Setup & Fixtures
+        env::set_var("SERVICE_TEST_BIND_HOST_V6", "::1");
+        env::set_var("SERVICE_TEST_BIND_HOST_BAD", "localhost");
+        env::set_var("SERVICE_TEST_BIND_PORT_BAD", "70000");
+
+        // -- Exec & Check
+        let host: IpAddr = get_env_parse("SERVICE_TEST_BIND_HOST_V6")?;
+        assert!(host.is_loopback() && host.is_ipv6());
+        assert!(get_env_parse::<IpAddr>("SERVICE_TEST_BIND_HOST_BAD").is_err());
+        assert!(get_env_parse::<u16>("SERVICE_TEST_BIND_PORT_BAD").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_env_parse_valid() -> Result<()> {
+        // -- Setup & Fixtures
+        env::set_var("SERVICE_TEST_RATE_CAPACITY_OK", "10");
+        env::set_var("SERVICE_TEST_RATE_CAPACITY_LOW", "0.5");
+        env::set_var("SERVICE_TEST_RATE_CAPACITY_INF", "inf");
+        env::set_var("SERVICE_TEST_RATE_REFILL_ZERO", "0");
+        env::set_var("SERVICE_TEST_RATE_REFILL_NAN", "NaN");
+
+        // -- Exec & Check
+        let capacity =
+            get_env_parse_valid("SERVICE_TEST_RATE_CAPACITY_OK", is_rate_limit_capacity)?;
+        assert_eq!(capacity, 10.);
+        for name in [
+            "SERVICE_TEST_RATE_CAPACITY_LOW",
+            "SERVICE_TEST_RATE_CAPACITY_INF",
+        ] {
+            let res = get_env_parse_valid(name, is_rate_limit_capacity);
+            assert!(matches!(res, Err(Error::WrongFormat(_))), "{name}");
+        }
+        for name in [
+            "SERVICE_TEST_RATE_REFILL_ZERO",
+            "SERVICE_TEST_RATE_REFILL_NAN",
+        ] {
+            let res = get_env_parse_valid(name, is_rate_limit_refill);
+            assert!(matches!(res, Err(Error::WrongFormat(_))), "{name}");
+        }
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
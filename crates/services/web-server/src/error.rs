@@ -1,13 +1,32 @@
 use derive_more::From;
+use lib_auth::oidc;
 use lib_core::model;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
 #[derive(Debug, From)]
 pub enum Error {
+    // -- Server
+    TlsConfig(String),
+    Serve(String),
+    MetricsRecorder(String),
+    OtlpExporter(String),
+
+    // -- Request Log
+    RequestLogChannelFull,
+    RequestLogChannelClosed,
+
     // -- Modules
     #[from]
     Model(model::Error),
+    #[from]
+    Oidc(oidc::Error),
+
+    // -- Externals
+    #[from]
+    Io(std::io::Error),
+    #[from]
+    SerdeJson(serde_json::Error),
 }
 
 // region:    --- Error Boilerplate
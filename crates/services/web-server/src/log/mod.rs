@@ -1,31 +1,130 @@
+// region:    --- Modules
+
+mod sink;
+
+pub use sink::{DbSink, FileSink, RequestLogSink, RequestLogSinkKind, StdoutSink};
+
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::Duration;
 
-use axum::http::{Method, Uri};
+use axum::http::{Method, StatusCode, Uri};
+use lib_utils::time::now_utc;
 use serde::Serialize;
-use serde_json::{json, Value};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
-use tracing::debug;
+use time::OffsetDateTime;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::config::web_config;
+use crate::web::mw_req_stamp::ReqStamp;
 use crate::web::routes_rpc::RpcInfo;
 use crate::web::{self, ClientError};
-use crate::Result;
+use crate::{Error, Result};
 use lib_core::ctx::Ctx;
+use lib_core::model::ModelManager;
+
+// endregion: --- Modules
+
+// region:    --- Request Logger
+
+/// Sends the request log lines to the sinks, through a bounded channel, so that
+/// logging never blocks the request (lines are dropped when the channel is full).
+#[derive(Clone)]
+pub struct RequestLogger {
+    tx: mpsc::Sender<RequestLogLine>,
+}
+
+impl RequestLogger {
+    pub fn from_config(mm: &ModelManager) -> (Self, RequestLogWriter) {
+        let config = web_config();
+
+        let sinks = config
+            .REQUEST_LOG_SINKS
+            .iter()
+            .map(|kind| -> Box<dyn RequestLogSink> {
+                match kind {
+                    RequestLogSinkKind::Stdout => Box::new(StdoutSink),
+                    RequestLogSinkKind::File => Box::new(FileSink::new(
+                        &config.REQUEST_LOG_DIR,
+                        config.REQUEST_LOG_FILE_MAX_BYTES,
+                        config.REQUEST_LOG_FILE_MAX_FILES,
+                    )),
+                    RequestLogSinkKind::Db => Box::new(DbSink::new(mm.clone())),
+                }
+            })
+            .collect();
+
+        Self::new(sinks, config.REQUEST_LOG_CHANNEL_CAPACITY)
+    }
+
+    /// Spawn the sinks writer task (lines are written to each sink, in order).
+    ///
+    /// Note: `capacity` (at least 1) is validated by the `WebConfig` load.
+    pub fn new(
+        mut sinks: Vec<Box<dyn RequestLogSink>>,
+        capacity: usize,
+    ) -> (Self, RequestLogWriter) {
+        let (tx, mut rx) = mpsc::channel::<RequestLogLine>(capacity);
+
+        let writer = tokio::spawn(async move {
+            while let Some(log_line) = rx.recv().await {
+                for sink in sinks.iter_mut() {
+                    if let Err(ex) = sink.write(&log_line).await {
+                        error!("REQUEST LOG SINK - write fail - {ex:?}");
+                    }
+                }
+            }
+        });
+
+        (RequestLogger { tx }, RequestLogWriter(writer))
+    }
+
+    fn send(&self, log_line: RequestLogLine) -> Result<()> {
+        self.tx.try_send(log_line).map_err(|ex| match ex {
+            TrySendError::Full(_) => Error::RequestLogChannelFull,
+            TrySendError::Closed(_) => Error::RequestLogChannelClosed,
+        })
+    }
+}
+
+/// The sinks writer task, to drain at shutdown.
+pub struct RequestLogWriter(JoinHandle<()>);
+
+impl RequestLogWriter {
+    /// Wait until the lines still in the channel are written.
+    ///
+    /// Note: The channel closes when all the `RequestLogger` are dropped (i.e., the server
+    ///       routes, after `server::serve`), so it must be called after it, and before the
+    ///       `ModelManager` close (`db` sink).
+    pub async fn drain(self, timeout: Duration) {
+        match tokio::time::timeout(timeout, self.0).await {
+            Ok(Ok(())) => info!("REQUEST LOG drained"),
+            Ok(Err(ex)) => error!("REQUEST LOG writer failed - {ex}"),
+            Err(_) => warn!("REQUEST LOG drain timed out after {timeout:?}"),
+        }
+    }
+}
 
-pub async fn log_request(
-    uuid: Uuid,         // uuid string formatted
-    req_method: Method, // (should be iso8601)
+// endregion: --- Request Logger
+
+#[allow(clippy::too_many_arguments)]
+pub fn log_request(
+    logger: &RequestLogger,
+    req_stamp: ReqStamp,
+    req_method: Method,
     uri: Uri,
+    http_status: StatusCode,
+    client_ip: Option<String>,
     rpc_info: Option<&RpcInfo>,
     ctx: Option<Ctx>,
     web_error: Option<&Arc<web::Error>>,
     client_error: Option<ClientError>,
 ) -> Result<()> {
-    let timestamp = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_millis();
+    let ReqStamp { uuid, time_in } = req_stamp;
+    let duration = now_utc() - time_in;
 
     let error_type = web_error.map(|se| se.as_ref().to_string());
     let error_data = serde_json::to_value(web_error)
@@ -34,15 +133,18 @@ pub async fn log_request(
 
     // Create the RequestLogLine
     let log_line = RequestLogLine {
-        uuid: uuid.to_string(),           // uuid string formatted
-        timestamp: timestamp.to_string(), // (should be iso8601)
+        uuid,
+        timestamp: time_in,
+        duration_ms: duration.as_seconds_f64() * 1000.,
 
         // -- User and context attributes
         user_id: ctx.map(|c| c.user_id()),
+        client_ip,
 
         // -- http request attributes.
         http_path: uri.to_string(),
         http_method: req_method.to_string(),
+        http_status: http_status.as_u16(),
 
         // -- RPC information
         rpc_id: rpc_info.and_then(|rpc| rpc.id.as_ref().map(|id| id.to_string())),
@@ -54,33 +156,134 @@ pub async fn log_request(
         error_data,
     };
 
-    debug!("REQUEST LOG LINE: \n{}", json!(log_line));
-
-    // TODO -- Send to CloudWatch service
-
-    Ok(())
+    logger.send(log_line).inspect_err(|ex| {
+        warn!("REQUEST LOG LINE dropped - {ex:?}");
+    })
 }
 
 #[skip_serializing_none]
-#[derive(Serialize)]
-struct RequestLogLine {
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestLogLine {
     // -- General attributes
-    uuid: String,      // uuid string formatted
-    timestamp: String, // (should be iso8601 formatted)
+    pub uuid: Uuid,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime, // Request start.
+    pub duration_ms: f64,
 
     // -- User and context attributes
-    user_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub client_ip: Option<String>,
 
     // -- Http request attributes
-    http_path: String,
-    http_method: String,
+    pub http_path: String,
+    pub http_method: String,
+    pub http_status: u16,
 
     // -- rpc info
-    rpc_id: Option<String>,
-    rpc_method: Option<String>,
+    pub rpc_id: Option<String>,
+    pub rpc_method: Option<String>,
 
     // -- Error attributes
-    client_error_type: Option<String>,
-    error_type: Option<String>,
-    error_data: Option<Value>,
+    pub client_error_type: Option<String>,
+    pub error_type: Option<String>,
+    pub error_data: Option<Value>,
 }
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use tokio::sync::mpsc::UnboundedSender;
+
+    struct ChannelSink(UnboundedSender<RequestLogLine>);
+
+    #[async_trait]
+    impl RequestLogSink for ChannelSink {
+        async fn write(&mut self, log_line: &RequestLogLine) -> crate::Result<()> {
+            let _ = self.0.send(log_line.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_request_to_sink_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (logger, _writer) = RequestLogger::new(vec![Box::new(ChannelSink(tx))], 8);
+        let fx_stamp = ReqStamp {
+            uuid: Uuid::new_v4(),
+            time_in: now_utc(),
+        };
+
+        // -- Exec
+        log_request(
+            &logger,
+            fx_stamp,
+            Method::POST,
+            "/api/rpc".parse()?,
+            StatusCode::OK,
+            Some("127.0.0.1".to_string()),
+            None,
+            None,
+            None,
+            None,
+        )?;
+
+        // -- Check
+        let log_line = rx.recv().await.expect("Should have a log line");
+        assert_eq!(log_line.uuid, fx_stamp.uuid);
+        assert_eq!(log_line.http_status, 200);
+        assert_eq!(log_line.client_ip.as_deref(), Some("127.0.0.1"));
+        let json = serde_json::to_value(&log_line)?;
+        let timestamp = json["timestamp"].as_str().unwrap_or_default();
+        assert!(
+            lib_utils::time::parse_utc(timestamp).is_ok(),
+            "timestamp should be rfc3339, but was '{timestamp}'"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_writer_drain_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (logger, writer) = RequestLogger::new(vec![Box::new(ChannelSink(tx))], 8);
+        let fx_stamp = ReqStamp {
+            uuid: Uuid::new_v4(),
+            time_in: now_utc(),
+        };
+        for _ in 0..3 {
+            log_request(
+                &logger,
+                fx_stamp,
+                Method::GET,
+                "/".parse()?,
+                StatusCode::OK,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )?;
+        }
+
+        // -- Exec
+        drop(logger);
+        writer.drain(Duration::from_secs(5)).await;
+
+        // -- Check
+        let mut count = 0;
+        while rx.try_recv().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 3, "All the pending lines should be written");
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
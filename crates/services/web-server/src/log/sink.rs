@@ -0,0 +1,266 @@
+//! Request log sinks (see `WebConfig` `REQUEST_LOG_SINKS`).
+//!
+//! - `stdout` JSON lines on the standard output.
+//! - `file` JSON lines in `REQUEST_LOG_DIR/request-log.jsonl`, rotated by size
+//!   (`REQUEST_LOG_FILE_MAX_BYTES`) to `request-log.1.jsonl`, `request-log.2.jsonl`, ...
+//!   keeping `REQUEST_LOG_FILE_MAX_FILES` files (the oldest are deleted).
+//! - `db` rows in the `request_log` table.
+
+use crate::log::RequestLogLine;
+use crate::Result;
+
+use async_trait::async_trait;
+use lib_core::ctx::Ctx;
+use lib_core::model::request_log::{RequestLogBmc, RequestLogForCreate};
+use lib_core::model::ModelManager;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+#[async_trait]
+pub trait RequestLogSink: Send {
+    async fn write(&mut self, log_line: &RequestLogLine) -> Result<()>;
+}
+
+// region:    --- Sink Kind
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestLogSinkKind {
+    Stdout,
+    File,
+    Db,
+}
+
+impl FromStr for RequestLogSinkKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> core::result::Result<Self, Self::Err> {
+        match kind.trim() {
+            "stdout" => Ok(Self::Stdout),
+            "file" => Ok(Self::File),
+            "db" => Ok(Self::Db),
+            other => Err(format!("request log sink '{other}' not supported")),
+        }
+    }
+}
+
+// endregion: --- Sink Kind
+
+// region:    --- Stdout
+
+pub struct StdoutSink;
+
+#[async_trait]
+impl RequestLogSink for StdoutSink {
+    async fn write(&mut self, log_line: &RequestLogLine) -> Result<()> {
+        let mut line = serde_json::to_string(log_line)?;
+        line.push('\n');
+
+        let mut stdout = tokio::io::stdout();
+        stdout.write_all(line.as_bytes()).await?;
+        stdout.flush().await?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Stdout
+
+// region:    --- File
+
+pub struct FileSink {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    /// The active file, and its size.
+    current: Option<(File, u64)>,
+}
+
+impl FileSink {
+    /// Note: `max_bytes` and `max_files` (at least 1) are validated by the `WebConfig` load.
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: u64, max_files: usize) -> Self {
+        FileSink {
+            dir: dir.into(),
+            max_bytes,
+            max_files,
+            current: None,
+        }
+    }
+
+    /// `request-log.jsonl` for the active file (index 0), `request-log.{index}.jsonl` for the rotated ones.
+    fn path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join("request-log.jsonl"),
+            index => self.dir.join(format!("request-log.{index}.jsonl")),
+        }
+    }
+
+    /// The active file, rotated first when `line_len` would make it exceed `max_bytes`.
+    /// Note: A single line over `max_bytes` is still written (alone in its file).
+    async fn file_for(&mut self, line_len: u64) -> Result<&mut (File, u64)> {
+        let current = match self.current.take() {
+            Some((_, size)) if size > 0 && size + line_len > self.max_bytes => {
+                self.rotate().await?;
+                self.open().await?
+            }
+            Some(current) => current,
+            None => self.open().await?,
+        };
+
+        Ok(self.current.insert(current))
+    }
+
+    async fn open(&self) -> Result<(File, u64)> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(0))
+            .await?;
+        let size = file.metadata().await?.len();
+
+        Ok((file, size))
+    }
+
+    /// Delete the oldest file, and shift the others (the active one becomes `.1`).
+    async fn rotate(&self) -> Result<()> {
+        remove_if_exists(&self.path(self.max_files - 1)).await?;
+        for index in (0..self.max_files - 1).rev() {
+            rename_if_exists(&self.path(index), &self.path(index + 1)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RequestLogSink for FileSink {
+    async fn write(&mut self, log_line: &RequestLogLine) -> Result<()> {
+        let mut line = serde_json::to_string(log_line)?;
+        line.push('\n');
+        let line_len = line.len() as u64;
+
+        let (file, size) = self.file_for(line_len).await?;
+        file.write_all(line.as_bytes()).await?;
+        // Note: Flush, so that no line is lost when the file is dropped (rotation, shutdown).
+        file.flush().await?;
+        *size += line_len;
+
+        Ok(())
+    }
+}
+
+async fn remove_if_exists(path: &Path) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(ex) if ex.kind() != ErrorKind::NotFound => Err(ex.into()),
+        _ => Ok(()),
+    }
+}
+
+async fn rename_if_exists(from: &Path, to: &Path) -> Result<()> {
+    match tokio::fs::rename(from, to).await {
+        Err(ex) if ex.kind() != ErrorKind::NotFound => Err(ex.into()),
+        _ => Ok(()),
+    }
+}
+
+// endregion: --- File
+
+// region:    --- Db
+
+pub struct DbSink {
+    mm: ModelManager,
+}
+
+impl DbSink {
+    pub fn new(mm: ModelManager) -> Self {
+        DbSink { mm }
+    }
+}
+
+#[async_trait]
+impl RequestLogSink for DbSink {
+    async fn write(&mut self, log_line: &RequestLogLine) -> Result<()> {
+        let log_c = RequestLogForCreate {
+            uuid: log_line.uuid,
+            timestamp: log_line.timestamp,
+            duration_ms: log_line.duration_ms,
+            user_id: log_line.user_id,
+            client_ip: log_line.client_ip.clone(),
+            http_method: log_line.http_method.clone(),
+            http_path: log_line.http_path.clone(),
+            http_status: log_line.http_status.into(),
+            rpc_id: log_line.rpc_id.clone(),
+            rpc_method: log_line.rpc_method.clone(),
+            client_error_type: log_line.client_error_type.clone(),
+            error_type: log_line.error_type.clone(),
+            error_data: log_line.error_data.clone(),
+        };
+
+        RequestLogBmc::create(&Ctx::root_ctx(), &self.mm, log_c).await?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Db
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use lib_utils::time::now_utc;
+    use uuid::Uuid;
+
+    fn fx_log_line() -> RequestLogLine {
+        RequestLogLine {
+            uuid: Uuid::new_v4(),
+            timestamp: now_utc(),
+            duration_ms: 1.,
+            user_id: None,
+            client_ip: None,
+            http_path: "/api/rpc".to_string(),
+            http_method: "POST".to_string(),
+            http_status: 200,
+            rpc_id: None,
+            rpc_method: None,
+            client_error_type: None,
+            error_type: None,
+            error_data: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_sink_rotate_and_retention() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_dir = std::env::temp_dir().join(format!("request-log-test-{}", Uuid::new_v4()));
+        let fx_line_len = serde_json::to_string(&fx_log_line())?.len() as u64 + 1;
+        // Two lines per file, three files kept.
+        let mut sink = FileSink::new(&fx_dir, fx_line_len * 2, 3);
+
+        // -- Exec
+        for _ in 0..7 {
+            sink.write(&fx_log_line()).await?;
+        }
+
+        // -- Check
+        let line_count = |index: usize| {
+            std::fs::read_to_string(sink.path(index)).map(|content| content.lines().count())
+        };
+        assert_eq!(line_count(0)?, 1);
+        assert_eq!(line_count(1)?, 2);
+        assert_eq!(line_count(2)?, 2);
+        assert!(!sink.path(3).exists(), "Oldest file should be deleted");
+
+        // -- Cleanup
+        std::fs::remove_dir_all(&fx_dir)?;
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
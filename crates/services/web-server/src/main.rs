@@ -3,35 +3,45 @@
 mod config;
 mod error;
 mod log;
+mod purge;
+mod server;
+mod telemetry;
 mod web;
 
 // #[cfg(test)] // Commented during early development
 pub use self::error::{Error, Result};
 use config::web_config;
 
+use crate::log::RequestLogger;
 use crate::web::mw_auth::{mw_ctx_require, mw_ctx_resolve};
+use crate::web::mw_metrics::mw_metrics;
+use crate::web::mw_rate_limit::{mw_rate_limit, mw_rate_limit_ip, RateLimiter};
+use crate::web::mw_req_span::mw_req_span;
+use crate::web::mw_req_stamp::mw_req_stamp;
 use crate::web::mw_res_map::mw_response_map;
-use crate::web::{routes_login, routes_rpc, routes_static};
+use crate::web::{
+    routes_health, routes_login, routes_login_oidc, routes_metrics, routes_rpc, routes_static,
+};
 
+use lib_auth::oidc::{oidc_config, OidcClient};
 use lib_core::_dev_utils;
 use lib_core::model::ModelManager;
 
 use axum::{middleware, Router};
-use tokio::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
 use tower_cookies::CookieManagerLayer;
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 
 // endregion: --- Modules
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Tracing
-    tracing_subscriber::fmt()
-        .without_time() // For early local development
-        .with_target(false)
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    // Tracing (and OpenTelemetry export when configured)
+    let telemetry = telemetry::init_tracing()?;
+
+    // Metrics (before anything records one).
+    let metrics_handle = routes_metrics::install_recorder()?;
 
     // -- FOR DEV ONLY
     _dev_utils::init_dev().await;
@@ -39,25 +49,64 @@ async fn main() -> Result<()> {
     // Initialize ModelManager.
     let mm = ModelManager::new().await?;
 
+    // Background purges (soft deleted tasks, expired sessions)
+    purge::spawn_purge_deleted(mm.clone());
+    purge::spawn_prune_sessions(mm.clone());
+
+    // Request log (async sinks)
+    let (request_logger, request_log_writer) = RequestLogger::from_config(&mm);
+
     // -- Define Routes
     let routes_rpc =
         routes_rpc::routes(mm.clone()).route_layer(middleware::from_fn(mw_ctx_require));
 
+    // -- Oidc (SSO) login, when configured
+    let mut routes_login = routes_login::routes(mm.clone());
+    if let Some(oidc_config) = oidc_config() {
+        let oidc = OidcClient::discover(oidc_config.clone()).await?;
+        info!("OIDC login enabled ({})", oidc.metadata().issuer);
+        routes_login = routes_login.merge(routes_login_oidc::routes(mm.clone(), Arc::new(oidc)));
+    }
+
+    // -- Rate limiters (per user, and per IP before the ctx resolve db queries)
+    let rate_limiter = Arc::new(RateLimiter::from_config());
+    let rate_limiter_ip = Arc::new(RateLimiter::ip_from_config());
+    RateLimiter::spawn_prune(rate_limiter.clone());
+    RateLimiter::spawn_prune(rate_limiter_ip.clone());
+
     let routes_all = Router::new()
-        .merge(routes_login::routes(mm.clone()))
+        .merge(routes_login)
         .nest("/api", routes_rpc)
-        .layer(middleware::map_response(mw_response_map))
+        .layer(middleware::from_fn_with_state(rate_limiter, mw_rate_limit))
         .layer(middleware::from_fn_with_state(mm.clone(), mw_ctx_resolve))
+        .layer(middleware::from_fn_with_state(
+            rate_limiter_ip,
+            mw_rate_limit_ip,
+        ))
+        .layer(middleware::from_fn(mw_metrics))
+        .layer(middleware::map_response_with_state(
+            request_logger,
+            mw_response_map,
+        ))
         .layer(CookieManagerLayer::new())
-        .fallback_service(routes_static::serve_dir());
+        // Probes, outside of the middlewares (but with the request span and id)
+        .merge(routes_health::routes(mm.clone()))
+        .merge(routes_metrics::routes(metrics_handle, mm.clone()))
+        .fallback_service(routes_static::serve_dir())
+        .layer(middleware::from_fn(mw_req_span))
+        .layer(middleware::from_fn(mw_req_stamp));
 
     // region:    --- Start Server
-    let listener = TcpListener::bind("127.0.0.1:8080").await.unwrap();
-    info!("LISTENING on {:?}\n", listener.local_addr());
-    axum::serve(listener, routes_all.into_make_service())
-        .await
-        .unwrap();
+    server::serve(routes_all).await?;
+
+    // -- All connections are drained (or timed out), flush the request log lines
+    //    (the db sink needs the pool), then release the db pool.
+    let drain_timeout = Duration::from_secs(web_config().SHUTDOWN_TIMEOUT_SEC);
+    request_log_writer.drain(drain_timeout).await;
+    mm.close().await;
+    info!("SHUTDOWN complete");
 
+    telemetry.shutdown();
     // endregion: --- Start Server
 
     Ok(())
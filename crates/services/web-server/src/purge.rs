@@ -0,0 +1,119 @@
+//! Background purges:
+//!
+//! - The soft deleted tasks, once deleted for longer than `SOFT_DELETE_RETENTION_DAYS`
+//!   (checked every `SOFT_DELETE_PURGE_INTERVAL_SEC`).
+//! - The expired user sessions (hourly).
+//!
+//! Each job runs supervised: a panic is logged, and the job restarted.
+
+use crate::config::web_config;
+
+use lib_core::ctx::Ctx;
+use lib_core::model::task::TaskBmc;
+use lib_core::model::user_session::UserSessionBmc;
+use lib_core::model::ModelManager;
+use lib_utils::time::now_utc;
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+const SESSION_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+/// Delay before restarting a job that panicked.
+const RESTART_DELAY: Duration = Duration::from_secs(10);
+
+/// Note: `SOFT_DELETE_PURGE_INTERVAL_SEC` (at least 1) is validated by the `WebConfig` load.
+pub fn spawn_purge_deleted(mm: ModelManager) -> JoinHandle<()> {
+    let config = web_config();
+    let retention = time::Duration::days(config.SOFT_DELETE_RETENTION_DAYS);
+    let period = Duration::from_secs(config.SOFT_DELETE_PURGE_INTERVAL_SEC);
+
+    spawn_supervised("purge deleted tasks", move || {
+        let mm = mm.clone();
+        async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+
+                let deleted_before = now_utc() - retention;
+                match TaskBmc::purge_deleted_before(&Ctx::root_ctx(), &mm, deleted_before).await {
+                    Ok(0) => (),
+                    Ok(count) => info!("{:<12} - purged {count} deleted task(s)", "PURGE"),
+                    Err(ex) => error!("{:<12} - purge deleted tasks fail - {ex:?}", "PURGE"),
+                }
+            }
+        }
+    })
+}
+
+pub fn spawn_prune_sessions(mm: ModelManager) -> JoinHandle<()> {
+    spawn_supervised("prune expired sessions", move || {
+        let mm = mm.clone();
+        async move {
+            let mut interval = tokio::time::interval(SESSION_PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                match UserSessionBmc::prune_expired(&Ctx::root_ctx(), &mm).await {
+                    Ok(0) => (),
+                    Ok(count) => info!("{:<12} - pruned {count} expired session(s)", "PURGE"),
+                    Err(ex) => error!("{:<12} - prune expired sessions fail - {ex:?}", "PURGE"),
+                }
+            }
+        }
+    })
+}
+
+/// Run the `job` task, and restart it (after `RESTART_DELAY`) when it panics
+/// (rather than the panic being lost with the dropped `JoinHandle`).
+fn spawn_supervised<F, Fut>(name: &'static str, job: F) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        loop {
+            match tokio::spawn(job()).await {
+                Ok(()) => return,
+                Err(ex) => error!("{:<12} - {name} job failed, restarting - {ex}", "PURGE"),
+            }
+            tokio::time::sleep(RESTART_DELAY).await;
+        }
+    })
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spawn_supervised_restart_on_panic() -> Result<()> {
+        // -- Setup & Fixtures
+        let runs = Arc::new(AtomicU32::new(0));
+        let job_runs = runs.clone();
+
+        // -- Exec
+        // Panics on the first run, and returns on the second.
+        let supervisor = spawn_supervised("test job", move || {
+            let runs = job_runs.clone();
+            async move {
+                if runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("test job panic");
+                }
+            }
+        });
+        supervisor.await?;
+
+        // -- Check
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,228 @@
+//! Server start, with optional TLS (rustls) and graceful shutdown.
+//!
+//! - The bind address is `WEB_HOST:WEB_PORT`.
+//! - When `TLS_CERT_FILE` and `TLS_KEY_FILE` are set, serves https, and reloads the
+//!   cert/key when their files change (e.g., certificate renewal), without restart.
+//! - On SIGTERM/SIGINT, stops accepting, and drains the in-flight requests (up to
+//!   `SHUTDOWN_TIMEOUT_SEC`). The caller then drains the request log, and closes
+//!   the `ModelManager` db pool.
+
+use crate::config::{web_config, TlsFiles};
+use crate::{Error, Result};
+
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use axum_server::Handle;
+use std::net::SocketAddr;
+use std::time::{Duration, SystemTime};
+use tokio::signal;
+use tracing::{error, info};
+
+/// Interval at which the TLS cert/key files are checked for changes.
+const TLS_RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+pub async fn serve(routes_all: Router) -> Result<()> {
+    let config = web_config();
+    let addr = SocketAddr::new(config.WEB_HOST, config.WEB_PORT);
+
+    let handle = Handle::new();
+    tokio::spawn(graceful_shutdown_on_signal(
+        handle.clone(),
+        config.SHUTDOWN_TIMEOUT_SEC,
+    ));
+
+    serve_with_handle(routes_all, addr, config.TLS_FILES.as_ref(), handle).await
+}
+
+/// Serve until the `handle` shutdown (and the in-flight requests are drained).
+async fn serve_with_handle(
+    routes_all: Router,
+    addr: SocketAddr,
+    tls_files: Option<&'static TlsFiles>,
+    handle: Handle,
+) -> Result<()> {
+    let make_service = routes_all.into_make_service_with_connect_info::<SocketAddr>();
+
+    match tls_files {
+        Some(tls_files) => {
+            let tls_config = RustlsConfig::from_pem_file(&tls_files.cert, &tls_files.key)
+                .await
+                .map_err(|ex| Error::TlsConfig(ex.to_string()))?;
+            tokio::spawn(reload_tls_on_change(tls_config.clone(), tls_files));
+
+            info!("LISTENING on https://{addr}\n");
+            axum_server::bind_rustls(addr, tls_config)
+                .handle(handle)
+                .serve(make_service)
+                .await
+        }
+        None => {
+            info!("LISTENING on http://{addr}\n");
+            axum_server::bind(addr)
+                .handle(handle)
+                .serve(make_service)
+                .await
+        }
+    }
+    .map_err(|ex| Error::Serve(ex.to_string()))
+}
+
+// region:    --- Graceful Shutdown
+
+async fn graceful_shutdown_on_signal(handle: Handle, timeout_sec: u64) {
+    shutdown_signal().await;
+
+    info!(
+        "SHUTDOWN - draining {} connection(s) (timeout {timeout_sec}s)",
+        handle.connection_count()
+    );
+    handle.graceful_shutdown(Some(Duration::from_secs(timeout_sec)));
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+// endregion: --- Graceful Shutdown
+
+// region:    --- TLS Reload
+
+async fn reload_tls_on_change(tls_config: RustlsConfig, tls_files: &'static TlsFiles) {
+    let mut last_modified = tls_files_modified(tls_files).await;
+    let mut interval = tokio::time::interval(TLS_RELOAD_CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let modified = tls_files_modified(tls_files).await;
+        if modified == last_modified {
+            continue;
+        }
+
+        // Note: On failure (e.g., cert written but not the key yet), the current
+        //       config is kept, and the reload is retried on the next check.
+        match tls_config
+            .reload_from_pem_file(&tls_files.cert, &tls_files.key)
+            .await
+        {
+            Ok(()) => {
+                info!("TLS cert/key reloaded");
+                last_modified = modified;
+            }
+            Err(ex) => error!("TLS cert/key reload failed - {ex}"),
+        }
+    }
+}
+
+async fn tls_files_modified(tls_files: &TlsFiles) -> [Option<SystemTime>; 2] {
+    [
+        file_modified(&tls_files.cert).await,
+        file_modified(&tls_files.key).await,
+    ]
+}
+
+async fn file_modified(path: &str) -> Option<SystemTime> {
+    tokio::fs::metadata(path)
+        .await
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+// endregion: --- TLS Reload
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, Result};
+    use axum::routing::get;
+    use std::time::Instant;
+    use tokio::task::JoinHandle;
+
+    /// Serve a `/slow` route (answering after `delay`) on a free local port.
+    async fn spawn_slow_server(
+        delay: Duration,
+    ) -> Result<(Handle, SocketAddr, JoinHandle<Result<(), Error>>)> {
+        let routes = Router::new().route(
+            "/slow",
+            get(move || async move {
+                tokio::time::sleep(delay).await;
+                "done"
+            }),
+        );
+        let handle = Handle::new();
+        let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+        let server = tokio::spawn(serve_with_handle(routes, addr, None, handle.clone()));
+        let addr = handle.listening().await.context("Should be listening")?;
+
+        Ok((handle, addr, server))
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_in_flight() -> Result<()> {
+        // -- Setup & Fixtures
+        let (handle, addr, server) = spawn_slow_server(Duration::from_millis(300)).await?;
+        let in_flight = tokio::spawn(reqwest::get(format!("http://{addr}/slow")));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // -- Exec
+        handle.graceful_shutdown(Some(Duration::from_secs(5)));
+        let res = in_flight.await??;
+        server.await??;
+
+        // -- Check
+        assert_eq!(res.text().await?, "done");
+        let res_after = reqwest::get(format!("http://{addr}/slow")).await;
+        assert!(res_after.is_err(), "Should not accept after the shutdown");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_timeout_closes_in_flight() -> Result<()> {
+        // -- Setup & Fixtures
+        let (handle, addr, server) = spawn_slow_server(Duration::from_secs(30)).await?;
+        let in_flight = tokio::spawn(reqwest::get(format!("http://{addr}/slow")));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // -- Exec
+        let start = Instant::now();
+        handle.graceful_shutdown(Some(Duration::from_millis(200)));
+        server.await??;
+
+        // -- Check
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "Should not wait the request"
+        );
+        assert!(
+            in_flight.await?.is_err(),
+            "In flight request should be closed"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,75 @@
+//! Tracing subscriber setup, with an optional OpenTelemetry (OTLP/HTTP) span exporter.
+//!
+//! - The `fmt` layer (with `RUST_LOG` env filter) is always on, on stderr (stdout is
+//!   for the `stdout` request log sink JSON lines).
+//! - When `SERVICE_OTLP_ENDPOINT` is set (e.g., `http://localhost:4318/v1/traces`),
+//!   the spans (request, rpc, db) are also exported with OTLP.
+//!
+//! Note: The returned `TelemetryGuard` must be kept until the end of `main`, and
+//!       `shutdown` called to flush the pending spans.
+
+use crate::config::web_config;
+use crate::{Error, Result};
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+const SERVICE_NAME: &str = env!("CARGO_PKG_NAME");
+
+pub struct TelemetryGuard {
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl TelemetryGuard {
+    /// Flush and stop the span exporter (if any).
+    pub fn shutdown(self) {
+        if let Some(tracer_provider) = self.tracer_provider {
+            if let Err(ex) = tracer_provider.shutdown() {
+                eprintln!("OTEL - tracer provider shutdown failed - {ex}");
+            }
+        }
+    }
+}
+
+pub fn init_tracing() -> Result<TelemetryGuard> {
+    let tracer_provider = match &web_config().OTLP_ENDPOINT {
+        Some(endpoint) => Some(new_otlp_tracer_provider(endpoint)?),
+        None => None,
+    };
+
+    let otel_layer = tracer_provider.as_ref().map(|tracer_provider| {
+        tracing_opentelemetry::layer().with_tracer(tracer_provider.tracer(SERVICE_NAME))
+    });
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_writer(std::io::stderr),
+        )
+        .with(otel_layer)
+        .init();
+
+    Ok(TelemetryGuard { tracer_provider })
+}
+
+fn new_otlp_tracer_provider(endpoint: &str) -> Result<SdkTracerProvider> {
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|ex| Error::OtlpExporter(ex.to_string()))?;
+
+    let resource = Resource::builder().with_service_name(SERVICE_NAME).build();
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
 use crate::web;
-use lib_auth::{pwd, token};
+use lib_auth::pwd::{self, policy, PolicyViolation};
+use lib_auth::{oidc, token};
 use lib_core::model;
 
 use axum::http::StatusCode;
@@ -27,6 +28,22 @@ pub enum Error {
         user_id: i64,
     },
 
+    // -- Oidc Login Errors
+    OidcAuthRequestNotInCookie,
+    OidcStateNotMatching,
+    OidcCodeMissing,
+    OidcProviderFail(String),
+
+    // -- Csrf
+    CsrfTokenNotInHeader,
+    CsrfTokenInvalid,
+
+    // -- Rate Limit
+    RateLimited {
+        retry_after_sec: u64,
+    },
+    RateLimitBodyFail,
+
     // -- CtxExtError
     #[from]
     CtxExt(web::mw_auth::CtxExtError),
@@ -39,6 +56,8 @@ pub enum Error {
     #[from]
     Token(token::Error),
     #[from]
+    Oidc(oidc::Error),
+    #[from]
     Rpc(lib_rpc::Error),
 
     // -- External Modules
@@ -83,14 +102,110 @@ impl Error {
             | LoginFailUserHasNoPwd { .. }
             | LoginFailPwdNotMatching { .. } => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
 
+            // -- Oidc Login
+            OidcAuthRequestNotInCookie
+            | OidcStateNotMatching
+            | OidcCodeMissing
+            | OidcProviderFail(_)
+            | Oidc(_) => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
+
             // -- Auth
             CtxExt(_) => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+            CsrfTokenNotInHeader | CsrfTokenInvalid => {
+                (StatusCode::FORBIDDEN, ClientError::CSRF_FAIL)
+            }
+
+            // -- Rate Limit
+            RateLimited { retry_after_sec } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                ClientError::RATE_LIMITED {
+                    retry_after_sec: *retry_after_sec,
+                },
+            ),
 
             // -- Model
             Model(model::Error::EntityNotFound { entity, id }) => (
                 StatusCode::BAD_REQUEST,
                 ClientError::ENTITY_NOT_FOUND { entity, id: *id },
             ),
+            Model(model::Error::ListCursorInvalid)
+            | Rpc(lib_rpc::Error::Model(model::Error::ListCursorInvalid)) => {
+                (StatusCode::BAD_REQUEST, ClientError::LIST_CURSOR_INVALID)
+            }
+            Model(model::Error::ColumnNotAllowed { column, .. })
+            | Rpc(lib_rpc::Error::Model(model::Error::ColumnNotAllowed { column, .. })) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::COLUMN_NOT_ALLOWED {
+                    column: column.clone(),
+                },
+            ),
+            Model(model::Error::VersionConflict {
+                entity,
+                id,
+                current,
+            })
+            | Rpc(lib_rpc::Error::Model(model::Error::VersionConflict {
+                entity,
+                id,
+                current,
+            })) => (
+                StatusCode::CONFLICT,
+                ClientError::CONFLICT(ConflictDetail {
+                    entity,
+                    id: *id,
+                    current_version: *current,
+                }),
+            ),
+            Model(model::Error::AccessDenied { .. })
+            | Rpc(lib_rpc::Error::Model(model::Error::AccessDenied { .. })) => {
+                (StatusCode::FORBIDDEN, ClientError::ACCESS_DENIED)
+            }
+            Model(model::Error::TaskCycle {
+                relation,
+                task_id,
+                linked_id,
+            })
+            | Rpc(lib_rpc::Error::Model(model::Error::TaskCycle {
+                relation,
+                task_id,
+                linked_id,
+            })) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::TASK_CYCLE(TaskCycleDetail {
+                    relation,
+                    task_id: *task_id,
+                    linked_id: *linked_id,
+                }),
+            ),
+            Model(model::Error::TaskHasOpenChildren { id, open_ids })
+            | Rpc(lib_rpc::Error::Model(model::Error::TaskHasOpenChildren { id, open_ids })) => (
+                StatusCode::CONFLICT,
+                ClientError::TASK_HAS_OPEN_CHILDREN {
+                    id: *id,
+                    open_ids: open_ids.clone(),
+                },
+            ),
+            Model(model::Error::UserAlreadyExists { .. }) => {
+                (StatusCode::BAD_REQUEST, ClientError::USERNAME_NOT_AVAILABLE)
+            }
+            Model(model::Error::LabelAlreadyExists { .. })
+            | Rpc(lib_rpc::Error::Model(model::Error::LabelAlreadyExists { .. })) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::LABEL_NAME_NOT_AVAILABLE,
+            ),
+
+            // -- Pwd Policy
+            Model(model::Error::Pwd(pwd::Error::Policy(policy::Error::Violations(violations))))
+            | Rpc(lib_rpc::Error::Model(model::Error::Pwd(pwd::Error::Policy(
+                policy::Error::Violations(violations),
+            ))))
+            | Pwd(pwd::Error::Policy(policy::Error::Violations(violations))) => (
+                StatusCode::BAD_REQUEST,
+                ClientError::PWD_POLICY_FAIL {
+                    violations: violations.clone(),
+                },
+            ),
+
             // Fallback
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -103,12 +218,37 @@ impl Error {
 // Client Errors
 #[derive(Debug, Serialize, strum_macros::AsRefStr)]
 #[serde(tag = "message", content = "detail")]
-#[allow(non_camel_case_types)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
 pub enum ClientError {
     LOGIN_FAIL,
     NO_AUTH,
+    ACCESS_DENIED,
+    CSRF_FAIL,
+    RATE_LIMITED { retry_after_sec: u64 },
     ENTITY_NOT_FOUND { entity: &'static str, id: i64 },
+    LIST_CURSOR_INVALID,
+    COLUMN_NOT_ALLOWED { column: String },
+    CONFLICT(ConflictDetail),
+    TASK_CYCLE(TaskCycleDetail),
+    TASK_HAS_OPEN_CHILDREN { id: i64, open_ids: Vec<i64> },
+    USERNAME_NOT_AVAILABLE,
+    LABEL_NAME_NOT_AVAILABLE,
+    PWD_POLICY_FAIL { violations: Vec<PolicyViolation> },
 
     SERVICE_ERROR,
 }
+
+#[derive(Debug, Serialize)]
+pub struct ConflictDetail {
+    pub entity: &'static str,
+    pub id: i64,
+    pub current_version: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskCycleDetail {
+    pub relation: &'static str,
+    pub task_id: i64,
+    pub linked_id: i64,
+}
 // endregion:      — Client Error
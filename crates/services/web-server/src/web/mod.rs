@@ -1,15 +1,28 @@
 // region:         — Modules
+mod client_info;
 mod error;
 pub mod mw_auth;
+pub mod mw_metrics;
+pub mod mw_rate_limit;
+pub mod mw_req_span;
+pub mod mw_req_stamp;
 pub mod mw_res_map;
+pub mod routes_health;
 pub mod routes_login;
+pub mod routes_login_oidc;
+pub mod routes_metrics;
 pub mod routes_rpc;
 pub mod routes_static;
 
-use lib_auth::token::generate_web_token;
+use crate::config::web_config;
+use lib_auth::token::{generate_csrf_token, generate_web_token};
+use lib_core::ctx::Ctx;
+use lib_core::model::user_session::{UserSessionBmc, UserSessionForCreate};
+use lib_core::model::ModelManager;
 use tower_cookies::{Cookie, Cookies};
 use uuid::Uuid;
 
+pub use self::client_info::ClientInfo;
 pub use self::error::ClientError;
 pub use self::error::{Error, Result};
 
@@ -17,25 +30,77 @@ pub use self::error::{Error, Result};
 
 pub const AUTH_TOKEN: &str = "auth-token";
 
-fn set_token_cookie(cookies: &Cookies, user: &str, salt: Uuid) -> Result<()> {
-    let token = generate_web_token(user, salt)?;
+/// CSRF token cookie, readable by the client JS, which must send it back in the
+/// `CSRF_HEADER` for state-changing requests (see `mw_ctx_require`).
+pub const CSRF_TOKEN: &str = "csrf-token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Create the user session (at login) and set its web token and CSRF token cookies.
+async fn start_user_session(
+    mm: &ModelManager,
+    cookies: &Cookies,
+    client_info: ClientInfo,
+    user_id: i64,
+    username: &str,
+    token_salt: Uuid,
+) -> Result<()> {
+    let ClientInfo { user_agent, ip } = client_info;
+    let session_id = UserSessionBmc::create(
+        &Ctx::root_ctx(),
+        mm,
+        UserSessionForCreate {
+            user_id,
+            user_agent,
+            ip,
+        },
+    )
+    .await?;
+
+    set_token_cookie(cookies, username, session_id, token_salt)?;
+    set_csrf_cookie(cookies, session_id)
+}
+
+fn set_token_cookie(cookies: &Cookies, user: &str, session_id: i64, salt: Uuid) -> Result<()> {
+    let token = generate_web_token(user, session_id, salt)?;
 
     let mut cookie = Cookie::new(AUTH_TOKEN, token.to_string());
     cookie.set_http_only(true);
     // Default path is the URI path of the request. This will be '/api/login'
     // for login requests. Super important to set the path!!
     cookie.set_path("/");
+    set_cookie_attributes(&mut cookie);
 
     cookies.add(cookie);
 
     Ok(())
 }
 
-fn remove_token_cookie(cookies: &Cookies) -> Result<()> {
-    let mut cookie = Cookie::from(AUTH_TOKEN);
+fn set_csrf_cookie(cookies: &Cookies, session_id: i64) -> Result<()> {
+    let csrf_token = generate_csrf_token(session_id)?;
+
+    // Not http_only, since the client JS needs to read it.
+    let mut cookie = Cookie::new(CSRF_TOKEN, csrf_token);
     cookie.set_path("/");
+    set_cookie_attributes(&mut cookie);
+
+    cookies.add(cookie);
+
+    Ok(())
+}
+
+fn set_cookie_attributes(cookie: &mut Cookie) {
+    let config = web_config();
+    cookie.set_same_site(config.COOKIE_SAME_SITE);
+    cookie.set_secure(config.COOKIE_SECURE);
+}
+
+fn remove_token_cookie(cookies: &Cookies) -> Result<()> {
+    for name in [AUTH_TOKEN, CSRF_TOKEN] {
+        let mut cookie = Cookie::from(name);
+        cookie.set_path("/");
 
-    cookies.remove(cookie);
+        cookies.remove(cookie);
+    }
 
     Ok(())
 }
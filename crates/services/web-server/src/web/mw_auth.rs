@@ -1,9 +1,11 @@
-use crate::web::{set_token_cookie, AUTH_TOKEN};
+use crate::web::mw_req_stamp::ReqStamp;
+use crate::web::{set_token_cookie, ClientInfo, AUTH_TOKEN, CSRF_HEADER};
 use crate::web::{Error, Result};
-use lib_auth::token::{validate_web_token, Token};
+use lib_auth::token::{validate_csrf_token, validate_web_token, WebToken};
 use lib_core::ctx::Ctx;
 use lib_core::model::user::{UserBmc, UserForAuth};
-use lib_core::model::ModelManager;
+use lib_core::model::user_session::UserSessionBmc;
+use lib_core::model::{self, ModelManager};
 
 use async_trait::async_trait;
 use axum::body::Body;
@@ -14,14 +16,25 @@ use axum::middleware::Next;
 use axum::response::Response;
 use serde::Serialize;
 use tower_cookies::{Cookie, Cookies};
-use tracing::debug;
+use tracing::{debug, Span};
 
-// Check Cookie existence and validity.
+// Check Cookie existence and validity, and the CSRF token for state-changing requests.
 #[allow(dead_code)] // For now, until we have rpc.
 pub async fn mw_ctx_require(ctx: Result<CtxW>, req: Request<Body>, next: Next) -> Result<Response> {
     debug!("{:<12} - mw_ctx_require - {:?}", "MIDDLEWARE", ctx);
 
-    ctx?;
+    let CtxW(ctx) = ctx?;
+
+    if !req.method().is_safe() {
+        let csrf_token = req
+            .headers()
+            .get(CSRF_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .ok_or(Error::CsrfTokenNotInHeader)?;
+        let session_id = ctx.session_id().ok_or(Error::CsrfTokenInvalid)?;
+
+        validate_csrf_token(csrf_token, session_id).map_err(|_| Error::CsrfTokenInvalid)?;
+    }
 
     Ok(next.run(req).await)
 }
@@ -35,7 +48,19 @@ pub async fn mw_ctx_resolve(
 ) -> Result<Response> {
     debug!("{:<12} - mw_ctx_resolve", "MIDDLEWARE");
 
-    let ctx_ext_result = _ctx_resolve(mm, &cookies).await;
+    let client_info = ClientInfo::new(req.headers(), req.extensions());
+    let req_id = req.extensions().get::<ReqStamp>().map(|stamp| stamp.uuid);
+    let ctx_ext_result =
+        _ctx_resolve(mm, &cookies, client_info)
+            .await
+            .map(|CtxW(ctx)| match req_id {
+                Some(req_id) => CtxW(ctx.with_req_id(req_id)),
+                None => CtxW(ctx),
+            });
+
+    if let Ok(CtxW(ctx)) = &ctx_ext_result {
+        Span::current().record("user_id", ctx.user_id());
+    }
 
     // If auth token is invalid then remove it, so we don't keep validating
     if ctx_ext_result.is_err() && !matches!(ctx_ext_result, Err(CtxExtError::TokenNotInCookie)) {
@@ -44,12 +69,23 @@ pub async fn mw_ctx_resolve(
 
     // Store the ctx_ext_result in the request extension
     // (for Ctx Extractor)
-    req.extensions_mut().insert(ctx_ext_result);
+    req.extensions_mut().insert(ctx_ext_result.clone());
 
-    Ok(next.run(req).await)
+    let mut res = next.run(req).await;
+
+    // And the ctx in the response extension (for `mw_response_map`, layered before)
+    if let Ok(ctx) = ctx_ext_result {
+        res.extensions_mut().insert(ctx);
+    }
+
+    Ok(res)
 }
 
-async fn _ctx_resolve(mm: State<ModelManager>, cookies: &Cookies) -> CtxExtResult {
+async fn _ctx_resolve(
+    mm: State<ModelManager>,
+    cookies: &Cookies,
+    client_info: ClientInfo,
+) -> CtxExtResult {
     // -- Get Token String
     let token = cookies
         .get(AUTH_TOKEN)
@@ -57,10 +93,10 @@ async fn _ctx_resolve(mm: State<ModelManager>, cookies: &Cookies) -> CtxExtResul
         .ok_or(CtxExtError::TokenNotInCookie)?;
 
     // -- Parse Token
-    let token: Token = token.parse().map_err(|_| CtxExtError::TokenWrongFormat)?;
+    let token: WebToken = token.parse().map_err(|_| CtxExtError::TokenWrongFormat)?;
 
     // -- Get UserForAuth
-    let user: UserForAuth = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, &token.ident)
+    let user: UserForAuth = UserBmc::first_by_username(&Ctx::root_ctx(), &mm, token.ident())
         .await
         .map_err(|ex| CtxExtError::ModelAccessError(ex.to_string()))?
         .ok_or(CtxExtError::UserNotFound)?;
@@ -68,12 +104,21 @@ async fn _ctx_resolve(mm: State<ModelManager>, cookies: &Cookies) -> CtxExtResul
     // -- Validate Token
     validate_web_token(&token, user.token_salt).map_err(|_| CtxExtError::FailValidate)?;
 
+    // -- Check and touch the session (fails when revoked or expired)
+    let session_id = token.session_id();
+    UserSessionBmc::touch(&Ctx::root_ctx(), &mm, session_id, user.id, client_info.ip)
+        .await
+        .map_err(|ex| match ex {
+            model::Error::EntityNotFound { .. } => CtxExtError::SessionNotFound,
+            ex => CtxExtError::ModelAccessError(ex.to_string()),
+        })?;
+
     // -- Update Token
-    set_token_cookie(cookies, &user.username, user.token_salt)
+    set_token_cookie(cookies, &user.username, session_id, user.token_salt)
         .map_err(|_| CtxExtError::CannotSetTokenCookie)?;
 
     // -- Create CtxExtResult
-    Ctx::new(user.id)
+    Ctx::new_for_session(user.id, session_id)
         .map(CtxW)
         .map_err(|ex| CtxExtError::CtxCreateFail(ex.to_string()))
 }
@@ -111,6 +156,7 @@ pub enum CtxExtError {
     UserNotFound,
     ModelAccessError(String),
     FailValidate,
+    SessionNotFound,
     CannotSetTokenCookie,
 
     CtxNotInRequestExt,
@@ -118,3 +164,88 @@ pub enum CtxExtError {
 }
 
 // endregion:      --- Ctx Extractor Result/Errors
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use axum::{middleware, Router};
+    use lib_auth::token::generate_csrf_token;
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn fx_app() -> Router {
+        Router::new()
+            .route("/fx", post(|| async { "ok" }))
+            .route_layer(middleware::from_fn(mw_ctx_require))
+    }
+
+    /// Post to the fx app, with the ctx of the session as resolved by `mw_ctx_resolve`.
+    async fn exec(session_id: i64, csrf_token: Option<&str>) -> Result<Response> {
+        let mut req = Request::post("/fx");
+        if let Some(csrf_token) = csrf_token {
+            req = req.header(CSRF_HEADER, csrf_token);
+        }
+        let mut req = req.body(Body::empty())?;
+        let ctx_ext_result: CtxExtResult = Ok(CtxW(Ctx::new_for_session(1000, session_id)?));
+        req.extensions_mut().insert(ctx_ext_result);
+
+        Ok(fx_app().oneshot(req).await?)
+    }
+
+    fn res_error(res: &Response) -> Option<&Error> {
+        res.extensions().get::<Arc<Error>>().map(|err| err.as_ref())
+    }
+
+    #[tokio::test]
+    async fn test_ctx_require_csrf_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_csrf_token = generate_csrf_token(101)?;
+
+        // -- Exec
+        let res = exec(101, Some(&fx_csrf_token)).await?;
+
+        // -- Check
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res_error(&res).is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ctx_require_csrf_err_not_in_header() -> Result<()> {
+        // -- Exec
+        let res = exec(101, None).await?;
+
+        // -- Check
+        assert!(
+            matches!(res_error(&res), Some(Error::CsrfTokenNotInHeader)),
+            "should be CsrfTokenNotInHeader"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ctx_require_csrf_err_other_session() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_csrf_token = generate_csrf_token(102)?;
+
+        // -- Exec
+        let res = exec(101, Some(&fx_csrf_token)).await?;
+
+        // -- Check
+        assert!(
+            matches!(res_error(&res), Some(Error::CsrfTokenInvalid)),
+            "should be CsrfTokenInvalid"
+        );
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
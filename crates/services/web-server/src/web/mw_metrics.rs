@@ -0,0 +1,168 @@
+//! Request metrics (count and latency), labeled by:
+//!
+//! - `path` the matched route path (e.g., `/api/rpc`).
+//! - `rpc_method` the rpc method (from the response `RpcInfo`), `"unknown"` for the
+//!   not existing methods (client provided, so not bounded), or `""`.
+//! - `error` the `ClientError` variant name (from the response `web::Error`), or `""`.
+//! - `status` the response status code (the client error one for the errors).
+//!
+//! Note: Must be layered before `mw_response_map` (which replaces the error responses),
+//!       and after `mw_rate_limit`, so that the rate limited requests are counted.
+
+use crate::web;
+use crate::web::routes_rpc::RpcInfo;
+
+use axum::body::Body;
+use axum::extract::MatchedPath;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Counter of the requests.
+pub const HTTP_REQUESTS_METRIC: &str = "http_requests_total";
+/// Histogram (seconds) of the request durations.
+pub const HTTP_REQUEST_DURATION_METRIC: &str = "http_request_duration_seconds";
+
+pub async fn mw_metrics(req: Request<Body>, next: Next) -> Response {
+    let path = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let start = Instant::now();
+    let res = next.run(req).await;
+    let duration = start.elapsed().as_secs_f64();
+
+    let web_error = res.extensions().get::<Arc<web::Error>>();
+    let rpc_method = match (res.extensions().get::<Arc<RpcInfo>>(), web_error) {
+        (Some(_), Some(web_error))
+            if matches!(
+                web_error.as_ref(),
+                web::Error::Rpc(lib_rpc::Error::RpcMethodUnknown(_))
+            ) =>
+        {
+            "unknown".to_string()
+        }
+        (Some(rpc_info), _) => rpc_info.method.clone(),
+        (None, _) => String::new(),
+    };
+    // Note: The client status, since the error response is still the 500 placeholder.
+    let (status, error) = match web_error {
+        Some(web_error) => {
+            let (status, client_error) = web_error.client_status_and_error();
+            (status, client_error.as_ref().to_string())
+        }
+        None => (res.status(), String::new()),
+    };
+
+    let labels = [
+        ("path", path),
+        ("rpc_method", rpc_method),
+        ("error", error),
+        ("status", status.as_u16().to_string()),
+    ];
+    metrics::counter!(HTTP_REQUESTS_METRIC, &labels).increment(1);
+    metrics::histogram!(HTTP_REQUEST_DURATION_METRIC, &labels).record(duration);
+
+    res
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use axum::extract::Path;
+    use axum::middleware;
+    use axum::response::IntoResponse;
+    use axum::routing::{get, post};
+    use axum::Router;
+    use lib_core::model;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use tower::ServiceExt;
+
+    async fn item_handler(Path(id): Path<i64>) -> web::Result<&'static str> {
+        if id > 100 {
+            return Err(web::Error::Model(model::Error::EntityNotFound {
+                entity: "task",
+                id,
+            }));
+        }
+        Ok("item")
+    }
+
+    async fn rpc_handler(Path(method): Path<String>) -> Response {
+        let mut res = match method.as_str() {
+            "list_tasks" => "[]".into_response(),
+            _ => web::Error::Rpc(lib_rpc::Error::RpcMethodUnknown(method.clone())).into_response(),
+        };
+        res.extensions_mut()
+            .insert(Arc::new(RpcInfo { id: None, method }));
+        res
+    }
+
+    /// Note: The local recorder is per thread (the tokio test runtime is single threaded).
+    #[tokio::test]
+    async fn test_labels_bounded_and_prometheus_output() -> Result<()> {
+        // -- Setup & Fixtures
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+        let app = Router::new()
+            .route("/items/:id", get(item_handler))
+            .route("/rpc/:method", post(rpc_handler))
+            .layer(middleware::from_fn(mw_metrics));
+        let fx_requests = [
+            ("GET", "/items/1"),
+            ("GET", "/items/2"),
+            ("GET", "/items/101"),
+            ("GET", "/items/102"),
+            ("POST", "/rpc/list_tasks"),
+            ("POST", "/rpc/random_01"),
+            ("POST", "/rpc/random_02"),
+            ("GET", "/not/found/01"),
+            ("GET", "/not/found/02"),
+        ];
+
+        // -- Exec
+        for (method, uri) in fx_requests {
+            let req = Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())?;
+            app.clone().oneshot(req).await?;
+        }
+        let output = handle.render();
+
+        // -- Check
+        let requests_total: Vec<&str> = output
+            .lines()
+            .filter(|line| line.starts_with(HTTP_REQUESTS_METRIC))
+            .collect();
+        let expected = [
+            r#"path="/items/:id",rpc_method="",error="",status="200"} 2"#,
+            r#"path="/items/:id",rpc_method="",error="ENTITY_NOT_FOUND",status="400"} 2"#,
+            r#"path="/rpc/:method",rpc_method="list_tasks",error="",status="200"} 1"#,
+            r#"path="/rpc/:method",rpc_method="unknown",error="SERVICE_ERROR",status="500"} 2"#,
+            r#"path="unmatched",rpc_method="",error="",status="404"} 2"#,
+        ];
+        assert_eq!(requests_total.len(), expected.len(), "series:\n{output}");
+        for series in expected {
+            let line = format!("{HTTP_REQUESTS_METRIC}{{{series}");
+            assert!(
+                requests_total.contains(&line.as_str()),
+                "no `{line}` in:\n{output}"
+            );
+        }
+        assert!(output.contains(&format!("# TYPE {HTTP_REQUESTS_METRIC} counter")));
+        assert!(output.contains(&format!("# TYPE {HTTP_REQUEST_DURATION_METRIC} summary")));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,323 @@
+//! Token bucket rate limiting, in two layers (see `WebConfig` `RATE_LIMIT_*`):
+//!
+//! - `mw_rate_limit_ip` keyed by the client IP, before `mw_ctx_resolve` (so that its
+//!   db queries are limited as well). Each request costs 1 token.
+//! - `mw_rate_limit` keyed by the user id, after `mw_ctx_resolve` (needs the Ctx).
+//!   Each request costs 1 token, except RPC methods with a cost override
+//!   (e.g., `list_tasks:2`).
+//!
+//! Buckets refill continuously up to their capacity, and the idle ones are pruned
+//! by a background task (`RateLimiter::spawn_prune`).
+//!
+//! Note: Both must be layered before `mw_response_map` (which maps the `RateLimited`
+//!       error and sets `Retry-After`).
+
+use crate::config::web_config;
+use crate::web::mw_auth::CtxW;
+use crate::web::{ClientInfo, Error, Result};
+
+use axum::body::{to_bytes, Body};
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+const RPC_PATH: &str = "/api/rpc";
+/// Same as the axum default body limit (for the `Json` extractor).
+const RPC_BODY_LIMIT: usize = 2 * 1024 * 1024;
+/// Period of the idle buckets pruning.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Per client IP limit (before the Ctx resolve).
+pub async fn mw_rate_limit_ip(
+    State(limiter): State<Arc<RateLimiter>>,
+    client_info: ClientInfo,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response> {
+    debug!("{:<12} - mw_rate_limit_ip", "MIDDLEWARE");
+
+    // Note: No connect info (e.g., not served with `into_make_service_with_connect_info`).
+    if let Some(ip) = client_info.ip {
+        limiter.consume(RateKey::Ip(ip), 1.).map_err(rate_limited)?;
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Per user limit, with the RPC method costs (the not authenticated requests
+/// are only limited per IP).
+pub async fn mw_rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    ctx: Option<CtxW>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response> {
+    debug!("{:<12} - mw_rate_limit", "MIDDLEWARE");
+
+    let Some(CtxW(ctx)) = ctx else {
+        return Ok(next.run(req).await);
+    };
+    let key = RateKey::User(ctx.user_id());
+
+    // -- Get the cost (peek at the RPC method when it has an override)
+    let (req, cost) = if req.uri().path() == RPC_PATH && !limiter.rpc_costs.is_empty() {
+        let (parts, body) = req.into_parts();
+        let bytes = to_bytes(body, RPC_BODY_LIMIT)
+            .await
+            .map_err(|_| Error::RateLimitBodyFail)?;
+        let cost = serde_json::from_slice::<RpcMethodOnly>(&bytes)
+            .ok()
+            .and_then(|rpc| limiter.rpc_costs.get(&rpc.method))
+            .unwrap_or(1.);
+        (Request::from_parts(parts, Body::from(bytes)), cost)
+    } else {
+        (req, 1.)
+    };
+
+    limiter.consume(key, cost).map_err(rate_limited)?;
+
+    Ok(next.run(req).await)
+}
+
+fn rate_limited(retry_after: Duration) -> Error {
+    Error::RateLimited {
+        retry_after_sec: retry_after.as_secs_f64().ceil() as u64,
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcMethodOnly {
+    method: String,
+}
+
+// region:    --- RateLimiter
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum RateKey {
+    User(i64),
+    Ip(String),
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    rpc_costs: RpcCosts,
+    buckets: Mutex<HashMap<RateKey, Bucket>>,
+}
+
+impl RateLimiter {
+    /// The per user limiter (with the RPC costs).
+    pub fn from_config() -> Self {
+        let config = web_config();
+        Self::new(
+            config.RATE_LIMIT_CAPACITY,
+            config.RATE_LIMIT_REFILL_PER_SEC,
+            config.RATE_LIMIT_RPC_COSTS.clone(),
+        )
+    }
+
+    /// The per client IP limiter.
+    pub fn ip_from_config() -> Self {
+        let config = web_config();
+        Self::new(
+            config.RATE_LIMIT_IP_CAPACITY,
+            config.RATE_LIMIT_IP_REFILL_PER_SEC,
+            RpcCosts::default(),
+        )
+    }
+
+    /// Note: `capacity` (at least 1) and `refill_per_sec` (more than 0) are validated
+    ///       by the `WebConfig` load.
+    fn new(capacity: f64, refill_per_sec: f64, rpc_costs: RpcCosts) -> Self {
+        RateLimiter {
+            capacity,
+            refill_per_sec,
+            rpc_costs,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Periodically prune the idle buckets (off the request path).
+    pub fn spawn_prune(limiter: Arc<RateLimiter>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+            loop {
+                interval.tick().await;
+                limiter.prune(Instant::now());
+            }
+        })
+    }
+
+    /// Consume `cost` tokens from the key bucket,
+    /// or return how long to wait before it would succeed.
+    fn consume(&self, key: RateKey, cost: f64) -> core::result::Result<(), Duration> {
+        let now = Instant::now();
+        // Cannot ask for more than a full bucket.
+        let cost = cost.min(self.capacity);
+
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+
+        // -- Refill
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = now;
+
+        // -- Consume
+        if bucket.tokens >= cost {
+            bucket.tokens -= cost;
+            Ok(())
+        } else {
+            let missing = cost - bucket.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_sec))
+        }
+    }
+
+    /// Remove the buckets that would be full by now (same as a new bucket).
+    fn prune(&self, now: Instant) {
+        self.buckets.lock().unwrap().retain(|_, bucket| {
+            let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+            bucket.tokens + elapsed * self.refill_per_sec < self.capacity
+        });
+    }
+}
+
+// endregion: --- RateLimiter
+
+// region:    --- RpcCosts
+
+/// RPC method cost overrides.
+///
+/// String format: `method:cost,method:cost,...` (can be empty).
+#[derive(Debug, Clone, Default)]
+pub struct RpcCosts(HashMap<String, f64>);
+
+impl RpcCosts {
+    fn get(&self, method: &str) -> Option<f64> {
+        self.0.get(method).copied()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The highest cost (0 when none).
+    pub fn max_cost(&self) -> f64 {
+        self.0.values().copied().fold(0., f64::max)
+    }
+}
+
+impl FromStr for RpcCosts {
+    type Err = String;
+
+    fn from_str(costs_str: &str) -> core::result::Result<Self, Self::Err> {
+        let mut costs = HashMap::new();
+
+        for entry in costs_str
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let (method, cost) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("rpc cost entry '{entry}' should be 'method:cost'"))?;
+            let cost: f64 = cost
+                .trim()
+                .parse()
+                .ok()
+                .filter(|cost: &f64| cost.is_finite() && *cost >= 0.)
+                .ok_or_else(|| format!("rpc cost '{cost}' is not a positive number"))?;
+            costs.insert(method.trim().to_string(), cost);
+        }
+
+        Ok(RpcCosts(costs))
+    }
+}
+
+// endregion: --- RpcCosts
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_consume_ok_and_retry_after() -> Result<()> {
+        // -- Setup & Fixtures
+        let limiter = RateLimiter::new(3., 1., RpcCosts::default());
+        let fx_key = RateKey::User(1000);
+
+        // -- Exec
+        let res_ok = limiter.consume(fx_key.clone(), 2.);
+        let res_err = limiter.consume(fx_key.clone(), 2.);
+        let res_other_key = limiter.consume(RateKey::Ip("127.0.0.1".to_string()), 3.);
+
+        // -- Check
+        assert!(res_ok.is_ok());
+        let retry_after = res_err.expect_err("Should have been rate limited");
+        assert!(retry_after > Duration::from_millis(900) && retry_after <= Duration::from_secs(1));
+        assert!(res_other_key.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_idle_buckets() -> Result<()> {
+        // -- Setup & Fixtures
+        let limiter = RateLimiter::new(3., 1., RpcCosts::default());
+        let _ = limiter.consume(RateKey::User(1000), 1.);
+        let _ = limiter.consume(RateKey::User(1001), 3.);
+
+        // -- Exec
+        // 1001 (empty) would be full after 3 sec, 1000 (2 tokens) after 1 sec.
+        limiter.prune(Instant::now() + Duration::from_millis(1500));
+
+        // -- Check
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert!(buckets.contains_key(&RateKey::User(1001)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rpc_costs_from_str() -> Result<()> {
+        // -- Exec
+        let costs: RpcCosts = "list_tasks:2, create_task:0.5"
+            .parse()
+            .map_err(anyhow::Error::msg)?;
+        let empty: RpcCosts = "".parse().map_err(anyhow::Error::msg)?;
+
+        // -- Check
+        assert_eq!(costs.get("list_tasks"), Some(2.));
+        assert_eq!(costs.get("create_task"), Some(0.5));
+        assert_eq!(costs.get("delete_task"), None);
+        assert!(empty.is_empty());
+        assert_eq!(costs.max_cost(), 2.);
+        assert!("list_tasks".parse::<RpcCosts>().is_err());
+        assert!("list_tasks:-1".parse::<RpcCosts>().is_err());
+        assert!("list_tasks:NaN".parse::<RpcCosts>().is_err());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
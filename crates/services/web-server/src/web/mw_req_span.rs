@@ -0,0 +1,133 @@
+//! Per-request tracing span, created at the request entry (right after `mw_req_stamp`).
+//!
+//! - The span carries the `req_id` (the `ReqStamp` uuid, also used by `mw_response_map`
+//!   for the client error and the request log line), and later the `user_id` (`mw_ctx_resolve`)
+//!   and the `rpc_method` (`routes_rpc`).
+//! - The W3C `traceparent` request header, when present, becomes the span parent.
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tracing::field::Empty;
+use tracing::{info_span, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::web::mw_req_stamp::ReqStamp;
+
+pub async fn mw_req_span(req: Request<Body>, next: Next) -> Response {
+    let req_id = req
+        .extensions()
+        .get::<ReqStamp>()
+        .map(|req_stamp| req_stamp.uuid.to_string());
+
+    let span = info_span!(
+        "request",
+        otel.name = format!("{} {}", req.method(), req.uri().path()),
+        req_id = req_id.as_deref(),
+        http.method = %req.method(),
+        http.path = req.uri().path(),
+        http.status = Empty,
+        user_id = Empty,
+        rpc_method = Empty,
+    );
+
+    // -- Continue the caller trace (when valid `traceparent`)
+    let parent_cx = TraceContextPropagator::new().extract(&HeaderExtractor(req.headers()));
+    // Note: Only fails when there is no OpenTelemetry layer (i.e., no exporter).
+    let _ = span.set_parent(parent_cx);
+
+    let res = next.run(req).instrument(span.clone()).await;
+    span.record("http.status", i64::from(res.status().as_u16()));
+
+    res
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::mw_req_stamp::mw_req_stamp;
+    use anyhow::Result;
+    use axum::extract::Extension;
+    use axum::routing::get;
+    use axum::{middleware, Router};
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use tower::ServiceExt;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[tokio::test]
+    async fn test_req_span_with_traceparent_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+        let fx_traceparent = format!("00-{fx_trace_id}-00f067aa0ba902b7-01");
+
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route(
+                "/fx",
+                get(|Extension(req_stamp): Extension<ReqStamp>| async move {
+                    tracing::Span::current().record("user_id", 1000);
+                    req_stamp.uuid.to_string()
+                }),
+            )
+            .layer(middleware::from_fn(mw_req_span))
+            .layer(middleware::from_fn(mw_req_stamp));
+        let req = Request::get("/fx")
+            .header("traceparent", fx_traceparent)
+            .body(Body::empty())?;
+
+        // -- Exec
+        let res = app.oneshot(req).await?;
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+        provider.force_flush()?;
+
+        // -- Check
+        let spans = exporter.get_finished_spans()?;
+        let span = spans
+            .iter()
+            .find(|span| span.name == "GET /fx")
+            .expect("Should have the request span");
+        assert_eq!(span.span_context.trace_id().to_string(), fx_trace_id);
+        let attr = |key: &str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.clone())
+        };
+        assert_eq!(
+            attr("req_id"),
+            Some(Value::from(String::from_utf8(body.to_vec())?))
+        );
+        assert_eq!(attr("user_id"), Some(Value::I64(1000)));
+        assert_eq!(attr("http.status"), Some(Value::I64(200)));
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -0,0 +1,122 @@
+//! Request stamp (request id and start time), the front-most middleware.
+//!
+//! - The request id is the inbound `X-Request-Id` header when it is a valid uuid
+//!   (e.g., set by the load balancer), otherwise a new uuid v4.
+//! - It is stored in the request extensions as `ReqStamp` (for the request span,
+//!   the `Ctx`, the client error `req_uuid`, and the request log line).
+//! - It is echoed in the `X-Request-Id` header of every response.
+
+use axum::body::Body;
+use axum::http::{HeaderName, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use lib_utils::time::now_utc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The request id and start time, in the request extensions.
+#[derive(Debug, Clone, Copy)]
+pub struct ReqStamp {
+    pub uuid: Uuid,
+    pub time_in: OffsetDateTime,
+}
+
+pub async fn mw_req_stamp(mut req: Request<Body>, next: Next) -> Response {
+    let uuid = req
+        .headers()
+        .get(&X_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| Uuid::try_parse(value.trim()).ok())
+        .unwrap_or_else(Uuid::new_v4);
+
+    req.extensions_mut().insert(ReqStamp {
+        uuid,
+        time_in: now_utc(),
+    });
+
+    let mut res = next.run(req).await;
+
+    // Note: Always valid, as a formatted uuid.
+    if let Ok(value) = HeaderValue::from_str(&uuid.to_string()) {
+        res.headers_mut().insert(X_REQUEST_ID, value);
+    }
+
+    res
+}
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use axum::extract::Extension;
+    use axum::routing::get;
+    use axum::{middleware, Router};
+    use tower::ServiceExt;
+
+    fn fx_app() -> Router {
+        Router::new()
+            .route(
+                "/fx",
+                get(|Extension(req_stamp): Extension<ReqStamp>| async move {
+                    req_stamp.uuid.to_string()
+                }),
+            )
+            .layer(middleware::from_fn(mw_req_stamp))
+    }
+
+    async fn exec(req_id: Option<&str>) -> Result<(String, String)> {
+        let mut req = Request::get("/fx");
+        if let Some(req_id) = req_id {
+            req = req.header(X_REQUEST_ID, req_id);
+        }
+        let res = fx_app().oneshot(req.body(Body::empty())?).await?;
+
+        let header = res
+            .headers()
+            .get(X_REQUEST_ID)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+
+        Ok((header, String::from_utf8(body.to_vec())?))
+    }
+
+    #[tokio::test]
+    async fn test_req_stamp_inbound_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let fx_req_id = "0d9b7c2e-1f4a-4a8e-9a65-3c2b1f0e9d8c";
+
+        // -- Exec
+        let (header, handler_req_id) = exec(Some(fx_req_id)).await?;
+
+        // -- Check
+        assert_eq!(header, fx_req_id);
+        assert_eq!(handler_req_id, fx_req_id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_req_stamp_invalid_inbound_generated() -> Result<()> {
+        // -- Exec
+        let (header, handler_req_id) = exec(Some("not-a-uuid\"<script>")).await?;
+        let (header_none, _) = exec(None).await?;
+
+        // -- Check
+        assert!(
+            Uuid::try_parse(&header).is_ok(),
+            "should be a generated uuid"
+        );
+        assert_eq!(header, handler_req_id);
+        assert!(Uuid::try_parse(&header_none).is_ok());
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
@@ -1,28 +1,42 @@
-use crate::log::log_request;
-use crate::web;
+use crate::log::{log_request, RequestLogger};
 use crate::web::mw_auth::CtxW;
+use crate::web::mw_req_stamp::ReqStamp;
 use crate::web::routes_rpc::RpcInfo;
+use crate::web::{self, ClientInfo};
 
 use std::sync::Arc;
 
-use axum::http::{Method, Uri};
+use axum::extract::State;
+use axum::http::header::RETRY_AFTER;
+use axum::http::{HeaderValue, Method, Uri};
 use axum::response::{IntoResponse, Response};
-use axum::Json;
+use axum::{Extension, Json};
+use lib_utils::time::now_utc;
 use serde_json::{json, to_value};
 use tracing::debug;
 use uuid::Uuid;
 
 // region:         ---Response Mapper
 pub async fn mw_response_map(
-    ctx: Option<CtxW>,
+    State(logger): State<RequestLogger>,
     uri: Uri,
     req_method: Method,
+    req_stamp: Option<Extension<ReqStamp>>,
+    client_info: ClientInfo,
     res: Response,
 ) -> Response {
-    let ctx = ctx.map(|ctx| ctx.0);
+    // Note: From the response, since `mw_ctx_resolve` is layered after this middleware.
+    let ctx = res.extensions().get::<CtxW>().map(|ctx| ctx.0.clone());
 
     debug!("{:<12} - mw_response_map", "RES_MAPPER");
-    let uuid = Uuid::new_v4();
+    // Note: Set by `mw_req_stamp`, but the fallback keeps this middleware self-sufficient.
+    let req_stamp = req_stamp
+        .map(|Extension(req_stamp)| req_stamp)
+        .unwrap_or_else(|| ReqStamp {
+            uuid: Uuid::new_v4(),
+            time_in: now_utc(),
+        });
+    let uuid = req_stamp.uuid;
 
     let rpc_info = res.extensions().get::<Arc<RpcInfo>>().map(Arc::as_ref);
 
@@ -34,6 +48,10 @@ pub async fn mw_response_map(
     let error_response = client_status_error
         .as_ref()
         .map(|(status_code, client_error)| {
+            let retry_after_sec = match client_error {
+                web::ClientError::RATE_LIMITED { retry_after_sec } => Some(*retry_after_sec),
+                _ => None,
+            };
             let client_error = to_value(client_error).ok();
             let message = client_error.as_ref().and_then(|v| v.get("message"));
             let detail = client_error.as_ref().and_then(|v| v.get("detail"));
@@ -52,21 +70,32 @@ pub async fn mw_response_map(
             debug!("CLIENT ERROR BODY:\n{client_error_body}");
 
             // Build the new response from the client body
-            (*status_code, Json(client_error_body)).into_response()
+            let mut response = (*status_code, Json(client_error_body)).into_response();
+
+            if let Some(retry_after_sec) = retry_after_sec {
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after_sec));
+            }
+
+            response
         });
 
     // -- Build and log the server log line
+    let http_status = error_response.as_ref().unwrap_or(&res).status();
     let client_error = client_status_error.unzip().1;
     let _ = log_request(
-        uuid,
+        &logger,
+        req_stamp,
         req_method,
         uri,
+        http_status,
+        client_info.ip,
         rpc_info,
         ctx,
         web_error,
         client_error,
-    )
-    .await;
+    );
 
     debug!("\n");
     error_response.unwrap_or(res)
@@ -0,0 +1,236 @@
+//! Probe routes for the orchestrator (no auth, no rate limit, not logged).
+//!
+//! - `GET /health/live` the process is up (and serving).
+//! - `GET /health/ready` the config files are readable, and the db can be queried with
+//!   the schema applied (at the expected version). `503` when any component is down.
+//! - `GET /version` build info.
+//!
+//! Note: The check errors are logged, but not returned (public routes).
+
+use crate::config::web_config;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use lib_core::model::ModelManager;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+use tracing::error;
+
+/// Max time for each readiness check (probes usually time out in a few seconds).
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub fn routes(mm: ModelManager) -> Router {
+    Router::new()
+        .route("/health/live", get(live_handler))
+        .route("/health/ready", get(ready_handler))
+        .route("/version", get(version_handler))
+        .with_state(mm)
+}
+
+async fn live_handler() -> Json<Value> {
+    Json(json!({ "status": "up" }))
+}
+
+async fn ready_handler(State(mm): State<ModelManager>) -> (StatusCode, Json<Value>) {
+    let config = check("config", check_config_files()).await;
+    let db = check("db", mm.ping()).await;
+    let schema = check("schema", mm.check_schema()).await;
+
+    let ready = config.is_up() && db.is_up() && schema.is_up();
+    let status_code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let body = json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "components": {
+            "config": config,
+            "db": db,
+            "schema": schema,
+        }
+    });
+
+    (status_code, Json(body))
+}
+
+async fn version_handler() -> Json<Value> {
+    Json(json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_sha": option_env!("BUILD_GIT_SHA"),
+        "profile": if cfg!(debug_assertions) { "debug" } else { "release" },
+    }))
+}
+
+/// The config files and dirs exist (e.g., a TLS cert volume not mounted).
+async fn check_config_files() -> Result<(), String> {
+    let config = web_config();
+
+    let mut paths = vec![config.WEB_FOLDER.as_str()];
+    if let Some(tls_files) = &config.TLS_FILES {
+        paths.extend([tls_files.cert.as_str(), tls_files.key.as_str()]);
+    }
+
+    for path in paths {
+        tokio::fs::metadata(path)
+            .await
+            .map_err(|ex| format!("'{path}' - {ex}"))?;
+    }
+
+    Ok(())
+}
+
+// region:    --- Component Status
+
+#[derive(Serialize)]
+struct ComponentStatus {
+    status: &'static str,
+    latency_ms: f64,
+}
+
+impl ComponentStatus {
+    fn is_up(&self) -> bool {
+        self.status == "up"
+    }
+}
+
+/// Run the check, timing it (down when over `CHECK_TIMEOUT`).
+async fn check<E: std::fmt::Display>(
+    component: &'static str,
+    fut: impl Future<Output = Result<(), E>>,
+) -> ComponentStatus {
+    let start = Instant::now();
+    let res = timeout(CHECK_TIMEOUT, fut).await;
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.;
+
+    let status = match res {
+        Ok(Ok(())) => "up",
+        Ok(Err(ex)) => {
+            error!("{:<12} - {component} down - {ex}", "HEALTH");
+            "down"
+        }
+        Err(_) => {
+            error!(
+                "{:<12} - {component} down - timed out after {}s",
+                "HEALTH",
+                CHECK_TIMEOUT.as_secs()
+            );
+            "down"
+        }
+    };
+
+    ComponentStatus { status, latency_ms }
+}
+
+// endregion: --- Component Status
+
+// region:    --- Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use axum::body::Body;
+    use axum::http::Request;
+    use lib_core::_dev_utils;
+    use tower::ServiceExt;
+
+    async fn exec_get(mm: ModelManager, uri: &str) -> Result<(StatusCode, Value)> {
+        let req = Request::get(uri).body(Body::empty())?;
+        let res = routes(mm).oneshot(req).await?;
+
+        let status = res.status();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+
+        Ok((status, serde_json::from_slice(&body)?))
+    }
+
+    /// Only the status and latency (the check errors are not returned).
+    fn assert_component_shape(body: &Value, component: &str) {
+        let value = &body["components"][component];
+        let keys: Vec<&str> = value
+            .as_object()
+            .map(|obj| obj.keys().map(|k| k.as_str()).collect())
+            .unwrap_or_default();
+        assert_eq!(keys, ["latency_ms", "status"], "{component} keys");
+        assert!(value["latency_ms"].is_f64(), "{component} latency_ms");
+    }
+
+    #[tokio::test]
+    async fn test_live_and_version_ok() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+
+        // -- Exec
+        let (live_status, live_body) = exec_get(mm.clone(), "/health/live").await?;
+        let (version_status, version_body) = exec_get(mm, "/version").await?;
+
+        // -- Check
+        assert_eq!(live_status, StatusCode::OK);
+        assert_eq!(live_body, json!({ "status": "up" }));
+        assert_eq!(version_status, StatusCode::OK);
+        assert_eq!(version_body["name"], "web-server");
+        assert_eq!(version_body["version"], env!("CARGO_PKG_VERSION"));
+        assert!(version_body["profile"].is_string());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ready_db_and_schema_up() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+
+        // -- Exec
+        let (status, body) = exec_get(mm, "/health/ready").await?;
+
+        // -- Check
+        assert_eq!(body["components"]["db"]["status"], "up");
+        assert_eq!(body["components"]["schema"]["status"], "up");
+        for component in ["config", "db", "schema"] {
+            assert_component_shape(&body, component);
+        }
+        // Note: The config status depends on the web folder relative to the test current dir.
+        let ready = body["components"]["config"]["status"] == "up";
+        assert_eq!(body["status"], if ready { "ready" } else { "not_ready" });
+        assert_eq!(
+            status,
+            if ready {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ready_db_down_503() -> Result<()> {
+        // -- Setup & Fixtures
+        let mm = _dev_utils::init_test().await;
+        mm.close().await;
+
+        // -- Exec
+        let (status, body) = exec_get(mm, "/health/ready").await?;
+
+        // -- Check
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "not_ready");
+        assert_eq!(body["components"]["db"]["status"], "down");
+        assert_eq!(body["components"]["schema"]["status"], "down");
+        for component in ["config", "db", "schema"] {
+            assert_component_shape(&body, component);
+        }
+
+        Ok(())
+    }
+}
+
+// endregion: --- Tests
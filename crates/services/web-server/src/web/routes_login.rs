@@ -1,10 +1,12 @@
-use crate::web::{self, remove_token_cookie, Error, Result};
+use crate::web::mw_auth::{mw_ctx_require, CtxW};
+use crate::web::{self, remove_token_cookie, ClientInfo, Error, Result};
 use axum::extract::State;
 use axum::routing::post;
-use axum::{Json, Router};
+use axum::{middleware, Json, Router};
 use lib_auth::pwd::{self, ContentToHash, SchemeStatus};
 use lib_core::ctx::Ctx;
-use lib_core::model::user::{UserBmc, UserForLogin};
+use lib_core::model::user::{UserBmc, UserForCreate, UserForLogin};
+use lib_core::model::user_session::UserSessionBmc;
 use lib_core::model::ModelManager;
 use serde::Deserialize;
 use serde_json::{json, Value};
@@ -12,9 +14,16 @@ use tower_cookies::Cookies;
 use tracing::debug;
 
 pub fn routes(mm: ModelManager) -> Router {
+    // Logoff ends the cookie session, so it goes through the ctx and CSRF check.
+    let routes_logoff = Router::new()
+        .route("/api/logoff", post(api_logoff_handler))
+        .route_layer(middleware::from_fn(mw_ctx_require));
+
     Router::new()
         .route("/api/login", post(api_login_handler))
-        .route("/api/logoff", post(api_logoff_handler))
+        .route("/api/register", post(api_register_handler))
+        .route("/api/pwd_change", post(api_pwd_change_handler))
+        .merge(routes_logoff)
         .with_state(mm)
 }
 
@@ -22,6 +31,7 @@ pub fn routes(mm: ModelManager) -> Router {
 async fn api_login_handler(
     State(mm): State<ModelManager>,
     cookies: Cookies,
+    client_info: ClientInfo,
     Json(payload): Json<LoginPayload>,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - api_login_handler", "HANDLER");
@@ -60,8 +70,16 @@ async fn api_login_handler(
         UserBmc::update_pwd(&route_ctx, &mm, user_id, &pwd_clear).await?;
     }
 
-    // -- Set the web token
-    web::set_token_cookie(&cookies, &user.username, user.token_salt)?;
+    // -- Start the user session (sets the web token)
+    web::start_user_session(
+        &mm,
+        &cookies,
+        client_info,
+        user_id,
+        &user.username,
+        user.token_salt,
+    )
+    .await?;
 
     // Create the success body.
     let body = Json(json!({
@@ -82,6 +100,8 @@ struct LoginPayload {
 
 // region:    --- Logoff
 async fn api_logoff_handler(
+    State(mm): State<ModelManager>,
+    CtxW(ctx): CtxW,
     cookies: Cookies,
     Json(payload): Json<LogoffPayload>,
 ) -> Result<Json<Value>> {
@@ -89,6 +109,11 @@ async fn api_logoff_handler(
     let should_logoff = payload.logoff;
 
     if should_logoff {
+        // -- End the user session
+        if let Some(session_id) = ctx.session_id() {
+            UserSessionBmc::delete_for_user(&ctx, &mm, session_id, ctx.user_id()).await?;
+        }
+
         remove_token_cookie(&cookies)?;
     }
 
@@ -107,3 +132,100 @@ struct LogoffPayload {
     logoff: bool,
 }
 // endregion: --- Logoff
+
+// region:    --- Register
+async fn api_register_handler(
+    State(mm): State<ModelManager>,
+    Json(payload): Json<RegisterPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_register_handler", "HANDLER");
+
+    let RegisterPayload {
+        username,
+        password: pwd_clear,
+    } = payload;
+    let route_ctx = Ctx::root_ctx();
+
+    // -- Create the user (enforces the password policy)
+    UserBmc::create(
+        &route_ctx,
+        &mm,
+        UserForCreate {
+            username,
+            pwd_clear,
+        },
+    )
+    .await?;
+
+    // Create the success body.
+    let body = Json(json!({
+        "result": {
+            "success": true
+        }
+    }));
+
+    Ok(body)
+}
+
+#[derive(Debug, Deserialize)]
+struct RegisterPayload {
+    username: String,
+    password: String,
+}
+// endregion: --- Register
+
+// region:    --- Pwd Change
+async fn api_pwd_change_handler(
+    State(mm): State<ModelManager>,
+    Json(payload): Json<PwdChangePayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_pwd_change_handler", "HANDLER");
+
+    let PwdChangePayload {
+        username,
+        password: pwd_clear,
+        new_password: new_pwd_clear,
+    } = payload;
+    let route_ctx = Ctx::root_ctx();
+
+    // -- Get the user
+    let user: UserForLogin = UserBmc::first_by_username(&route_ctx, &mm, &username)
+        .await?
+        .ok_or(Error::LoginFailUsernameNotFound)?;
+    let user_id = user.id;
+
+    // -- Validate the current password
+    let Some(pwd) = user.pwd else {
+        return Err(Error::LoginFailUserHasNoPwd { user_id });
+    };
+
+    pwd::validate_pwd(
+        ContentToHash {
+            salt: user.pwd_salt,
+            content: pwd_clear,
+        },
+        &pwd,
+    )
+    .await
+    .map_err(|_| Error::LoginFailPwdNotMatching { user_id })?;
+
+    // -- Change the password (enforces the password policy)
+    UserBmc::change_pwd(&route_ctx, &mm, user_id, &new_pwd_clear).await?;
+
+    // Create the success body.
+    let body = Json(json!({
+        "result": {
+            "success": true
+        }
+    }));
+
+    Ok(body)
+}
+
+#[derive(Debug, Deserialize)]
+struct PwdChangePayload {
+    username: String,
+    password: String,
+    new_password: String,
+}
+// endregion: --- Pwd Change
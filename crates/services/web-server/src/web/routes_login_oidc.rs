@@ -0,0 +1,145 @@
+//! OIDC (SSO) login routes, beside the `routes_login` username/password ones.
+//!
+//! - `GET /api/oidc/login` redirects to the provider, keeping the `AuthRequest`
+//!   (state, nonce, PKCE verifier) in a short lived cookie.
+//! - `GET /api/oidc/callback` validates the state, exchanges the code, links or
+//!   provisions the user by subject, and sets the web token cookie.
+
+use crate::config::web_config;
+use crate::web::{self, ClientInfo, Error, Result};
+use axum::extract::{Query, State};
+use axum::response::Redirect;
+use axum::routing::get;
+use axum::Router;
+use lib_auth::oidc::{AuthRequest, IdTokenClaims, OidcClient};
+use lib_core::ctx::Ctx;
+use lib_core::model::user::{UserBmc, UserForAuth, UserForOidc};
+use lib_core::model::ModelManager;
+use serde::Deserialize;
+use std::sync::Arc;
+use tower_cookies::cookie::time::Duration;
+use tower_cookies::cookie::SameSite;
+use tower_cookies::{Cookie, Cookies};
+use tracing::debug;
+
+const OIDC_AUTH_REQUEST: &str = "oidc-auth-request";
+const OIDC_COOKIE_PATH: &str = "/api/oidc";
+
+#[derive(Clone)]
+pub struct OidcState {
+    mm: ModelManager,
+    oidc: Arc<OidcClient>,
+}
+
+pub fn routes(mm: ModelManager, oidc: Arc<OidcClient>) -> Router {
+    Router::new()
+        .route("/api/oidc/login", get(api_oidc_login_handler))
+        .route("/api/oidc/callback", get(api_oidc_callback_handler))
+        .with_state(OidcState { mm, oidc })
+}
+
+// region:    --- Login
+async fn api_oidc_login_handler(
+    State(state): State<OidcState>,
+    cookies: Cookies,
+) -> Result<Redirect> {
+    debug!("{:<12} - api_oidc_login_handler", "HANDLER");
+
+    let auth_req = AuthRequest::new();
+    let authorize_url = state.oidc.authorize_url(&auth_req)?;
+
+    // Lax, so that it is sent on the top-level redirect back from the provider.
+    let mut cookie = Cookie::new(OIDC_AUTH_REQUEST, auth_req.to_string());
+    cookie.set_http_only(true);
+    cookie.set_path(OIDC_COOKIE_PATH);
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_secure(web_config().COOKIE_SECURE);
+    cookie.set_max_age(Duration::minutes(10));
+    cookies.add(cookie);
+
+    Ok(Redirect::to(&authorize_url))
+}
+// endregion: --- Login
+
+// region:    --- Callback
+async fn api_oidc_callback_handler(
+    State(state): State<OidcState>,
+    cookies: Cookies,
+    client_info: ClientInfo,
+    Query(params): Query<CallbackParams>,
+) -> Result<Redirect> {
+    debug!("{:<12} - api_oidc_callback_handler", "HANDLER");
+
+    // -- Get the auth request (single use)
+    let auth_req = cookies
+        .get(OIDC_AUTH_REQUEST)
+        .map(|c| c.value().to_string())
+        .ok_or(Error::OidcAuthRequestNotInCookie)?;
+    let mut cookie = Cookie::from(OIDC_AUTH_REQUEST);
+    cookie.set_path(OIDC_COOKIE_PATH);
+    cookies.remove(cookie);
+    let auth_req: AuthRequest = auth_req.parse()?;
+
+    // -- Check the provider response
+    if let Some(error) = params.error {
+        return Err(Error::OidcProviderFail(error));
+    }
+    if params.state.as_deref() != Some(auth_req.state.as_str()) {
+        return Err(Error::OidcStateNotMatching);
+    }
+    let code = params.code.ok_or(Error::OidcCodeMissing)?;
+
+    // -- Exchange the code (validates the ID token)
+    let claims = state.oidc.exchange_code(&code, &auth_req).await?;
+
+    // -- Get, link, or provision the user
+    let user: UserForAuth =
+        UserBmc::first_or_create_by_oidc(&Ctx::root_ctx(), &state.mm, user_for_oidc(claims))
+            .await?;
+
+    // -- Start the user session (sets the web token)
+    web::start_user_session(
+        &state.mm,
+        &cookies,
+        client_info,
+        user.id,
+        &user.username,
+        user.token_salt,
+    )
+    .await?;
+
+    Ok(Redirect::to("/"))
+}
+
+#[derive(Debug, Deserialize)]
+struct CallbackParams {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+/// Verified email is the username (and can be linked to an existing user),
+/// otherwise `preferred_username` (or `sub`) is used for provisioning only.
+fn user_for_oidc(claims: IdTokenClaims) -> UserForOidc {
+    let IdTokenClaims {
+        iss,
+        sub,
+        email,
+        email_verified,
+        preferred_username,
+        ..
+    } = claims;
+
+    let (username, link_by_username) = match email {
+        Some(email) if email_verified => (email, true),
+        _ => (preferred_username.unwrap_or_else(|| sub.clone()), false),
+    };
+
+    UserForOidc {
+        issuer: iss,
+        subject: sub,
+        username,
+        link_by_username,
+    }
+}
+// endregion: --- Callback
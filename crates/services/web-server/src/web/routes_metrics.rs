@@ -0,0 +1,55 @@
+//! `GET /metrics` in the Prometheus text format (no auth, outside of the middlewares).
+//!
+//! The metrics are recorded with the `metrics` facade (e.g., `mw_metrics`,
+//! `lib_auth::pwd`), and the db pool gauges are sampled on each scrape.
+
+use crate::{Error, Result};
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use lib_core::model::ModelManager;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+/// Gauges of the db pool connections.
+const DB_POOL_SIZE_METRIC: &str = "db_pool_connections";
+const DB_POOL_IDLE_METRIC: &str = "db_pool_connections_idle";
+const DB_POOL_MAX_METRIC: &str = "db_pool_connections_max";
+
+/// Histogram buckets (seconds) for all of the duration histograms.
+const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10.,
+];
+
+#[derive(Clone)]
+struct MetricsState {
+    handle: PrometheusHandle,
+    mm: ModelManager,
+}
+
+/// Install the global Prometheus recorder. To be called once, at startup.
+pub fn install_recorder() -> Result<PrometheusHandle> {
+    PrometheusBuilder::new()
+        .set_buckets(DURATION_BUCKETS)
+        .and_then(|builder| builder.install_recorder())
+        .map_err(|ex| Error::MetricsRecorder(ex.to_string()))
+}
+
+pub fn routes(handle: PrometheusHandle, mm: ModelManager) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(MetricsState { handle, mm })
+}
+
+async fn metrics_handler(State(state): State<MetricsState>) -> impl IntoResponse {
+    let pool = state.mm.db_pool_status();
+    metrics::gauge!(DB_POOL_SIZE_METRIC).set(pool.size);
+    metrics::gauge!(DB_POOL_IDLE_METRIC).set(pool.idle as f64);
+    metrics::gauge!(DB_POOL_MAX_METRIC).set(pool.max);
+
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.handle.render(),
+    )
+}
@@ -15,7 +15,7 @@ use axum::{
 };
 use serde_json::{json, Value};
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, Span};
 
 // endregion: --- Modules
 
@@ -37,6 +37,7 @@ async fn rpc_handler(
         id: rpc_req.id.clone(),
         method: rpc_req.method.clone(),
     };
+    Span::current().record("rpc_method", &rpc_info.method);
 
     // -- Exec & Store RpcInfo in reponse.
     let mut response = _rpc_handler(ctx, mm, rpc_req).await.into_response();
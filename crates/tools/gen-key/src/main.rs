@@ -1,7 +1,10 @@
 use anyhow::Result;
 use lib_utils::b64::b64u_encode;
+use lib_utils::time::now_utc;
 use rand::RngCore;
 
+// Usage: `cargo run -p gen-key -- [key_id]`
+// (default key_id is `k` + today's date, e.g., `k20240615`)
 fn main() -> Result<()> {
     let mut key = [0u8; 64]; // 512 bits = 64 bytes
     rand::thread_rng().fill_bytes(&mut key);
@@ -10,5 +13,25 @@ fn main() -> Result<()> {
     let b64u = b64u_encode(key);
     println!("\nKey b64u encoded:\n{b64u}");
 
+    // -- Keyring entry
+    // To rotate, prepend it to the keyring (e.g., SERVICE_TOKEN_KEYRING), so it becomes the
+    // current key while the previous keys stay valid for validation.
+    let key_id = std::env::args().nth(1).unwrap_or_else(|| {
+        let today = now_utc().date();
+        format!(
+            "k{}{:02}{:02}",
+            today.year(),
+            u8::from(today.month()),
+            today.day()
+        )
+    });
+    if !key_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        anyhow::bail!("key_id '{key_id}' should only contain [A-Za-z0-9_-]");
+    }
+    println!("\nKeyring entry:\n{key_id}:{b64u}");
+
     Ok(())
 }
@@ -10,13 +10,161 @@ username varchar(128) NOT NULL UNIQUE,
 -- Auth
 pwd varchar(256),
 pwd_salt uuid NOT NULL DEFAULT gen_random_uuid(),
-token_salt uuid NOT NULL DEFAULT gen_random_uuid()
+token_salt uuid NOT NULL DEFAULT gen_random_uuid(),
+
+-- Authorization
+is_admin bool NOT NULL DEFAULT false,
+
+-- Oidc (SSO) identity
+oidc_iss varchar(256),
+oidc_sub varchar(256),
+UNIQUE (oidc_iss, oidc_sub)
+);
+
+-- User Session
+CREATE TABLE user_session (
+id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+user_id BIGINT NOT NULL REFERENCES "user"(id) ON DELETE CASCADE,
+
+user_agent varchar(512),
+ip varchar(64),
+ctime timestamp with time zone NOT NULL DEFAULT now(),
+last_seen timestamp with time zone NOT NULL DEFAULT now()
 );
 
 -- Task
+CREATE TYPE task_status AS ENUM ('todo', 'in_progress', 'blocked', 'done');
+CREATE TYPE task_priority AS ENUM ('low', 'medium', 'high', 'urgent');
+
 CREATE TABLE task (
 id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
 
 title varchar(256) NOT NULL,
-done bool NOT NULL DEFAULT false
+description text,
+due_date date,
+priority task_priority NOT NULL DEFAULT 'medium',
+status task_status NOT NULL DEFAULT 'todo',
+-- Derived from the status (kept for the clients filtering on it)
+done bool NOT NULL GENERATED ALWAYS AS (status = 'done') STORED,
+assignee_id BIGINT REFERENCES "user"(id) ON DELETE SET NULL,
+-- The creator (none when created by the system, with the root ctx)
+owner_id BIGINT REFERENCES "user"(id) ON DELETE SET NULL,
+-- Free form project name (for the grouping and filtering)
+project varchar(128),
+-- Subtask of the parent (the children become top tasks when the parent is purged)
+parent_id BIGINT REFERENCES task(id) ON DELETE SET NULL,
+-- Recurrence rule (RRULE subset, e.g., 'FREQ=DAILY'), moved to the next occurrence on done
+recurrence varchar(256),
+
+-- Optimistic concurrency (incremented on each update)
+version BIGINT NOT NULL DEFAULT 1,
+
+ctime timestamp with time zone NOT NULL DEFAULT now(),
+
+-- Soft delete (purged after the retention)
+deleted_at timestamp with time zone,
+
+-- Full-text search (title weighted over description)
+search_tsv tsvector GENERATED ALWAYS AS (
+  setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+  setweight(to_tsvector('english', coalesce(description, '')), 'B')
+) STORED
+);
+CREATE INDEX task_search_idx ON task USING GIN (search_tsv);
+CREATE INDEX task_parent_id_idx ON task (parent_id);
+CREATE INDEX task_owner_id_idx ON task (owner_id);
+CREATE INDEX task_project_idx ON task (project);
+
+-- Task Dependency (the task is blocked by the depends_on task)
+CREATE TABLE task_dependency (
+task_id BIGINT NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+depends_on_id BIGINT NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+PRIMARY KEY (task_id, depends_on_id),
+CHECK (task_id <> depends_on_id)
+);
+CREATE INDEX task_dependency_depends_on_id_idx ON task_dependency (depends_on_id);
+
+-- Comment (threaded with the parent_id, soft deleted so that the replies keep their parent)
+-- Note: The comments outlive their author (and the replies their purged parent).
+CREATE TABLE comment (
+id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+task_id BIGINT NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+author_id BIGINT REFERENCES "user"(id) ON DELETE SET NULL,
+parent_id BIGINT REFERENCES comment(id) ON DELETE SET NULL,
+
+body text NOT NULL,
+
+-- Optimistic concurrency (incremented on each update)
+version BIGINT NOT NULL DEFAULT 1,
+
+ctime timestamp with time zone NOT NULL DEFAULT now(),
+mtime timestamp with time zone NOT NULL DEFAULT now(),
+
+deleted_at timestamp with time zone
+);
+CREATE INDEX comment_task_id_idx ON comment (task_id);
+
+-- Label (and the task labels)
+CREATE TABLE label (
+id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+name varchar(128) NOT NULL UNIQUE,
+color varchar(32)
+);
+
+CREATE TABLE task_label (
+task_id BIGINT NOT NULL REFERENCES task(id) ON DELETE CASCADE,
+label_id BIGINT NOT NULL REFERENCES label(id) ON DELETE CASCADE,
+PRIMARY KEY (task_id, label_id)
+);
+CREATE INDEX task_label_label_id_idx ON task_label (label_id);
+
+-- Request Log (no FK on user_id, so that the logs outlive the users)
+CREATE TABLE request_log (
+id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+uuid uuid NOT NULL,
+timestamp timestamp with time zone NOT NULL,
+duration_ms double precision NOT NULL,
+
+user_id BIGINT,
+client_ip varchar(64),
+
+http_method varchar(16) NOT NULL,
+http_path varchar(2048) NOT NULL,
+http_status integer NOT NULL,
+
+rpc_id varchar(256),
+rpc_method varchar(256),
+
+client_error_type varchar(256),
+error_type varchar(256),
+error_data jsonb
+);
+
+-- Audit Log (no FK on user_id nor entity_id, so that the entries outlive them)
+CREATE TABLE audit_log (
+id BIGINT GENERATED BY DEFAULT AS IDENTITY (START WITH 1000) PRIMARY KEY,
+
+ctime timestamp with time zone NOT NULL DEFAULT now(),
+user_id BIGINT NOT NULL,
+req_id uuid,
+
+entity varchar(128) NOT NULL,
+entity_id BIGINT NOT NULL,
+op varchar(16) NOT NULL,
+
+old_values jsonb,
+new_values jsonb
+);
+CREATE INDEX audit_log_entity_idx ON audit_log (entity, entity_id, ctime);
+
+-- Schema Version (one row per applied version, checked by the readiness probe)
+-- Note: Bump it (here and in the model layer `SCHEMA_VERSION`) on any schema change.
+CREATE TABLE schema_version (
+version integer PRIMARY KEY,
+applied_at timestamp with time zone NOT NULL DEFAULT now()
 );
+INSERT INTO schema_version (version) VALUES (3);
@@ -1,2 +1,2 @@
--- User demo1
-INSERT INTO "user" (username) VALUES ('demo1');
+-- User demo1 (admin)
+INSERT INTO "user" (username, is_admin) VALUES ('demo1', true);